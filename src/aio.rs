@@ -0,0 +1,579 @@
+//! The aio module holds the clients built on tokio, enabled by the aio feature.
+//!
+//! Their commands return futures instead of calling callbacks. The requests of every clone of a MultiplexedClient
+//! are written on one connection as soon as they are made, so the concurrent ones are pipelined, and a task spawned
+//! on the runtime reads the responses and hands them to their futures in order.
+//!
+//! They don't reconnect: once the connection dropped, the futures and the stream of messages end with an error
+//! or with None, and a new client has to be created.
+//!
+//! The connect timeout, TCP_NODELAY and the keepalive of the ConnectionConfig are applied, but the read and the write
+//! timeouts are not: wrap the futures in `tokio::time::timeout` to limit them. TLS connections are not supported,
+//! connecting with a configuration enabling TLS fails.
+extern crate futures_core;
+extern crate socket2;
+extern crate tokio;
+
+use args::ToRedisArgs;
+use commands::RedisCommand;
+use config::ConnectionConfig;
+use errors::RedisError;
+use pubsub::PubSubMessage;
+use reader::Decoder;
+use redis::PubSubFrame;
+use results::{FromRedisValue, RedisResult};
+use self::futures_core::Stream;
+use self::socket2::{SockRef, TcpKeepalive};
+use self::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use self::tokio::net::TcpStream;
+use self::tokio::sync::{mpsc, oneshot};
+use self::tokio::time::Sleep;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use types::ProtocolVersion;
+
+type Replies = Result<Vec<RedisResult>, RedisError>;
+
+/// The bytes of one or more commands, sent to the connection task with the number of responses they expect
+struct Request {
+    bytes: Vec<u8>,
+    reply_nb: usize,
+    sender: oneshot::Sender<Replies>,
+}
+
+/// A request written on the connection and waiting for its responses
+struct PendingRequest {
+    reply_nb: usize,
+    replies: Vec<RedisResult>,
+    sender: oneshot::Sender<Replies>,
+}
+
+/// The sending side of a connection, and the messages it receives when it is subscribed
+struct Connection {
+    sender: mpsc::UnboundedSender<Request>,
+    messages: Option<mpsc::UnboundedReceiver<PubSubMessage>>,
+}
+
+/// Send the bytes of a request to the connection task and return the future of its responses
+fn send_request<T>(sender: &mpsc::UnboundedSender<Request>, bytes: Vec<u8>, reply_nb: usize,
+                   convert: fn(Vec<RedisResult>) -> Result<T, RedisError>) -> RedisFuture<T> {
+    let (reply_sender, receiver) = oneshot::channel();
+    if reply_nb == 0 {
+        let _ = reply_sender.send(Ok(Vec::new()));
+    } else if let Err(mpsc::error::SendError(request)) = sender.send(Request { bytes, reply_nb, sender: reply_sender }) {
+        let _ = request.sender.send(Err(connection_closed()));
+    }
+    RedisFuture { receiver, convert }
+}
+
+/// Return a future already failed with the error
+fn failed_request<T>(err: RedisError) -> RedisFuture<T> {
+    let (reply_sender, receiver) = oneshot::channel();
+    let _ = reply_sender.send(Err(err));
+    RedisFuture { receiver, convert: |_| Err(connection_closed()) }
+}
+
+fn connection_closed() -> RedisError {
+    RedisError::Io(io::Error::new(io::ErrorKind::BrokenPipe, "the connection is closed"))
+}
+
+/// Convert the response of a single command, an error response being returned as an Err
+fn convert_reply<T: FromRedisValue>(mut replies: Vec<RedisResult>) -> Result<T, RedisError> {
+    match replies.pop() {
+        Some(RedisResult::Error(err)) => Err(RedisError::Response(err)),
+        Some(reply) => reply.try_convert::<T>(),
+        None => RedisResult::Nil.try_convert::<T>(),
+    }
+}
+
+/// Keep the responses of a pipeline as they are, the error responses included
+fn convert_replies(replies: Vec<RedisResult>) -> Result<Vec<RedisResult>, RedisError> {
+    Ok(replies)
+}
+
+/// Succeed only if none of the responses is an error
+fn check_replies(replies: Vec<RedisResult>) -> Result<(), RedisError> {
+    for reply in replies {
+        if let RedisResult::Error(err) = reply {
+            return Err(RedisError::Response(err));
+        }
+    }
+    Ok(())
+}
+
+/// The future of the responses of a request, converted to T once they are all received.
+///
+/// The request is sent as soon as the future is created: it doesn't need to be polled to be executed.
+#[must_use = "the future needs to be awaited to get the response"]
+pub struct RedisFuture<T> {
+    receiver: oneshot::Receiver<Replies>,
+    convert: fn(Vec<RedisResult>) -> Result<T, RedisError>,
+}
+
+impl<T> Future for RedisFuture<T> {
+    type Output = Result<T, RedisError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T, RedisError>> {
+        match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(Ok(Ok(replies))) => Poll::Ready((self.convert)(replies)),
+            Poll::Ready(Ok(Err(err))) => Poll::Ready(Err(err)),
+            Poll::Ready(Err(_)) => Poll::Ready(Err(connection_closed())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> fmt::Debug for RedisFuture<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RedisFuture")
+    }
+}
+
+/// The task owning a connection: it writes the requests, reads the responses and routes them to the pending requests,
+/// or to the stream of messages when the connection is subscribed
+struct ConnectionDriver {
+    stream: TcpStream,
+    requests: mpsc::UnboundedReceiver<Request>,
+    requests_closed: bool,
+    write_buffer: Vec<u8>,
+    pending: VecDeque<PendingRequest>,
+    decoder: Decoder,
+    protocol: ProtocolVersion,
+    messages: Option<mpsc::UnboundedSender<PubSubMessage>>,
+}
+
+impl ConnectionDriver {
+    /// Spawn the task of a connection on the current runtime
+    fn spawn(stream: TcpStream, protocol: ProtocolVersion, subscribed: bool) -> Connection {
+        let (sender, requests) = mpsc::unbounded_channel();
+        let (messages, receiver) = if subscribed {
+            let (messages, receiver) = mpsc::unbounded_channel();
+            (Some(messages), Some(receiver))
+        } else {
+            (None, None)
+        };
+
+        tokio::spawn(ConnectionDriver {
+            stream,
+            requests,
+            requests_closed: false,
+            write_buffer: Vec::new(),
+            pending: VecDeque::new(),
+            decoder: Decoder::new(),
+            protocol,
+            messages,
+        });
+        Connection { sender, messages: receiver }
+    }
+
+    /// Move the bytes of the new requests to the write buffer, so the ones made meanwhile are written together
+    fn receive_requests(&mut self, cx: &mut Context) {
+        while !self.requests_closed {
+            match self.requests.poll_recv(cx) {
+                Poll::Ready(Some(request)) => {
+                    self.write_buffer.extend_from_slice(&request.bytes);
+                    self.pending.push_back(PendingRequest {
+                        reply_nb: request.reply_nb,
+                        replies: Vec::with_capacity(request.reply_nb),
+                        sender: request.sender,
+                    });
+                },
+                Poll::Ready(None) => self.requests_closed = true,
+                Poll::Pending => return,
+            };
+        }
+    }
+
+    fn write_requests(&mut self, cx: &mut Context) -> Result<(), RedisError> {
+        while !self.write_buffer.is_empty() {
+            match Pin::new(&mut self.stream).poll_write(cx, &self.write_buffer) {
+                Poll::Ready(Ok(0)) => return Err(RedisError::Io(io::Error::new(io::ErrorKind::WriteZero, "the connection is closed"))),
+                Poll::Ready(Ok(size)) => {
+                    self.write_buffer.drain(..size);
+                },
+                Poll::Ready(Err(err)) => return Err(RedisError::Io(err)),
+                Poll::Pending => return Ok(()),
+            };
+        }
+        Ok(())
+    }
+
+    fn read_responses(&mut self, cx: &mut Context) -> Result<(), RedisError> {
+        let mut chunk = [0; 4096];
+        loop {
+            let mut read_buffer = ReadBuf::new(&mut chunk);
+            match Pin::new(&mut self.stream).poll_read(cx, &mut read_buffer) {
+                Poll::Ready(Ok(())) => {
+                    if read_buffer.filled().is_empty() {
+                        return Err(RedisError::Io(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed by the server")));
+                    }
+                    self.decoder.feed(read_buffer.filled());
+                    self.dispatch_responses()?;
+                },
+                Poll::Ready(Err(err)) => return Err(RedisError::Io(err)),
+                Poll::Pending => return Ok(()),
+            };
+        }
+    }
+
+    /// Route every complete response received so far
+    fn dispatch_responses(&mut self) -> Result<(), RedisError> {
+        loop {
+            let response = match self.decoder.decode() {
+                Ok(Some(response)) => response,
+                Ok(None) => return Ok(()),
+                Err(RedisError::Response(err)) => RedisResult::Error(err),
+                Err(err) => return Err(err),
+            };
+
+            match self.messages {
+                Some(ref messages) => match PubSubFrame::classify(response, self.protocol) {
+                    PubSubFrame::Message(message) | PubSubFrame::ShardMessage(message) => {
+                        let _ = messages.send(message);
+                    },
+                    PubSubFrame::Confirmation(_, _, _, frame) | PubSubFrame::Reply(frame) => self.add_reply(frame),
+                    PubSubFrame::Ignored => {},
+                },
+                None => match response {
+                    RedisResult::Push(_) => {},
+                    response => self.add_reply(response),
+                },
+            };
+        }
+    }
+
+    /// Give a response to the oldest pending request, completing it once it got all its responses.
+    /// On a subscribed connection an error ends the request, as redis answers a failed subscription with a single error.
+    fn add_reply(&mut self, reply: RedisResult) {
+        let subscribed = self.messages.is_some();
+        let complete = match self.pending.front_mut() {
+            Some(pending) => {
+                let is_error = matches!(reply, RedisResult::Error(_));
+                pending.replies.push(reply);
+                pending.replies.len() >= pending.reply_nb || (subscribed && is_error)
+            },
+            None => false,
+        };
+
+        if complete {
+            if let Some(pending) = self.pending.pop_front() {
+                let _ = pending.sender.send(Ok(pending.replies));
+            }
+        }
+    }
+
+    /// End the pending requests and the ones not received yet with the error
+    fn fail_requests(&mut self, err: RedisError) {
+        self.requests.close();
+        while let Ok(request) = self.requests.try_recv() {
+            let _ = request.sender.send(Err(err.clone()));
+        }
+        for pending in self.pending.drain(..) {
+            let _ = pending.sender.send(Err(err.clone()));
+        }
+    }
+}
+
+impl Future for ConnectionDriver {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let driver = &mut *self;
+        driver.receive_requests(cx);
+
+        let result = driver.write_requests(cx).and_then(|_| driver.read_responses(cx));
+        if let Err(err) = result {
+            driver.fail_requests(err);
+            return Poll::Ready(());
+        }
+
+        if driver.requests_closed && driver.pending.is_empty() && driver.write_buffer.is_empty() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+enum ConnectState {
+    Connecting(Pin<Box<dyn Future<Output = io::Result<TcpStream>> + Send>>, Option<Pin<Box<Sleep>>>),
+    Initializing(Connection, RedisFuture<()>),
+    Failed(RedisError),
+    Done,
+}
+
+/// The future of a client being connected: it opens the connection, then authenticates, selects the database
+/// and sets the client name if the configuration requires it.
+#[must_use = "the future needs to be awaited to get the client"]
+pub struct Connect<T> {
+    config: ConnectionConfig,
+    subscribed: bool,
+    state: ConnectState,
+    build: fn(Connection, ConnectionConfig) -> T,
+}
+
+impl<T> Connect<T> {
+    fn new(config: &ConnectionConfig, subscribed: bool, build: fn(Connection, ConnectionConfig) -> T) -> Connect<T> {
+        let state = if config.get_tls() {
+            ConnectState::Failed(RedisError::Io(io::Error::new(io::ErrorKind::Unsupported, "the aio clients don't support TLS connections")))
+        } else {
+            let address = (config.get_host().to_string(), config.get_port());
+            ConnectState::Connecting(Box::pin(TcpStream::connect(address)), None)
+        };
+
+        Connect { config: config.clone(), subscribed, state, build }
+    }
+
+    /// Apply the socket options of the configuration, start the connection task and send the init commands
+    fn start(&self, stream: TcpStream) -> Result<ConnectState, RedisError> {
+        stream.set_nodelay(self.config.get_nodelay())?;
+        if let Some(keepalive) = self.config.get_keepalive() {
+            SockRef::from(&stream).set_tcp_keepalive(&TcpKeepalive::new().with_time(keepalive))?;
+        }
+
+        let connection = ConnectionDriver::spawn(stream, self.config.get_protocol(), self.subscribed);
+        let cmd = &mut self.config.get_init_commands();
+        let init = send_request(&connection.sender, cmd.into(), cmd.get_command_nb(), check_replies);
+        Ok(ConnectState::Initializing(connection, init))
+    }
+}
+
+impl<T> Future for Connect<T> {
+    type Output = Result<T, RedisError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T, RedisError>> {
+        let connect = &mut *self;
+        loop {
+            let next_state = match connect.state {
+                ConnectState::Connecting(ref mut tcp_connect, ref mut deadline) => {
+                    if deadline.is_none() {
+                        *deadline = connect.config.get_connect_timeout().map(|timeout| Box::pin(tokio::time::sleep(timeout)));
+                    }
+                    let stream = match tcp_connect.as_mut().poll(cx) {
+                        Poll::Ready(stream) => stream?,
+                        Poll::Pending => {
+                            if let Some(ref mut deadline) = *deadline {
+                                if deadline.as_mut().poll(cx).is_ready() {
+                                    return Poll::Ready(Err(RedisError::Io(io::Error::new(io::ErrorKind::TimedOut, "connection timed out"))));
+                                }
+                            }
+                            return Poll::Pending;
+                        },
+                    };
+                    connect.start(stream)?
+                },
+                ConnectState::Initializing(_, ref mut init) => {
+                    match Pin::new(init).poll(cx) {
+                        Poll::Ready(result) => result?,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    match mem::replace(&mut connect.state, ConnectState::Done) {
+                        ConnectState::Initializing(connection, _) => return Poll::Ready(Ok((connect.build)(connection, connect.config.clone()))),
+                        _ => unreachable!(),
+                    }
+                },
+                ConnectState::Failed(_) => match mem::replace(&mut connect.state, ConnectState::Done) {
+                    ConnectState::Failed(err) => return Poll::Ready(Err(err)),
+                    _ => unreachable!(),
+                },
+                ConnectState::Done => panic!("Connect polled after its completion"),
+            };
+            connect.state = next_state;
+        }
+    }
+}
+
+impl<T> fmt::Debug for Connect<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis Connect - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
+    }
+}
+
+/// A MultiplexedClient executes redis commands and returns futures of their responses. It implements CommandSenderFuture.
+///
+/// It is cheap to clone and all the clones share the same connection, so it can be given to as many tasks as needed.
+/// It has to be created within a tokio runtime.
+///
+/// ```no_run
+/// # extern crate redis_client;
+/// # extern crate tokio;
+/// # use redis_client::commands::CommandSenderFuture;
+/// # fn main() {}
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// let runtime = tokio::runtime::Runtime::new()?;
+/// let client = runtime.block_on(redis_client::aio::MultiplexedClient::new("127.0.0.1", "6379")?)?;
+/// // Both commands are sent before the first response is read
+/// let set = client.set::<String, _, _>("key", "value");
+/// let get = client.get::<Option<String>, _>("key");
+/// let value = runtime.block_on(get)?;
+/// # Ok(())}
+/// ```
+#[derive(Clone)]
+pub struct MultiplexedClient {
+    sender: mpsc::UnboundedSender<Request>,
+    config: ConnectionConfig,
+}
+
+impl MultiplexedClient {
+    pub fn new<H: ToString, P: ToString>(host: H, port: P) -> Result<Connect<MultiplexedClient>, RedisError> {
+        MultiplexedClient::new_with_protocol(host, port, ProtocolVersion::Resp2)
+    }
+
+    /// Create a MultiplexedClient speaking the given version of the redis protocol.
+    pub fn new_with_protocol<H: ToString, P: ToString>(host: H, port: P, protocol: ProtocolVersion)
+        -> Result<Connect<MultiplexedClient>, RedisError>
+    {
+        let mut config = ConnectionConfig::new(host, port.to_string().parse::<u16>()?);
+        config.set_protocol(protocol);
+        Ok(MultiplexedClient::with_config(&config))
+    }
+
+    /// Create a MultiplexedClient from a ConnectionConfig. The timeouts other than the connect timeout are not used:
+    /// the futures can be wrapped in tokio::time::timeout instead.
+    pub fn with_config(config: &ConnectionConfig) -> Connect<MultiplexedClient> {
+        Connect::new(config, false, |connection, config| MultiplexedClient { sender: connection.sender, config })
+    }
+
+    /// Tell whether the connection is still opened
+    pub fn is_connected(&self) -> bool {
+        !self.sender.is_closed()
+    }
+
+    pub fn get_config(&self) -> &ConnectionConfig {
+        &self.config
+    }
+
+    /// Execute a RedisCommand
+    pub fn exec_redis_command(&self, redis_command: &mut RedisCommand) -> RedisFuture<RedisResult> {
+        send_request(&self.sender, redis_command.into(), 1, convert_reply::<RedisResult>)
+    }
+
+    /// Execute a pipeline of RedisCommand, the error responses are kept among the others
+    pub fn exec_redis_pipeline_command(&self, redis_command: &mut RedisCommand) -> RedisFuture<Vec<RedisResult>> {
+        let cmd_nb = redis_command.get_command_nb();
+        send_request(&self.sender, redis_command.into(), cmd_nb, convert_replies)
+    }
+
+    /// Execute a RedisCommand and convert its response, used by CommandSenderFuture
+    pub(crate) fn send_command<R: FromRedisValue>(&self, redis_command: &mut RedisCommand) -> RedisFuture<R> {
+        send_request(&self.sender, redis_command.into(), 1, convert_reply::<R>)
+    }
+}
+
+impl fmt::Debug for MultiplexedClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis MultiplexedClient - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
+    }
+}
+
+impl fmt::Display for MultiplexedClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis MultiplexedClient - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
+    }
+}
+
+/// A PubSub is a connection in subscriber mode and a Stream of the messages it receives.
+///
+/// The futures of its subscription methods end once redis confirmed every channel or pattern.
+/// The stream ends when the connection drops.
+///
+/// ```no_run
+/// # extern crate redis_client;
+/// # extern crate tokio;
+/// # fn main() {}
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// let runtime = tokio::runtime::Runtime::new()?;
+/// let mut pubsub = runtime.block_on(redis_client::aio::PubSub::new("127.0.0.1", "6379")?)?;
+/// runtime.block_on(pubsub.subscribe(["foo", "bar"]))?;
+/// let message = runtime.block_on(std::future::poll_fn(|cx| pubsub.poll_message(cx)));
+/// # Ok(())}
+/// ```
+pub struct PubSub {
+    sender: mpsc::UnboundedSender<Request>,
+    messages: mpsc::UnboundedReceiver<PubSubMessage>,
+    config: ConnectionConfig,
+}
+
+impl PubSub {
+    pub fn new<H: ToString, P: ToString>(host: H, port: P) -> Result<Connect<PubSub>, RedisError> {
+        let config = ConnectionConfig::new(host, port.to_string().parse::<u16>()?);
+        Ok(PubSub::with_config(&config))
+    }
+
+    /// Create a PubSub from a ConnectionConfig
+    pub fn with_config(config: &ConnectionConfig) -> Connect<PubSub> {
+        Connect::new(config, true, |connection, config| PubSub {
+            sender: connection.sender,
+            messages: connection.messages.expect("a subscribed connection has a stream of messages"),
+            config,
+        })
+    }
+
+    /// Subscribe to one or more channels
+    pub fn subscribe<C: ToRedisArgs>(&self, channels: C) -> RedisFuture<()> {
+        self.change_subscriptions("SUBSCRIBE", channels)
+    }
+
+    /// Subscribe to one or more patterns
+    pub fn psubscribe<P: ToRedisArgs>(&self, patterns: P) -> RedisFuture<()> {
+        self.change_subscriptions("PSUBSCRIBE", patterns)
+    }
+
+    /// Unsubscribe from one or more channels
+    pub fn unsubscribe<C: ToRedisArgs>(&self, channels: C) -> RedisFuture<()> {
+        self.change_subscriptions("UNSUBSCRIBE", channels)
+    }
+
+    /// Unsubscribe from one or more patterns
+    pub fn punsubscribe<P: ToRedisArgs>(&self, patterns: P) -> RedisFuture<()> {
+        self.change_subscriptions("PUNSUBSCRIBE", patterns)
+    }
+
+    /// Poll for the next message, None meaning the connection dropped
+    pub fn poll_message(&mut self, cx: &mut Context) -> Poll<Option<PubSubMessage>> {
+        self.messages.poll_recv(cx)
+    }
+
+    /// Send a subscription command, expecting a confirmation for each of its channels or patterns.
+    /// They have to be named as the number of confirmations of a command with no argument is unknown.
+    fn change_subscriptions<C: ToRedisArgs>(&self, command: &str, names: C) -> RedisFuture<()> {
+        let names = names.to_redis_args();
+        if names.is_empty() {
+            return failed_request(RedisError::InvalidInput(format!("{} needs at least one channel or pattern", command)));
+        }
+
+        let name_nb = names.len();
+        let cmd = &mut RedisCommand::new();
+        cmd.add_cmd(command);
+        for name in names {
+            cmd.add_arg(name);
+        }
+        cmd.end();
+        send_request(&self.sender, cmd.into(), name_nb, check_replies)
+    }
+}
+
+impl Stream for PubSub {
+    type Item = PubSubMessage;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<PubSubMessage>> {
+        self.poll_message(cx)
+    }
+}
+
+impl fmt::Debug for PubSub {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis aio PubSub - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
+    }
+}
+
+impl fmt::Display for PubSub {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis aio PubSub - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
+    }
+}
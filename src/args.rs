@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::BuildHasher;
+
+/// The trait ToRedisArgs converts a value into the arguments of a redis command.
+///
+/// Each argument is sent as a binary-safe bulk string, so keys and values can hold any bytes:
+///
+/// ```
+/// # use redis_client::commands::CommandBuilder;
+/// let cmd = &mut redis_client::RedisCommand::new();
+/// cmd.set("key", &[0xffu8, 0x00, 0xfe][..]);
+/// ```
+///
+/// A value can produce several arguments: the slices, the vectors, the sets and the tuples produce one argument per element,
+/// the maps produce a key then its value for each entry and None or () produce no argument at all.
+/// The only exception is a sequence of u8 which is binary data, so `&[u8]` and `Vec<u8>` produce a single argument.
+///
+/// The integers and the floats are sent in their decimal representation and bool as 1 or 0.
+pub trait ToRedisArgs {
+    /// Append the arguments produced by the value
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>);
+
+    /// Append the arguments produced by a sequence of values. By default each value writes its own arguments.
+    fn write_redis_args_from_slice(items: &[Self], out: &mut Vec<Vec<u8>>) where Self: Sized {
+        for item in items {
+            item.write_redis_args(out);
+        }
+    }
+
+    /// Get the arguments produced by the value
+    fn to_redis_args(&self) -> Vec<Vec<u8>> {
+        let mut out = Vec::new();
+        self.write_redis_args(&mut out);
+        out
+    }
+}
+
+impl ToRedisArgs for u8 {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        out.push(self.to_string().into_bytes());
+    }
+
+    /// A sequence of bytes is a single binary argument
+    fn write_redis_args_from_slice(items: &[u8], out: &mut Vec<Vec<u8>>) {
+        out.push(items.to_vec());
+    }
+}
+
+macro_rules! to_redis_args_for_numbers {
+    ($($number:ty),*) => {
+        $(
+            impl ToRedisArgs for $number {
+                fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+                    out.push(self.to_string().into_bytes());
+                }
+            }
+        )*
+    }
+}
+
+to_redis_args_for_numbers!(i8, i16, i32, i64, i128, isize, u16, u32, u64, u128, usize, f32, f64);
+
+impl ToRedisArgs for () {
+    fn write_redis_args(&self, _out: &mut Vec<Vec<u8>>) {}
+}
+
+impl ToRedisArgs for bool {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        out.push(if *self { b"1".to_vec() } else { b"0".to_vec() });
+    }
+}
+
+impl ToRedisArgs for str {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        out.push(self.as_bytes().to_vec());
+    }
+}
+
+impl ToRedisArgs for String {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        out.push(self.as_bytes().to_vec());
+    }
+}
+
+impl<T: ToRedisArgs + ?Sized> ToRedisArgs for &T {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        (**self).write_redis_args(out);
+    }
+}
+
+impl<T: ToRedisArgs> ToRedisArgs for Option<T> {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        if let Some(ref value) = *self {
+            value.write_redis_args(out);
+        }
+    }
+}
+
+impl<T: ToRedisArgs> ToRedisArgs for [T] {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        T::write_redis_args_from_slice(self, out);
+    }
+}
+
+impl<T: ToRedisArgs, const N: usize> ToRedisArgs for [T; N] {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        T::write_redis_args_from_slice(self, out);
+    }
+}
+
+impl<T: ToRedisArgs> ToRedisArgs for Vec<T> {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        T::write_redis_args_from_slice(self, out);
+    }
+}
+
+impl<T: ToRedisArgs, S: BuildHasher> ToRedisArgs for HashSet<T, S> {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        for item in self {
+            item.write_redis_args(out);
+        }
+    }
+}
+
+impl<T: ToRedisArgs> ToRedisArgs for BTreeSet<T> {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        for item in self {
+            item.write_redis_args(out);
+        }
+    }
+}
+
+impl<K: ToRedisArgs, V: ToRedisArgs, S: BuildHasher> ToRedisArgs for HashMap<K, V, S> {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        for (key, value) in self {
+            key.write_redis_args(out);
+            value.write_redis_args(out);
+        }
+    }
+}
+
+impl<K: ToRedisArgs, V: ToRedisArgs> ToRedisArgs for BTreeMap<K, V> {
+    fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+        for (key, value) in self {
+            key.write_redis_args(out);
+            value.write_redis_args(out);
+        }
+    }
+}
+
+macro_rules! to_redis_args_for_tuples {
+    ($(($($name:ident: $index:tt),+);)+) => {
+        $(
+            impl<$($name: ToRedisArgs),+> ToRedisArgs for ($($name,)+) {
+                fn write_redis_args(&self, out: &mut Vec<Vec<u8>>) {
+                    $(self.$index.write_redis_args(out);)+
+                }
+            }
+        )+
+    }
+}
+
+to_redis_args_for_tuples! {
+    (A: 0);
+    (A: 0, B: 1);
+    (A: 0, B: 1, C: 2);
+    (A: 0, B: 1, C: 2, D: 3);
+    (A: 0, B: 1, C: 2, D: 3, E: 4);
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11);
+}
@@ -0,0 +1,574 @@
+use commands::{CommandExecutor, RedisCommand};
+use config::ConnectionConfig;
+use errors::{ParsingError, RedisError, ServerError, ServerErrorKind};
+use reader::Reader;
+use redis::RedisClient;
+use results::RedisResult;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// The number of hash slots of a redis cluster
+pub const SLOT_NB: usize = 16384;
+
+/// How many times a command follows a redirection or is retried after a TRYAGAIN before its error is returned
+const MAX_REDIRECTIONS: u32 = 16;
+
+/// The delay before retrying a command refused with TRYAGAIN, while a slot is being migrated
+const TRYAGAIN_DELAY_MS: u64 = 20;
+
+/// The host and the port of a cluster node
+type NodeAddress = (String, u16);
+
+/// Compute the CRC16 (XMODEM) of the bytes, the checksum redis uses to hash the keys
+pub fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in bytes {
+        crc ^= u16::from(*byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// Get the hash slot of a key. When the key contains a non empty `{hashtag}`, only the hashtag is hashed
+/// so the keys sharing it are stored in the same slot:
+///
+/// ```
+/// # use redis_client::cluster::key_slot;
+/// assert_eq!(key_slot(b"foo"), 12182);
+/// assert_eq!(key_slot(b"{user1000}.following"), key_slot(b"{user1000}.followers"));
+/// ```
+pub fn key_slot(key: &[u8]) -> u16 {
+    let hashed = match key.iter().position(|&byte| byte == b'{') {
+        Some(open) => match key[open + 1..].iter().position(|&byte| byte == b'}') {
+            Some(length) if length > 0 => &key[open + 1..open + 1 + length],
+            _ => key,
+        },
+        None => key,
+    };
+    crc16(hashed) % SLOT_NB as u16
+}
+
+/// Get the key a command is routed by, None for the commands without key
+fn command_key(args: &[Vec<u8>]) -> Option<&[u8]> {
+    let name = String::from_utf8_lossy(args.first()?).to_uppercase();
+    let position = match name.as_str() {
+        "EVAL" | "EVALSHA" | "EVAL_RO" | "EVALSHA_RO" | "FCALL" | "FCALL_RO" => {
+            let key_nb = String::from_utf8_lossy(args.get(2)?).parse::<usize>().ok()?;
+            if key_nb == 0 {
+                return None;
+            }
+            3
+        },
+        "XREAD" | "XREADGROUP" => args.iter().position(|arg| arg.eq_ignore_ascii_case(b"STREAMS"))? + 1,
+        "BITOP" | "OBJECT" | "MEMORY" | "XINFO" | "XGROUP" => 2,
+        "PING" | "ECHO" | "INFO" | "CLUSTER" | "CONFIG" | "CLIENT" | "AUTH" | "HELLO" | "SELECT" | "MULTI" | "EXEC"
+            | "DISCARD" | "UNWATCH" | "SCRIPT" | "FUNCTION" | "DBSIZE" | "FLUSHALL" | "FLUSHDB" | "TIME" | "PUBLISH"
+            | "PUBSUB" | "KEYS" | "SCAN" | "RANDOMKEY" | "WAIT" | "COMMAND" | "ASKING" | "READONLY" | "READWRITE"
+            | "LASTSAVE" | "SAVE" | "BGSAVE" | "BGREWRITEAOF" | "QUIT" => return None,
+        _ => 1,
+    };
+    args.get(position).map(|key| &key[..])
+}
+
+/// Split the commands of a RedisCommand into their arguments
+fn split_commands(redis_command: &mut RedisCommand) -> Result<Vec<Vec<Vec<u8>>>, RedisError> {
+    let bytes: &[u8] = redis_command.into();
+    let mut commands = Vec::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        let (command, length) = Reader::parse(&bytes[position..])?;
+        commands.push(command.try_convert::<Vec<Vec<u8>>>()?);
+        position += length;
+    }
+    Ok(commands)
+}
+
+/// Build a RedisCommand from the arguments of a single command
+fn join_command(args: &[Vec<u8>]) -> RedisCommand {
+    let mut cmd = RedisCommand::new();
+    cmd.add_args(args.to_vec()).end();
+    cmd
+}
+
+/// Get the slot and the address of a MOVED or ASK error, whose message is like `3999 127.0.0.1:6381`
+fn parse_redirection(err: &ServerError) -> Option<(u16, NodeAddress)> {
+    let mut words = err.message.split_whitespace();
+    let slot = words.next()?.parse::<u16>().ok()?;
+    let (host, port) = words.next()?.rsplit_once(':')?;
+    Some((slot, (host.trim_start_matches('[').trim_end_matches(']').to_string(), port.parse::<u16>().ok()?)))
+}
+
+fn invalid_slots(reply: &str) -> RedisError {
+    RedisError::Parse(ParsingError::InvalidValue(format!("invalid {} response", reply)))
+}
+
+/// The slot ranges of the masters, as returned by CLUSTER SLOTS. An empty or unknown host means the host of the node queried.
+fn parse_cluster_slots(reply: RedisResult, queried_host: &str) -> Result<Vec<(u16, u16, NodeAddress)>, RedisError> {
+    let mut ranges = Vec::new();
+    for range in reply.try_convert::<Vec<Vec<RedisResult>>>()? {
+        let mut range = range.into_iter();
+        let (start, end, master) = match (range.next(), range.next(), range.next()) {
+            (Some(start), Some(end), Some(master)) => (start.try_convert::<u16>()?, end.try_convert::<u16>()?, master.try_convert::<Vec<RedisResult>>()?),
+            _ => return Err(invalid_slots("CLUSTER SLOTS")),
+        };
+        let mut master = master.into_iter();
+        let host = match master.next() {
+            Some(host) => host.try_convert::<String>()?,
+            None => return Err(invalid_slots("CLUSTER SLOTS")),
+        };
+        let port = match master.next() {
+            Some(port) => port.try_convert::<u16>()?,
+            None => return Err(invalid_slots("CLUSTER SLOTS")),
+        };
+        let host = if host.is_empty() || host == "?" { queried_host.to_string() } else { host };
+        ranges.push((start, end, (host, port)));
+    }
+    Ok(ranges)
+}
+
+/// The slot ranges of the masters, as returned by CLUSTER SHARDS (redis 7 and above)
+fn parse_cluster_shards(reply: RedisResult, queried_host: &str) -> Result<Vec<(u16, u16, NodeAddress)>, RedisError> {
+    let mut ranges = Vec::new();
+    for shard in reply.try_convert::<Vec<HashMap<String, RedisResult>>>()? {
+        let mut shard = shard;
+        let slots = match shard.remove("slots") {
+            Some(slots) => slots.try_convert::<Vec<u16>>()?,
+            None => return Err(invalid_slots("CLUSTER SHARDS")),
+        };
+        let nodes = match shard.remove("nodes") {
+            Some(nodes) => nodes.try_convert::<Vec<HashMap<String, RedisResult>>>()?,
+            None => return Err(invalid_slots("CLUSTER SHARDS")),
+        };
+
+        let master = nodes.into_iter().find(|node| {
+            node.get("role").and_then(|role| role.clone().try_convert::<String>().ok()).as_deref() == Some("master")
+        });
+        let mut master = match master {
+            Some(master) => master,
+            None => continue,
+        };
+        let host = ["endpoint", "ip", "hostname"].iter()
+            .filter_map(|field| master.remove(*field))
+            .filter_map(|host| host.try_convert::<String>().ok())
+            .find(|host| !host.is_empty() && host != "?")
+            .unwrap_or_else(|| queried_host.to_string());
+        let port = match master.remove("port") {
+            Some(port) => port.try_convert::<u16>()?,
+            None => return Err(invalid_slots("CLUSTER SHARDS")),
+        };
+
+        for range in slots.chunks(2) {
+            if range.len() == 2 {
+                ranges.push((range[0], range[1], (host.clone(), port)));
+            }
+        }
+    }
+    Ok(ranges)
+}
+
+/// A ClusterClient executes redis commands on a redis cluster. It implements CommandExecutor, so CommandSender
+/// and Pipeline can be used with it like with a RedisClient.
+///
+/// The slots of the nodes are read from the seed nodes with CLUSTER SLOTS, or CLUSTER SHARDS if it is not supported,
+/// and each command is sent to the master owning the slot of its key. A connection is opened to a node the first time
+/// a command is sent to it, with the configuration of the first seed node.
+///
+/// A MOVED redirection refreshes the slots and the command is sent again to the new owner, an ASK redirection sends the command
+/// once to the node importing the slot preceded by ASKING, and a command refused with TRYAGAIN is retried after a short delay.
+/// A pipeline is split by node, each node getting its commands as a single pipeline, and its responses are put back in order.
+///
+/// ```no_run
+/// # use redis_client::commands::CommandSender;
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// let mut client = try!(redis_client::ClusterClient::new(&["redis://127.0.0.1:7000", "redis://127.0.0.1:7001"]));
+/// let value: Option<String> = try!(client.get("{user1000}.name"));
+/// # Ok(())}
+/// ```
+pub struct ClusterClient {
+    config: ConnectionConfig,
+    seeds: Vec<NodeAddress>,
+    connections: HashMap<NodeAddress, RedisClient>,
+    nodes: Vec<NodeAddress>,
+    slots: Vec<Option<usize>>,
+}
+
+impl ClusterClient {
+    /// Create a ClusterClient from the URLs of some of the nodes
+    pub fn new<U: AsRef<str>>(urls: &[U]) -> Result<ClusterClient, RedisError> {
+        let mut configs = Vec::with_capacity(urls.len());
+        for url in urls {
+            configs.push(ConnectionConfig::from_url(url.as_ref())?);
+        }
+        ClusterClient::with_configs(&configs)
+    }
+
+    /// Create a ClusterClient from the configurations of some of the nodes. The database has to be 0 in cluster mode
+    /// and the other settings of the first configuration are used for every node.
+    pub fn with_configs(configs: &[ConnectionConfig]) -> Result<ClusterClient, RedisError> {
+        let config = match configs.first() {
+            Some(config) => config.clone(),
+            None => return Err(RedisError::InvalidInput("a cluster needs at least one seed node".to_string())),
+        };
+
+        let mut client = ClusterClient {
+            config,
+            seeds: configs.iter().map(|config| (config.get_host().to_string(), config.get_port())).collect(),
+            connections: HashMap::new(),
+            nodes: Vec::new(),
+            slots: vec![None; SLOT_NB],
+        };
+        client.refresh_slots()?;
+        Ok(client)
+    }
+
+    /// Read the slots of the nodes again, from the first node which answers
+    pub fn refresh_slots(&mut self) -> Result<(), RedisError> {
+        let mut candidates: Vec<NodeAddress> = self.connections.keys().cloned().collect();
+        candidates.sort();
+        for seed in &self.seeds {
+            if !candidates.contains(seed) {
+                candidates.push(seed.clone());
+            }
+        }
+
+        let mut last_error = RedisError::Io(io::Error::new(io::ErrorKind::NotConnected, "no cluster node could be reached"));
+        for address in candidates {
+            match self.fetch_slots(&address) {
+                Ok(ranges) => {
+                    self.set_slots(ranges);
+                    return Ok(());
+                },
+                Err(err) => {
+                    if let RedisError::Io(_) = err {
+                        self.connections.remove(&address);
+                    }
+                    last_error = err;
+                },
+            };
+        }
+        Err(last_error)
+    }
+
+    /// Get the address of the master owning a slot, if it is known
+    pub fn get_slot_node(&self, slot: u16) -> Option<(&str, u16)> {
+        self.slots.get(slot as usize)
+            .and_then(|node| *node)
+            .map(|node| (&self.nodes[node].0[..], self.nodes[node].1))
+    }
+
+    pub fn get_config(&self) -> &ConnectionConfig {
+        &self.config
+    }
+
+    fn fetch_slots(&mut self, address: &NodeAddress) -> Result<Vec<(u16, u16, NodeAddress)>, RedisError> {
+        let client = self.get_connection(address)?;
+        match client.exec_redis_command(RedisCommand::new().add_cmd("CLUSTER SLOTS").end()) {
+            Ok(reply) => parse_cluster_slots(reply, &address.0),
+            Err(RedisError::Response(_)) => {
+                let reply = client.exec_redis_command(RedisCommand::new().add_cmd("CLUSTER SHARDS").end())?;
+                parse_cluster_shards(reply, &address.0)
+            },
+            Err(err) => Err(err),
+        }
+    }
+
+    fn set_slots(&mut self, ranges: Vec<(u16, u16, NodeAddress)>) {
+        self.nodes.clear();
+        self.slots = vec![None; SLOT_NB];
+        for (start, end, address) in ranges {
+            let node = self.node_index(address);
+            for slot in start as usize..=(end as usize).min(SLOT_NB - 1) {
+                self.slots[slot] = Some(node);
+            }
+        }
+    }
+
+    fn node_index(&mut self, address: NodeAddress) -> usize {
+        match self.nodes.iter().position(|node| *node == address) {
+            Some(node) => node,
+            None => {
+                self.nodes.push(address);
+                self.nodes.len() - 1
+            },
+        }
+    }
+
+    /// Get the connection to a node, opening it if needed
+    fn get_connection(&mut self, address: &NodeAddress) -> Result<&mut RedisClient, RedisError> {
+        if !self.connections.contains_key(address) {
+            let mut config = self.config.clone();
+            config.set_host(&address.0).set_port(address.1);
+            let client = RedisClient::with_config(&config)?;
+            self.connections.insert(address.clone(), client);
+        }
+        match self.connections.get_mut(address) {
+            Some(client) => Ok(client),
+            None => Err(RedisError::Io(io::Error::new(io::ErrorKind::NotConnected, "the node connection is missing"))),
+        }
+    }
+
+    /// Get the node a command with the key is sent to. The commands without key, or whose slot is not known, go to any node.
+    fn get_node(&self, key: Option<&[u8]>) -> NodeAddress {
+        let node = key.and_then(|key| self.slots[key_slot(key) as usize]);
+        match node.or(if self.nodes.is_empty() { None } else { Some(0) }) {
+            Some(node) => self.nodes[node].clone(),
+            None => self.seeds[0].clone(),
+        }
+    }
+
+    /// Send a command to a node, preceded by ASKING after an ASK redirection
+    fn send_to_node(&mut self, address: &NodeAddress, redis_command: &mut RedisCommand, asking: bool) -> Result<RedisResult, RedisError> {
+        let client = self.get_connection(address)?;
+        if !asking {
+            return client.exec_redis_command(redis_command);
+        }
+
+        let cmd = &mut RedisCommand::new();
+        cmd.add_cmd("ASKING").end().add_commands(redis_command);
+        match client.exec_redis_pipeline_command(cmd)?.pop() {
+            Some(RedisResult::Error(err)) => Err(RedisError::Response(err)),
+            Some(result) => Ok(result),
+            None => Err(RedisError::Parse(ParsingError::InvalidValue("no response after ASKING".to_string()))),
+        }
+    }
+
+    /// Tell where a command goes after a redirection or a TRYAGAIN error, and whether ASKING has to be sent first.
+    /// A MOVED redirection gives the slot to its new owner, the slots being refreshed by the caller beforehand.
+    fn redirect(&mut self, err: &ServerError, address: &NodeAddress) -> Option<(NodeAddress, bool)> {
+        match err.kind {
+            ServerErrorKind::Moved => {
+                let (slot, target) = parse_redirection(err)?;
+                let node = self.node_index(target.clone());
+                self.slots[slot as usize] = Some(node);
+                Some((target, false))
+            },
+            ServerErrorKind::Ask => parse_redirection(err).map(|(_, target)| (target, true)),
+            ServerErrorKind::TryAgain => {
+                thread::sleep(Duration::from_millis(TRYAGAIN_DELAY_MS));
+                Some((address.clone(), false))
+            },
+            _ => None,
+        }
+    }
+
+    /// Send a command to a node and follow the redirections
+    fn run_command(&mut self, args: &[Vec<u8>], mut address: NodeAddress, mut asking: bool) -> Result<RedisResult, RedisError> {
+        let cmd = &mut join_command(args);
+        let mut attempt = 0;
+        loop {
+            let err = match self.send_to_node(&address, cmd, asking) {
+                Err(RedisError::Response(err)) => err,
+                Err(RedisError::Io(err)) => {
+                    self.forget_node(&address);
+                    return Err(RedisError::Io(err));
+                },
+                result => return result,
+            };
+
+            attempt += 1;
+            if attempt > MAX_REDIRECTIONS {
+                return Err(RedisError::Response(err));
+            }
+            self.refresh_if_moved(&err);
+            match self.redirect(&err, &address) {
+                Some((next_address, next_asking)) => {
+                    address = next_address;
+                    asking = next_asking;
+                },
+                None => return Err(RedisError::Response(err)),
+            };
+        }
+    }
+
+    /// Read the slots again after a MOVED redirection, as the other slots of the node may have moved too
+    fn refresh_if_moved(&mut self, err: &ServerError) {
+        if err.kind == ServerErrorKind::Moved {
+            let _ = self.refresh_slots();
+        }
+    }
+
+    /// Close the connection of a node which failed, and read the slots again in case it was replaced
+    fn forget_node(&mut self, address: &NodeAddress) {
+        self.connections.remove(address);
+        let _ = self.refresh_slots();
+    }
+
+    /// Send a transaction to a node. ASKING is sent before MULTI, so the importing node accepts every command of the transaction.
+    fn send_transaction_to_node(&mut self, address: &NodeAddress, redis_command: &mut RedisCommand, asking: bool) -> Result<Vec<RedisResult>, RedisError> {
+        let client = self.get_connection(address)?;
+        if !asking {
+            return client.exec_redis_pipeline_command(redis_command);
+        }
+
+        let cmd = &mut RedisCommand::new();
+        cmd.add_cmd("ASKING").end().add_commands(redis_command);
+        let mut results = client.exec_redis_pipeline_command(cmd)?;
+        results.remove(0);
+        Ok(results)
+    }
+
+    /// Send a transaction to the node of the first key, sending it again where its commands were redirected.
+    /// A node which fails is forgotten and the transaction is sent once more to the owner of the slot after the refresh.
+    fn run_transaction(&mut self, commands: &[Vec<Vec<u8>>], redis_command: &mut RedisCommand) -> Result<Vec<RedisResult>, RedisError> {
+        let key = commands.iter().filter_map(|args| command_key(args)).next();
+        let mut address = self.get_node(key);
+        let mut asking = false;
+        let mut attempt = 0;
+        let mut reconnected = false;
+        loop {
+            let results = match self.send_transaction_to_node(&address, redis_command, asking) {
+                Err(RedisError::Io(err)) => {
+                    self.forget_node(&address);
+                    if reconnected {
+                        return Err(RedisError::Io(err));
+                    }
+                    reconnected = true;
+                    address = self.get_node(key);
+                    asking = false;
+                    continue;
+                },
+                results => results?,
+            };
+
+            // a redirected command isn't queued, EXEC is then refused with EXECABORT
+            let redirection = results.iter().filter_map(|result| match *result {
+                RedisResult::Error(ref err) => match err.kind {
+                    ServerErrorKind::Moved | ServerErrorKind::Ask | ServerErrorKind::TryAgain => Some(err.clone()),
+                    _ => None,
+                },
+                _ => None,
+            }).next();
+            let err = match redirection {
+                Some(err) => err,
+                None => return Ok(results),
+            };
+
+            attempt += 1;
+            if attempt > MAX_REDIRECTIONS {
+                return Err(RedisError::Response(err));
+            }
+            self.refresh_if_moved(&err);
+            match self.redirect(&err, &address) {
+                Some((next_address, next_asking)) => {
+                    address = next_address;
+                    asking = next_asking;
+                },
+                None => return Ok(results),
+            };
+        }
+    }
+
+    /// Execute a RedisCommand on the node owning the slot of its key
+    pub fn exec_redis_command(&mut self, redis_command: &mut RedisCommand) -> Result<RedisResult, RedisError> {
+        let commands = split_commands(redis_command)?;
+        let args = match commands.first() {
+            Some(args) => args,
+            None => return Err(RedisError::InvalidInput("the command is empty".to_string())),
+        };
+        let address = self.get_node(command_key(args));
+        self.run_command(args, address, false)
+    }
+
+    /// Execute a RedisCommand on every master owning slots, for the commands changing the whole cluster like FUNCTION LOAD.
+    /// The responses are returned in the order of the masters and an error reply stops at its master.
+    pub fn exec_redis_command_on_masters(&mut self, redis_command: &mut RedisCommand) -> Result<Vec<RedisResult>, RedisError> {
+        let masters = self.nodes.clone();
+        let mut results = Vec::with_capacity(masters.len());
+        for address in masters {
+            let result = match self.get_connection(&address).and_then(|client| client.exec_redis_command(redis_command)) {
+                Err(RedisError::Io(err)) => {
+                    self.forget_node(&address);
+                    return Err(RedisError::Io(err));
+                },
+                result => result?,
+            };
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// Execute a pipeline of RedisCommand. The commands are grouped by node and each group is sent as a pipeline.
+    ///
+    /// A pipeline containing MULTI is a transaction: it is sent as it is to the node of its first key, so all its keys
+    /// have to be in the same slot.
+    pub fn exec_redis_pipeline_command(&mut self, redis_command: &mut RedisCommand) -> Result<Vec<RedisResult>, RedisError> {
+        let commands = split_commands(redis_command)?;
+        if commands.iter().any(|args| args.first().map(|name| name.eq_ignore_ascii_case(b"MULTI")).unwrap_or(false)) {
+            return self.run_transaction(&commands, redis_command);
+        }
+
+        let mut groups: BTreeMap<NodeAddress, Vec<usize>> = BTreeMap::new();
+        for (index, args) in commands.iter().enumerate() {
+            groups.entry(self.get_node(command_key(args))).or_default().push(index);
+        }
+
+        let mut results: Vec<RedisResult> = vec![RedisResult::Nil; commands.len()];
+        let mut errors: Vec<(usize, NodeAddress, ServerError)> = Vec::new();
+        for (address, indexes) in groups {
+            let cmd = &mut RedisCommand::new();
+            for &index in &indexes {
+                cmd.add_args(commands[index].clone()).end();
+            }
+            let node_results = match self.get_connection(&address).and_then(|client| client.exec_redis_pipeline_command(cmd)) {
+                Err(RedisError::Io(err)) => {
+                    self.forget_node(&address);
+                    return Err(RedisError::Io(err));
+                },
+                node_results => node_results?,
+            };
+
+            for (index, result) in indexes.into_iter().zip(node_results) {
+                if let RedisResult::Error(ref err) = result {
+                    errors.push((index, address.clone(), err.clone()));
+                }
+                results[index] = result;
+            }
+        }
+
+        // the slots are read once for all the commands which moved, then each of them is sent to its new node
+        if errors.iter().any(|(_, _, err)| err.kind == ServerErrorKind::Moved) {
+            let _ = self.refresh_slots();
+        }
+        for (index, address, err) in errors {
+            if let Some((next_address, asking)) = self.redirect(&err, &address) {
+                results[index] = match self.run_command(&commands[index], next_address, asking) {
+                    Ok(result) => result,
+                    Err(RedisError::Response(err)) => RedisResult::Error(err),
+                    Err(err) => return Err(err),
+                };
+            }
+        }
+        Ok(results)
+    }
+}
+
+impl CommandExecutor for ClusterClient {
+    fn exec_redis_command(&mut self, redis_command: &mut RedisCommand) -> Result<RedisResult, RedisError> {
+        ClusterClient::exec_redis_command(self, redis_command)
+    }
+
+    fn exec_redis_pipeline_command(&mut self, redis_command: &mut RedisCommand) -> Result<Vec<RedisResult>, RedisError> {
+        ClusterClient::exec_redis_pipeline_command(self, redis_command)
+    }
+
+    fn get_key_connection_id(&self, key: &[u8]) -> Option<u64> {
+        self.connections.get(&self.get_node(Some(key))).and_then(|client| client.get_connection_id())
+    }
+}
+
+impl fmt::Debug for ClusterClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis Cluster Client - NODES = {}", self.nodes.len())
+    }
+}
+
+impl fmt::Display for ClusterClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis Cluster Client - NODES = {}", self.nodes.len())
+    }
+}
@@ -1,9 +1,18 @@
-use errors::RedisError;
-use redis::{PubSubArg, PubSubClientAsync, RedisClient, RedisClientAsync};
-use results::RedisResult;
+use args::ToRedisArgs;
+#[cfg(feature = "aio")]
+use aio::{MultiplexedClient, RedisFuture};
+use errors::{ParsingError, RedisError};
+use pipeline::Pipeline;
+use pubsub::PubSubMessage;
+use redis::{PubSubArg, PubSubClientAsync, RedisClientAsync};
+use results::{FromRedisValue, RedisResult};
+use scan::ScanOptions;
 use std::collections::HashMap;
 use types::PubSubType;
 
+/// The number of times a transaction is run before giving up when its watched keys keep being modified
+pub const TRANSACTION_MAX_ATTEMPTS: usize = 32;
+
 /// A RedisCommand purpose is to build redis commands.
 /// It can contains one or more commands for pipelining
 ///
@@ -23,6 +32,7 @@ use types::PubSubType;
 /// ```
 pub struct RedisCommand {
     cmd: Vec<u8>,
+    args: Vec<Vec<u8>>,
     cmd_nb: usize,
 }
 
@@ -32,77 +42,174 @@ impl<'a> From<&'a mut RedisCommand> for &'a[u8] {
     }
 }
 
-impl<'a> From<&'a mut RedisCommand> for Vec<u8> {
+impl From<&mut RedisCommand> for Vec<u8> {
     fn from(command: &mut RedisCommand) -> Vec<u8> {
         command.cmd.clone()
     }
 }
 
+impl Default for RedisCommand {
+    fn default() -> RedisCommand {
+        RedisCommand::new()
+    }
+}
+
 impl RedisCommand {
     pub fn new() -> RedisCommand {
         RedisCommand {
             cmd: vec![],
+            args: vec![],
             cmd_nb: 0,
         }
     }
 
     /// Add a string representing the command (APPEND, GET, SET...) to the command. (Each command should start with this method)
+    ///
+    /// A command made of several words (CLIENT SETNAME, CONFIG GET...) is split on whitespaces so each word is sent as its own argument.
     pub fn add_cmd<C>(&mut self, command: C) -> &mut RedisCommand where C: ToString {
-        self.cmd.extend(command.to_string().into_bytes());
+        for word in command.to_string().split_whitespace() {
+            self.args.push(word.as_bytes().to_vec());
+        }
         self
     }
 
-    /// Add a whitespace and a string to the commands
-    pub fn add_arg<A>(&mut self, arg: A) -> &mut RedisCommand where A: ToString {
-        self.cmd.extend([32].iter().cloned()); 
-        self.cmd.extend(arg.to_string().into_bytes());
+    /// Add the arguments produced by a value to the current command.
+    /// A string, a number or binary data (`&[u8]`, `Vec<u8>`) is a single argument, see ToRedisArgs for the other types.
+    pub fn add_arg<A>(&mut self, arg: A) -> &mut RedisCommand where A: ToRedisArgs {
+        arg.write_redis_args(&mut self.args);
         self
     }
 
-    /// Add a whitespace and a string for each one of the vector's items to the commands 
-    pub fn add_args<A>(&mut self, args: Vec<A>) -> &mut RedisCommand where A: ToString {
+    /// Add the arguments produced by each one of the vector's items to the current command
+    pub fn add_args<A>(&mut self, args: Vec<A>) -> &mut RedisCommand where A: ToRedisArgs {
         for arg in args {
-            self.cmd.extend([32].iter().cloned()); 
-            self.cmd.extend(arg.to_string().into_bytes());
+            arg.write_redis_args(&mut self.args);
         }
         self
     }
 
-    /// Add a whitespace a key another whitespace and the value for each pair of the hash map to the curent command   
-    pub fn add_arg_map<F: ToString>(&mut self, args: HashMap<String, F>) -> &mut RedisCommand {
+    /// Add a key and then its value as new arguments of the current command for each pair of the hash map
+    pub fn add_arg_map<K: ToRedisArgs, F: ToRedisArgs>(&mut self, args: HashMap<K, F>) -> &mut RedisCommand {
         for (arg, value) in args {
-            self.cmd.extend([32].iter().cloned()); 
-            self.cmd.extend(arg.to_string().into_bytes());
-            self.cmd.extend([32].iter().cloned()); 
-            self.cmd.extend(value.to_string().into_bytes());
+            arg.write_redis_args(&mut self.args);
+            value.write_redis_args(&mut self.args);
         }
         self
     }
-    
-    /// Add a whitespace and then an array of byte to the command        
-    pub fn add_binary_arg(&mut self, arg: &[u8]) -> &mut RedisCommand {
-        self.cmd.extend([32].iter().cloned()); 
-        self.cmd.extend(arg.iter().cloned());
-        self
-    }
 
-    /// Teminate a command
+    /// Teminate a command by encoding it and its arguments as a RESP array of bulk strings:
+    ///
+    /// ```plain
+    /// *<number of arguments>\r\n$<argument length>\r\n<argument>\r\n...
+    /// ```
     pub fn end(&mut self) -> &mut RedisCommand {
-        self.cmd.extend([13, 10].iter().cloned());
+        self.cmd.extend(format!("*{}\r\n", self.args.len()).into_bytes());
+        for arg in self.args.drain(..) {
+            self.cmd.extend(format!("${}\r\n", arg.len()).into_bytes());
+            self.cmd.extend(arg);
+            self.cmd.extend(b"\r\n".iter().cloned());
+        }
         self.cmd_nb += 1;
         self
     }
 
+    /// Add the commands of another RedisCommand after the ones of this one
+    pub fn add_commands(&mut self, other: &RedisCommand) -> &mut RedisCommand {
+        self.cmd.extend_from_slice(&other.cmd);
+        self.cmd_nb += other.cmd_nb;
+        self
+    }
+
     /// Get the number of commands in the RedisCommand object
     pub fn get_command_nb(&self) -> usize {
         self.cmd_nb
     }
 }
 
+/// The trait CommandExecutor sends a RedisCommand and returns its responses synchronously.
+///
+/// Every type implementing it gets the CommandSender methods, like RedisClient or the connections borrowed from a RedisPool.
+pub trait CommandExecutor {
+    /// Execute a RedisCommand and return its response
+    fn exec_redis_command(&mut self, redis_command: &mut RedisCommand) -> Result<RedisResult, RedisError>;
+
+    /// Execute a pipeline of RedisCommand and return the response of each command
+    fn exec_redis_pipeline_command(&mut self, redis_command: &mut RedisCommand) -> Result<Vec<RedisResult>, RedisError>;
+
+    /// Get the id of the opened connection the commands on the key are sent to, None when it is closed or unknown.
+    /// A transaction compares it before and after the closure to detect a reconnection dropping the WATCH.
+    fn get_key_connection_id(&self, _key: &[u8]) -> Option<u64> {
+        None
+    }
+
+    /// Run a transaction with optimistic locking on the keys.
+    ///
+    /// The keys are watched, then the closure is called with the executor, to read the state the transaction depends on,
+    /// and with an empty RedisCommand to queue the commands of the transaction. They are sent between MULTI and EXEC
+    /// and the responses of EXEC are converted to T. If a watched key was modified meanwhile, EXEC returns nil and
+    /// the whole transaction, closure included, is run again, up to TRANSACTION_MAX_ATTEMPTS times before failing
+    /// with a TransactionAborted error.
+    ///
+    /// An error of the closure ends the transaction after unwatching the keys. A command refused by redis while being
+    /// queued makes EXEC fail with an EXECABORT response error. If the client reconnected after the WATCH, the keys
+    /// aren't watched anymore and the transaction fails with a TransactionAborted error instead of sending EXEC.
+    ///
+    /// ```no_run
+    /// # use redis_client::commands::{CommandBuilder, CommandExecutor, CommandSender};
+    /// # fn function() -> Result<(), redis_client::errors::RedisError> {
+    /// # let mut client = try!(redis_client::RedisClient::new("127.0.0.1", "6379"));
+    /// let (value,): (i64,) = try!(client.transaction("counter", |client, cmd| {
+    ///     let counter: Option<i64> = try!(client.get("counter"));
+    ///     cmd.set("counter", counter.unwrap_or(0) * 2);
+    ///     Ok(())
+    /// }));
+    /// # Ok(())}
+    /// ```
+    fn transaction<K, T, F>(&mut self, keys: K, mut func: F) -> Result<T, RedisError>
+        where K: ToRedisArgs, T: FromRedisValue, F: FnMut(&mut Self, &mut RedisCommand) -> Result<(), RedisError>, Self: Sized
+    {
+        let keys = keys.to_redis_args();
+        for _ in 0..TRANSACTION_MAX_ATTEMPTS {
+            let watched = match keys.first() {
+                Some(key) => {
+                    self.exec_redis_command(RedisCommand::new().add_cmd("WATCH").add_args(keys.clone()).end())?;
+                    self.get_key_connection_id(key)
+                },
+                None => None,
+            };
+
+            let queued = &mut RedisCommand::new();
+            if let Err(err) = func(self, queued) {
+                if !keys.is_empty() {
+                    let _ = self.exec_redis_command(RedisCommand::new().unwatch());
+                }
+                return Err(err);
+            }
+
+            // a new connection doesn't watch the keys, EXEC would succeed whatever happened to them
+            if let (Some(key), Some(_)) = (keys.first(), watched) {
+                if self.get_key_connection_id(key) != watched {
+                    return Err(RedisError::TransactionAborted("the connection was lost while the keys were watched".to_string()));
+                }
+            }
+
+            let cmd = &mut RedisCommand::new();
+            cmd.multi().add_commands(queued).exec();
+            match self.exec_redis_pipeline_command(cmd)?.pop() {
+                Some(RedisResult::Nil) => continue,
+                Some(RedisResult::Error(err)) => return Err(RedisError::Response(err)),
+                Some(results) => return results.try_convert::<T>(),
+                None => return Err(RedisError::Parse(ParsingError::InvalidValue("EXEC got no response".to_string()))),
+            };
+        }
+        Err(RedisError::TransactionAborted(format!("the watched keys were modified during {} attempts", TRANSACTION_MAX_ATTEMPTS)))
+    }
+}
+
 macro_rules! generate_command_traits {
     ($(
         fn $func_name:ident$(<$($gen_id:ident: $gen_type:ident),*>)*($($arg_name:ident: $arg_type:ty),*)  {
-            $($cmd:ident $bo:expr;)+
+            $($cmd:ident($($bo:expr),*);)+
         } 
     )*)
     => 
@@ -132,21 +239,32 @@ macro_rules! generate_command_traits {
         impl CommandBuilder for RedisCommand{
             $(
                 fn $func_name$(<$($gen_id : $gen_type),*>)* (&mut self $(,$arg_name: $arg_type)*) -> &mut RedisCommand {
-                    $(self.$cmd($bo));*;
+                    $(self.$cmd($($bo),*));*;
                     self.end()
                 }
             )*
         }
 
+        impl Pipeline {
+            $(
+                pub fn $func_name$(<$($gen_id : $gen_type),*>)* (&mut self $(,$arg_name: $arg_type)*) -> &mut Pipeline {
+                    let cmd = &mut RedisCommand::new();
+                    cmd.$func_name($($arg_name),*);
+                    self.add_command(cmd)
+                }
+            )*
+        }
+
         /// The trait CommandSender implements methods to send redis commands and receive the response synchronously.
         ///
         /// Each methods returns a:
         ///
         /// ```plain
-        /// Result<R: From<RedisResult>, RedisError>
+        /// Result<R: FromRedisValue, RedisError>
         /// ```
         /// 
         /// It means that when calling a method from this trait you need to specify the type you want R to be.
+        /// If the response does not fit R, a RedisError::Conversion is returned.
         /// For example:
         ///
         /// ```no_run
@@ -158,18 +276,48 @@ macro_rules! generate_command_traits {
         /// ```
         pub trait CommandSender {
             $(
-                fn $func_name<R: From<RedisResult>, $($($gen_id : $gen_type),*)*> (&mut self $(,$arg_name: $arg_type)*) -> Result<R, RedisError>;
+                fn $func_name<R: FromRedisValue, $($($gen_id : $gen_type),*)*> (&mut self $(,$arg_name: $arg_type)*) -> Result<R, RedisError>;
             )*
         }
 
-        impl CommandSender for RedisClient{
+        impl<Executor: CommandExecutor> CommandSender for Executor {
             $(
-                fn $func_name<R: From<RedisResult>, $($($gen_id : $gen_type),*)*> (&mut self $(,$arg_name: $arg_type)*) -> Result<R, RedisError> {
+                fn $func_name<R: FromRedisValue, $($($gen_id : $gen_type),*)*> (&mut self $(,$arg_name: $arg_type)*) -> Result<R, RedisError> {
                     let cmd = &mut RedisCommand::new();
                     cmd.$func_name($($arg_name),*);
 
-                    let res = try!(self.exec_redis_command(cmd));     
-                    Ok(res.convert::<R>())
+                    let res = self.exec_redis_command(cmd)?;
+                    res.try_convert::<R>()
+                }
+            )*
+        }
+
+        /// The trait CommandSenderFuture implements methods to send redis commands and get a future of their response,
+        /// with the aio feature.
+        ///
+        /// Each methods returns a:
+        ///
+        /// ```plain
+        /// RedisFuture<R: FromRedisValue>
+        /// ```
+        ///
+        /// The command is sent when the method is called, the future only waits for its response. So the commands sent
+        /// before awaiting the first response are pipelined on the connection.
+        #[cfg(feature = "aio")]
+        pub trait CommandSenderFuture {
+            $(
+                fn $func_name<R: FromRedisValue, $($($gen_id : $gen_type),*)*> (&self $(,$arg_name: $arg_type)*) -> RedisFuture<R>;
+            )*
+        }
+
+        #[cfg(feature = "aio")]
+        impl CommandSenderFuture for MultiplexedClient {
+            $(
+                fn $func_name<R: FromRedisValue, $($($gen_id : $gen_type),*)*> (&self $(,$arg_name: $arg_type)*) -> RedisFuture<R> {
+                    let cmd = &mut RedisCommand::new();
+                    cmd.$func_name($($arg_name),*);
+
+                    self.send_command::<R>(cmd)
                 }
             )*
         }
@@ -188,82 +336,308 @@ macro_rules! generate_command_traits {
         /// Each method will contained a callback argument:
         ///
         /// ```plain
-        /// Fn(Result<RedisResult, RedisError>)
+        /// FnOnce(Result<RedisResult, RedisError>)
         /// ```
         /// Once the command execution is over, it will be called once the pump method is called.
         /// 
         /// All commands execution are made in a background thread. 
         pub trait CommandSenderAsync {
             $(
-                fn $func_name<G: Fn(Result<RedisResult, RedisError>), $($($gen_id : $gen_type),*)*> (&mut self $(,$arg_name: $arg_type)*, callback: G) 
-                    -> Result<(), RedisError> where G: Send + 'static;
+                fn $func_name<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, $($($gen_id : $gen_type),*)*> (&mut self $(,$arg_name: $arg_type)*, callback: G) 
+                    -> Result<(), RedisError>;
             )*
         }
 
         impl CommandSenderAsync for RedisClientAsync{
             $(
-                fn $func_name<G: Fn(Result<RedisResult, RedisError>), $($($gen_id : $gen_type),*)*> (&mut self $(,$arg_name: $arg_type)*, callback: G) 
-                    -> Result<(), RedisError> where G: Send + 'static 
+                fn $func_name<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, $($($gen_id : $gen_type),*)*> (&mut self $(,$arg_name: $arg_type)*, callback: G) 
+                    -> Result<(), RedisError> 
                 {
                     let cmd = &mut RedisCommand::new();
                     cmd.$func_name($($arg_name),*);
 
-                    try!(self.exec_redis_command_async(cmd, callback));     
+                    self.exec_redis_command_async(cmd, callback)?;     
                     Ok(())
                 }
             )*
         }
 
         pub trait PubSubCommandAsync {
-            fn subscribe<C: ToString, G: Fn(Result<RedisResult, RedisError>), S: Fn(RedisResult)>(&mut self, channel: C, cmd_callback: G, callback: S) 
-                -> Result<(), RedisError> where G: Send + 'static, S: Send + 'static;
+            fn subscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, S: FnMut(PubSubMessage) + Send + 'static>(&mut self, channel: C, cmd_callback: G, callback: S) 
+                -> Result<(), RedisError>;
+
+            fn psubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, S: FnMut(PubSubMessage) + Send + 'static>(&mut self, channel: C, cmd_callback: G, callback: S) 
+                -> Result<(), RedisError>;
+
+            fn publish<C: ToRedisArgs, M: ToRedisArgs, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, channel: C, message: M, cmd_callback: G)
+                -> Result<(), RedisError>;
+
+            fn ping<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, cmd_callback: G)
+                -> Result<(), RedisError>;
+
+            /// Subscribe to several channels sharing the callback, the cmd_callback gets all the confirmations
+            fn msubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, S: FnMut(PubSubMessage) + Send + 'static>(&mut self, channels: &[C], cmd_callback: G, callback: S)
+                -> Result<(), RedisError>;
+
+            /// Subscribe to several patterns sharing the callback, the cmd_callback gets all the confirmations
+            fn mpsubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, S: FnMut(PubSubMessage) + Send + 'static>(&mut self, patterns: &[C], cmd_callback: G, callback: S)
+                -> Result<(), RedisError>;
+
+            /// Subscribe to a sharded channel
+            fn ssubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, S: FnMut(PubSubMessage) + Send + 'static>(&mut self, channel: C, cmd_callback: G, callback: S)
+                -> Result<(), RedisError>;
+
+            /// Unsubscribe from a channel, its callback is removed once redis confirms
+            fn unsubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, channel: C, cmd_callback: G)
+                -> Result<(), RedisError>;
+
+            /// Unsubscribe from all the channels, the cmd_callback gets all the confirmations
+            fn unsubscribe_all<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, cmd_callback: G)
+                -> Result<(), RedisError>;
+
+            /// Unsubscribe from a pattern, its callback is removed once redis confirms
+            fn punsubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, pattern: C, cmd_callback: G)
+                -> Result<(), RedisError>;
+
+            /// Unsubscribe from all the patterns, the cmd_callback gets all the confirmations
+            fn punsubscribe_all<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, cmd_callback: G)
+                -> Result<(), RedisError>;
+
+            /// Unsubscribe from a sharded channel, its callback is removed once redis confirms
+            fn sunsubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, channel: C, cmd_callback: G)
+                -> Result<(), RedisError>;
+
+            /// Unsubscribe from all the sharded channels, the cmd_callback gets all the confirmations
+            fn sunsubscribe_all<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, cmd_callback: G)
+                -> Result<(), RedisError>;
 
-            fn psubscribe<C: ToString, G: Fn(Result<RedisResult, RedisError>), S: Fn(RedisResult)>(&mut self, channel: C, cmd_callback: G, callback: S) 
-                -> Result<(), RedisError> where G: Send + 'static, S: Send + 'static;
+            /// List the active channels, matching the pattern if it is not None
+            fn pubsub_channels<P: ToRedisArgs, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, pattern: P, cmd_callback: G)
+                -> Result<(), RedisError>;
 
-            fn publish<C: ToString, M: ToString, G: Fn(Result<RedisResult, RedisError>)>(&mut self, channel: C, message: M, cmd_callback: G)
-                -> Result<(), RedisError> where G: Send + 'static;
+            /// Get the number of subscribers of the channels
+            fn pubsub_numsub<C: ToRedisArgs, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, channels: C, cmd_callback: G)
+                -> Result<(), RedisError>;
+
+            /// Get the number of patterns subscribed by all the clients
+            fn pubsub_numpat<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, cmd_callback: G)
+                -> Result<(), RedisError>;
         }
 
         impl PubSubCommandAsync for PubSubClientAsync {
-            fn subscribe<C: ToString, G: Fn(Result<RedisResult, RedisError>), S: Fn(RedisResult)>(&mut self, channel: C, cmd_callback: G, callback: S) 
-                -> Result<(), RedisError> where G: Send + 'static, S: Send + 'static
+            fn subscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, S: FnMut(PubSubMessage) + Send + 'static>(&mut self, channel: C, cmd_callback: G, callback: S) 
+                -> Result<(), RedisError>
             {
                 let channel_str: String = channel.to_string();
                 let cmd = &mut RedisCommand::new();
-                cmd.add_arg("SUBSCRIBE").add_arg(channel).end();
+                cmd.add_cmd("SUBSCRIBE").add_arg(&channel_str).end();
 
-                try!(self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
                     pubsub_type: PubSubType::Channel(channel_str), 
                     callback: Some(Box::new(callback))
-                }));     
+                })?;     
                 Ok(())
             }
 
-            fn psubscribe<C: ToString, G: Fn(Result<RedisResult, RedisError>), S: Fn(RedisResult)>(&mut self, channel: C, cmd_callback: G, callback: S) 
-                -> Result<(), RedisError> where G: Send + 'static, S: Send + 'static
+            fn psubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, S: FnMut(PubSubMessage) + Send + 'static>(&mut self, channel: C, cmd_callback: G, callback: S) 
+                -> Result<(), RedisError>
             {
                 let channel_str: String = channel.to_string();
                 let cmd = &mut RedisCommand::new();
-                cmd.add_arg("PSUBSCRIBE").add_arg(channel).end();
+                cmd.add_cmd("PSUBSCRIBE").add_arg(&channel_str).end();
 
-                try!(self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
                     pubsub_type: PubSubType::Pattern(channel_str), 
                     callback: Some(Box::new(callback))
-                }));     
+                })?;     
                 Ok(())
             }
 
-            fn publish<C: ToString, M: ToString, G: Fn(Result<RedisResult, RedisError>)>(&mut self, channel: C, message: M, cmd_callback: G) 
-                -> Result<(), RedisError> where G: Send + 'static
+            fn publish<C: ToRedisArgs, M: ToRedisArgs, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, channel: C, message: M, cmd_callback: G) 
+                -> Result<(), RedisError>
             {
                 let cmd = &mut RedisCommand::new();
-                cmd.add_arg("PUBLISH").add_arg(channel).add_arg(message).end();
+                cmd.add_cmd("PUBLISH").add_arg(channel).add_arg(message).end();
 
-                try!(self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
                     pubsub_type: PubSubType::Simple,
                     callback: None
-                }));     
+                })?;     
+                Ok(())
+            }
+
+            fn ping<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, cmd_callback: G) 
+                -> Result<(), RedisError>
+            {
+                let cmd = &mut RedisCommand::new();
+                cmd.add_cmd("PING").end();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::Simple,
+                    callback: None
+                })?;     
+                Ok(())
+            }
+
+            fn msubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, S: FnMut(PubSubMessage) + Send + 'static>(&mut self, channels: &[C], cmd_callback: G, callback: S) 
+                -> Result<(), RedisError>
+            {
+                let names: Vec<String> = channels.iter().map(|channel| channel.to_string()).collect();
+                let cmd = &mut RedisCommand::new();
+                cmd.add_cmd("SUBSCRIBE").add_arg(&names).end();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::Channels(names),
+                    callback: Some(Box::new(callback))
+                })?;     
+                Ok(())
+            }
+
+            fn mpsubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, S: FnMut(PubSubMessage) + Send + 'static>(&mut self, patterns: &[C], cmd_callback: G, callback: S) 
+                -> Result<(), RedisError>
+            {
+                let names: Vec<String> = patterns.iter().map(|pattern| pattern.to_string()).collect();
+                let cmd = &mut RedisCommand::new();
+                cmd.add_cmd("PSUBSCRIBE").add_arg(&names).end();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::Patterns(names),
+                    callback: Some(Box::new(callback))
+                })?;     
+                Ok(())
+            }
+
+            fn ssubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static, S: FnMut(PubSubMessage) + Send + 'static>(&mut self, channel: C, cmd_callback: G, callback: S) 
+                -> Result<(), RedisError>
+            {
+                let channel_str: String = channel.to_string();
+                let cmd = &mut RedisCommand::new();
+                cmd.add_cmd("SSUBSCRIBE").add_arg(&channel_str).end();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::ShardChannel(channel_str),
+                    callback: Some(Box::new(callback))
+                })?;     
+                Ok(())
+            }
+
+            fn unsubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, channel: C, cmd_callback: G) 
+                -> Result<(), RedisError>
+            {
+                let channel_str: String = channel.to_string();
+                let cmd = &mut RedisCommand::new();
+                cmd.add_cmd("UNSUBSCRIBE").add_arg(&channel_str).end();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::Unsubscribe(vec![channel_str]),
+                    callback: None
+                })?;     
+                Ok(())
+            }
+
+            fn unsubscribe_all<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, cmd_callback: G) 
+                -> Result<(), RedisError>
+            {
+                let cmd = &mut RedisCommand::new();
+                cmd.add_cmd("UNSUBSCRIBE").end();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::Unsubscribe(Vec::new()),
+                    callback: None
+                })?;     
+                Ok(())
+            }
+
+            fn punsubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, pattern: C, cmd_callback: G) 
+                -> Result<(), RedisError>
+            {
+                let pattern_str: String = pattern.to_string();
+                let cmd = &mut RedisCommand::new();
+                cmd.add_cmd("PUNSUBSCRIBE").add_arg(&pattern_str).end();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::PUnsubscribe(vec![pattern_str]),
+                    callback: None
+                })?;     
+                Ok(())
+            }
+
+            fn punsubscribe_all<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, cmd_callback: G) 
+                -> Result<(), RedisError>
+            {
+                let cmd = &mut RedisCommand::new();
+                cmd.add_cmd("PUNSUBSCRIBE").end();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::PUnsubscribe(Vec::new()),
+                    callback: None
+                })?;     
+                Ok(())
+            }
+
+            fn sunsubscribe<C: ToString, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, channel: C, cmd_callback: G) 
+                -> Result<(), RedisError>
+            {
+                let channel_str: String = channel.to_string();
+                let cmd = &mut RedisCommand::new();
+                cmd.add_cmd("SUNSUBSCRIBE").add_arg(&channel_str).end();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::SUnsubscribe(vec![channel_str]),
+                    callback: None
+                })?;     
+                Ok(())
+            }
+
+            fn sunsubscribe_all<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, cmd_callback: G) 
+                -> Result<(), RedisError>
+            {
+                let cmd = &mut RedisCommand::new();
+                cmd.add_cmd("SUNSUBSCRIBE").end();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::SUnsubscribe(Vec::new()),
+                    callback: None
+                })?;     
+                Ok(())
+            }
+
+            fn pubsub_channels<P: ToRedisArgs, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, pattern: P, cmd_callback: G) 
+                -> Result<(), RedisError>
+            {
+                let cmd = &mut RedisCommand::new();
+                cmd.pubsub_channels(pattern);
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::Simple,
+                    callback: None
+                })?;     
+                Ok(())
+            }
+
+            fn pubsub_numsub<C: ToRedisArgs, G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, channels: C, cmd_callback: G) 
+                -> Result<(), RedisError>
+            {
+                let cmd = &mut RedisCommand::new();
+                cmd.pubsub_numsub(channels);
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::Simple,
+                    callback: None
+                })?;     
+                Ok(())
+            }
+
+            fn pubsub_numpat<G: FnOnce(Result<RedisResult, RedisError>) + Send + 'static>(&mut self, cmd_callback: G) 
+                -> Result<(), RedisError>
+            {
+                let cmd = &mut RedisCommand::new();
+                cmd.pubsub_numpat();
+
+                self.exec_redis_command_async(cmd, cmd_callback, PubSubArg{
+                    pubsub_type: PubSubType::Simple,
+                    callback: None
+                })?;     
                 Ok(())
             }
         }
@@ -271,17 +645,23 @@ macro_rules! generate_command_traits {
 }
 
 generate_command_traits!{
-    fn append<K: ToString, V: ToString>(key: K, value: V) {
+    fn append<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
         add_cmd("APPEND");
         add_arg(key);
         add_arg(value);
     }
 
-    fn auth<P: ToString>(password: P) {
+    fn auth<P: ToRedisArgs>(password: P) {
         add_cmd("AUTH");
         add_arg(password);
     }
 
+    fn auth_user<U: ToRedisArgs, P: ToRedisArgs>(username: U, password: P) {
+        add_cmd("AUTH");
+        add_arg(username);
+        add_arg(password);
+    }
+
     fn bgrewriteaof() {
         add_cmd("BGREWRITEAOF");
     }
@@ -290,66 +670,75 @@ generate_command_traits!{
         add_cmd("BGSAVE");
     }
 
-    fn bitcount<K: ToString>(key: K) {
+    fn bitcount<K: ToRedisArgs>(key: K) {
         add_cmd("BITCOUNT");
         add_arg(key);
     }
 
-    fn bitcount_range<K: ToString>(key: K, start_range: i64, end_range: i64) {
+    fn bitcount_range<K: ToRedisArgs>(key: K, start_range: i64, end_range: i64) {
         add_cmd("BITCOUNT");
         add_arg(key);
         add_arg(start_range);
         add_arg(end_range);
     }
 
-    fn blpop<K: ToString>(key: K, timeout: u32) {
+    fn blpop<K: ToRedisArgs>(key: K, timeout: u32) {
         add_cmd("BLPOP");
         add_arg(key);
         add_arg(timeout);
     }
 
-    fn mblpop<K: ToString>(keys: Vec<K>, timeout: u32) {
+    fn mblpop<K: ToRedisArgs>(keys: Vec<K>, timeout: u32) {
         add_cmd("BLPOP");
         add_args(keys);
         add_arg(timeout);
     }
 
-    fn brpop<K: ToString>(key: K, timeout: u32) {
+    fn brpop<K: ToRedisArgs>(key: K, timeout: u32) {
         add_cmd("BRPOP");
         add_arg(key);
         add_arg(timeout);
     }
 
-    fn mbrpop<K: ToString>(keys: Vec<K>, timeout: u32) {
+    fn mbrpop<K: ToRedisArgs>(keys: Vec<K>, timeout: u32) {
         add_cmd("BRPOP");
         add_args(keys);
         add_arg(timeout);
     }
 
-    fn brpoplpush<S: ToString, D: ToString>(source: S, dest: D, timeout: u32) {
+    fn brpoplpush<S: ToRedisArgs, D: ToRedisArgs>(source: S, dest: D, timeout: u32) {
         add_cmd("BRPOPLPUSH");
         add_arg(source);
         add_arg(dest);
         add_arg(timeout);
     }
 
-    fn decr<K: ToString>(key: K) {
+    fn client_getname() {
+        add_cmd("CLIENT GETNAME");
+    }
+
+    fn client_setname<N: ToRedisArgs>(name: N) {
+        add_cmd("CLIENT SETNAME");
+        add_arg(name);
+    }
+
+    fn decr<K: ToRedisArgs>(key: K) {
         add_cmd("DECR");
         add_arg(key);
     }
 
-    fn decrby<K: ToString>(key: K, increment: i64) {
+    fn decrby<K: ToRedisArgs>(key: K, increment: i64) {
         add_cmd("DECRBY");
         add_arg(key);
         add_arg(increment);
     }
 
-    fn del<K: ToString>(key: K) {
+    fn del<K: ToRedisArgs>(key: K) {
         add_cmd("DEL");
         add_arg(key);
     }
 
-    fn mdel<K: ToString>(keys: Vec<K>){
+    fn mdel<K: ToRedisArgs>(keys: Vec<K>){
         add_cmd("DEL");
         add_args(keys);
     }
@@ -358,146 +747,258 @@ generate_command_traits!{
         add_cmd("DISCARD");
     }
 
-    fn echo<K: ToString>(msg: K) {
+    fn echo<K: ToRedisArgs>(msg: K) {
         add_cmd("ECHO");
         add_arg(msg);
     }
 
+    fn eval<S: ToRedisArgs, K: ToRedisArgs, A: ToRedisArgs>(script: S, keys: K, args: A) {
+        add_cmd("EVAL");
+        add_arg(script);
+        add_arg(keys.to_redis_args().len());
+        add_arg(keys);
+        add_arg(args);
+    }
+
+    fn eval_ro<S: ToRedisArgs, K: ToRedisArgs, A: ToRedisArgs>(script: S, keys: K, args: A) {
+        add_cmd("EVAL_RO");
+        add_arg(script);
+        add_arg(keys.to_redis_args().len());
+        add_arg(keys);
+        add_arg(args);
+    }
+
+    fn evalsha<H: ToRedisArgs, K: ToRedisArgs, A: ToRedisArgs>(sha1: H, keys: K, args: A) {
+        add_cmd("EVALSHA");
+        add_arg(sha1);
+        add_arg(keys.to_redis_args().len());
+        add_arg(keys);
+        add_arg(args);
+    }
+
+    fn evalsha_ro<H: ToRedisArgs, K: ToRedisArgs, A: ToRedisArgs>(sha1: H, keys: K, args: A) {
+        add_cmd("EVALSHA_RO");
+        add_arg(sha1);
+        add_arg(keys.to_redis_args().len());
+        add_arg(keys);
+        add_arg(args);
+    }
+
     fn exec() {
         add_cmd("EXEC");
     }
 
-    fn exists<K: ToString>(key: K) {
+    fn exists<K: ToRedisArgs>(key: K) {
         add_cmd("EXISTS");
         add_arg(key);
     }
 
-    fn mexists<K: ToString>(keys: Vec<K>){
+    fn mexists<K: ToRedisArgs>(keys: Vec<K>){
         add_cmd("EXISTS");
         add_args(keys);
     }
 
-    fn expire<K: ToString>(key: K, expiry: i64) {
+    fn expire<K: ToRedisArgs>(key: K, expiry: i64) {
         add_cmd("EXPIRE");
         add_arg(key);
         add_arg(expiry);
     }
 
-    fn expireat<K: ToString>(key: K, timestamp: i64) {
+    fn expireat<K: ToRedisArgs>(key: K, timestamp: i64) {
         add_cmd("EXPIREAT");
         add_arg(key);
         add_arg(timestamp);
     }
 
-    fn get<K: ToString>(key: K) {
+    fn fcall<F: ToRedisArgs, K: ToRedisArgs, A: ToRedisArgs>(function: F, keys: K, args: A) {
+        add_cmd("FCALL");
+        add_arg(function);
+        add_arg(keys.to_redis_args().len());
+        add_arg(keys);
+        add_arg(args);
+    }
+
+    fn fcall_ro<F: ToRedisArgs, K: ToRedisArgs, A: ToRedisArgs>(function: F, keys: K, args: A) {
+        add_cmd("FCALL_RO");
+        add_arg(function);
+        add_arg(keys.to_redis_args().len());
+        add_arg(keys);
+        add_arg(args);
+    }
+
+    fn function_delete<N: ToRedisArgs>(library_name: N) {
+        add_cmd("FUNCTION DELETE");
+        add_arg(library_name);
+    }
+
+    fn function_dump() {
+        add_cmd("FUNCTION DUMP");
+    }
+
+    fn function_flush() {
+        add_cmd("FUNCTION FLUSH");
+    }
+
+    fn function_list() {
+        add_cmd("FUNCTION LIST");
+    }
+
+    fn function_list_library<P: ToRedisArgs>(library_pattern: P) {
+        add_cmd("FUNCTION LIST LIBRARYNAME");
+        add_arg(library_pattern);
+    }
+
+    fn function_load<C: ToRedisArgs>(code: C) {
+        add_cmd("FUNCTION LOAD");
+        add_arg(code);
+    }
+
+    fn function_load_replace<C: ToRedisArgs>(code: C) {
+        add_cmd("FUNCTION LOAD REPLACE");
+        add_arg(code);
+    }
+
+    fn function_restore<P: ToRedisArgs>(payload: P) {
+        add_cmd("FUNCTION RESTORE");
+        add_arg(payload);
+    }
+
+    fn function_restore_with_policy<P: ToRedisArgs, Q: ToRedisArgs>(payload: P, policy: Q) {
+        add_cmd("FUNCTION RESTORE");
+        add_arg(payload);
+        add_arg(policy);
+    }
+
+    fn get<K: ToRedisArgs>(key: K) {
         add_cmd("GET");
         add_arg(key);
     }
 
-    fn getrange<K: ToString>(key: K, start_range: i64, end_range: i64) {
+    fn getrange<K: ToRedisArgs>(key: K, start_range: i64, end_range: i64) {
         add_cmd("GETRANGE");
         add_arg(key);
         add_arg(start_range);
         add_arg(end_range);
     }
 
-    fn hdel<K: ToString, F: ToString>(key: K, field: F) {
+    fn hdel<K: ToRedisArgs, F: ToRedisArgs>(key: K, field: F) {
         add_cmd("HDEL");
         add_arg(key);
         add_arg(field);
     }
 
-    fn hmdel<K: ToString, V: ToString>(key: K, fields: Vec<V>) {
+    fn hmdel<K: ToRedisArgs, V: ToRedisArgs>(key: K, fields: Vec<V>) {
         add_cmd("HDEL");
         add_arg(key);
         add_args(fields);
     }
 
-    fn hexists<K: ToString, F: ToString>(key: K, field: F) {
+    fn hello(protover: u8) {
+        add_cmd("HELLO");
+        add_arg(protover);
+    }
+
+    fn hello_auth<U: ToRedisArgs, P: ToRedisArgs>(protover: u8, username: U, password: P) {
+        add_cmd("HELLO");
+        add_arg(protover);
+        add_arg("AUTH");
+        add_arg(username);
+        add_arg(password);
+    }
+
+    fn hexists<K: ToRedisArgs, F: ToRedisArgs>(key: K, field: F) {
         add_cmd("HEXISTS");
         add_arg(key);
         add_arg(field);
     }
 
-    fn hget<K: ToString, F: ToString>(key: K, field: F) {
+    fn hget<K: ToRedisArgs, F: ToRedisArgs>(key: K, field: F) {
         add_cmd("HGET");
         add_arg(key);
         add_arg(field);
     }
 
-    fn hgetall<K: ToString>(key: K) {
+    fn hgetall<K: ToRedisArgs>(key: K) {
         add_cmd("HGETALL");
         add_arg(key);
     }
 
-    fn hincrby<K: ToString, F: ToString>(key: K, field: F, increment: i64) {
+    fn hincrby<K: ToRedisArgs, F: ToRedisArgs>(key: K, field: F, increment: i64) {
         add_cmd("HINCRBY");
         add_arg(key);
         add_arg(field);
         add_arg(increment);
     }
 
-    fn hincrbyfloat<K: ToString, F: ToString>(key: K, field: F, increment: f64) {
+    fn hincrbyfloat<K: ToRedisArgs, F: ToRedisArgs>(key: K, field: F, increment: f64) {
         add_cmd("HINCRBYBYFLOAT");
         add_arg(key);
         add_arg(field);
         add_arg(increment);
     }
 
-    fn hkeys<K: ToString>(key: K) {
+    fn hkeys<K: ToRedisArgs>(key: K) {
         add_cmd("HKEYS");
         add_arg(key);
     }
 
-    fn hlen<K: ToString>(key: K) {
+    fn hlen<K: ToRedisArgs>(key: K) {
         add_cmd("HLEN");
         add_arg(key);
     }
 
-    fn hmget<K: ToString, F: ToString>(key: K, fields: Vec<F>) {
+    fn hmget<K: ToRedisArgs, F: ToRedisArgs>(key: K, fields: Vec<F>) {
         add_cmd("HMGET");
         add_arg(key);
         add_args(fields);
     }
 
-    fn hmset<K: ToString>(key: K, fields: HashMap<String, K>) {
+    fn hmset<K: ToRedisArgs, F: ToRedisArgs, V: ToRedisArgs>(key: K, fields: HashMap<F, V>) {
         add_cmd("HMSET");
         add_arg(key);
         add_arg_map(fields);
     }
 
-    fn hset<K: ToString, F: ToString, V: ToString>(key: K, field: F, value: V) {
+    fn hset<K: ToRedisArgs, F: ToRedisArgs, V: ToRedisArgs>(key: K, field: F, value: V) {
         add_cmd("HSET");
         add_arg(key);
         add_arg(field);
         add_arg(value);
     }
 
-    fn hstrlen<K: ToString, F: ToString>(key: K, field: F) {
+    fn hstrlen<K: ToRedisArgs, F: ToRedisArgs>(key: K, field: F) {
         add_cmd("HSTRLEN");
         add_arg(key);
         add_arg(field);
     }
 
-    fn hsetnx<K: ToString, F: ToString, V: ToString>(key: K, field: F, value: V) {
+    fn hscan<K: ToRedisArgs>(key: K, cursor: u64, options: &ScanOptions) {
+        add_cmd("HSCAN");
+        add_arg(key);
+        add_arg(cursor);
+        add_arg(options.get_pattern().map(|pattern| ("MATCH", pattern)));
+        add_arg(options.get_count().map(|count| ("COUNT", count)));
+    }
+
+    fn hsetnx<K: ToRedisArgs, F: ToRedisArgs, V: ToRedisArgs>(key: K, field: F, value: V) {
         add_cmd("HSETNX");
         add_arg(key);
         add_arg(field);
         add_arg(value);
     }
 
-    fn hvals<K: ToString>(key: K) {
+    fn hvals<K: ToRedisArgs>(key: K) {
         add_cmd("HVALS");
         add_arg(key);
     }
 
-    fn lindex<K: ToString>(key: K, index: i32) {
+    fn lindex<K: ToRedisArgs>(key: K, index: i32) {
         add_cmd("LINDEX");
         add_arg(key);
         add_arg(index);
     }
 
-    fn linsert_after<K: ToString, P: ToString, V: ToString>(key: K, pivot: P, value: V) {
+    fn linsert_after<K: ToRedisArgs, P: ToRedisArgs, V: ToRedisArgs>(key: K, pivot: P, value: V) {
         add_cmd("LINSERT");
         add_arg(key);
         add_arg("AFTER");
@@ -505,7 +1006,7 @@ generate_command_traits!{
         add_arg(value);
     }
 
-    fn linsert_before<K: ToString, P: ToString, V: ToString>(key: K, pivot: P, value: V) {
+    fn linsert_before<K: ToRedisArgs, P: ToRedisArgs, V: ToRedisArgs>(key: K, pivot: P, value: V) {
         add_cmd("LINSERT");
         add_arg(key);
         add_arg("BEFORE");
@@ -513,56 +1014,56 @@ generate_command_traits!{
         add_arg(value);
     }
 
-    fn llen<K: ToString>(key: K) {
+    fn llen<K: ToRedisArgs>(key: K) {
         add_cmd("LLEN");
         add_arg(key);
     }
 
-    fn lpop<K: ToString>(key: K) {
+    fn lpop<K: ToRedisArgs>(key: K) {
         add_cmd("LPOP");
         add_arg(key);
     }
 
-    fn lpush<K: ToString, V: ToString>(key: K, value: V) {
+    fn lpush<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
         add_cmd("LPUSH");
         add_arg(key);
         add_arg(value);
     }
 
-    fn mlpush<K: ToString, V: ToString>(key: K, values: Vec<V>) {
+    fn mlpush<K: ToRedisArgs, V: ToRedisArgs>(key: K, values: Vec<V>) {
         add_cmd("LPUSH");
         add_arg(key);
         add_args(values);
     }
 
-    fn lpushx<K: ToString, V: ToString>(key: K, value: V) {
+    fn lpushx<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
         add_cmd("LPUSHX");
         add_arg(key);
         add_arg(value);
     }
 
-    fn lrange<K: ToString>(key: K, start: i32, end: i32) {
+    fn lrange<K: ToRedisArgs>(key: K, start: i32, end: i32) {
         add_cmd("LRANGE");
         add_arg(key);
         add_arg(start);
         add_arg(end);
     }
 
-    fn lrem<K: ToString, V: ToString>(key: K, count: i32, value: V) {
+    fn lrem<K: ToRedisArgs, V: ToRedisArgs>(key: K, count: i32, value: V) {
         add_cmd("LREM");
         add_arg(key);
         add_arg(count);
         add_arg(value);
     }
 
-    fn lset<K: ToString, V: ToString>(key: K, index: i32, value: V) {
+    fn lset<K: ToRedisArgs, V: ToRedisArgs>(key: K, index: i32, value: V) {
         add_cmd("LSET");
         add_arg(key);
         add_arg(index);
         add_arg(value);
     }
 
-    fn ltrim<K: ToString>(key: K, start: i32, end: i32) {
+    fn ltrim<K: ToRedisArgs>(key: K, start: i32, end: i32) {
         add_cmd("LTRIM");
         add_arg(key);
         add_arg(start);
@@ -573,88 +1074,119 @@ generate_command_traits!{
         add_cmd("MULTI");
     }
 
-    fn rename<K: ToString, N: ToString>(key: K, new_key: N) {
+    fn ping() {
+        add_cmd("PING");
+    }
+
+    fn pubsub_channels<P: ToRedisArgs>(pattern: P) {
+        add_cmd("PUBSUB");
+        add_arg("CHANNELS");
+        add_arg(pattern);
+    }
+
+    fn pubsub_numpat() {
+        add_cmd("PUBSUB");
+        add_arg("NUMPAT");
+    }
+
+    fn pubsub_numsub<C: ToRedisArgs>(channels: C) {
+        add_cmd("PUBSUB");
+        add_arg("NUMSUB");
+        add_arg(channels);
+    }
+
+    fn rename<K: ToRedisArgs, N: ToRedisArgs>(key: K, new_key: N) {
         add_cmd("RENAME");
         add_arg(key);
         add_arg(new_key);
     }
 
-    fn renamenx<K: ToString, N: ToString>(key: K, new_key: N) {
+    fn renamenx<K: ToRedisArgs, N: ToRedisArgs>(key: K, new_key: N) {
         add_cmd("RENAMENX");
         add_arg(key);
         add_arg(new_key);
     }
 
-    fn rpop<K: ToString>(key: K) {
+    fn rpop<K: ToRedisArgs>(key: K) {
         add_cmd("RPOP");
         add_arg(key);
     }
 
-    fn rpoplpush<S: ToString, D: ToString>(source: S, dest: D) {
+    fn rpoplpush<S: ToRedisArgs, D: ToRedisArgs>(source: S, dest: D) {
         add_cmd("RPOPLPUSH");
         add_arg(source);
         add_arg(dest);
     }
 
-    fn rpush<K: ToString, V: ToString>(key: K, value: V) {
+    fn rpush<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
         add_cmd("RPUSH");
         add_arg(key);
         add_arg(value);
     }
 
-    fn mrpush<K: ToString, V: ToString>(key: K, values: Vec<V>) {
+    fn mrpush<K: ToRedisArgs, V: ToRedisArgs>(key: K, values: Vec<V>) {
         add_cmd("RPUSH");
         add_arg(key);
         add_args(values);
     }
 
-    fn rpushx<K: ToString, V: ToString>(key: K, value: V) {
+    fn rpushx<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
         add_cmd("RPUSHX");
         add_arg(key);
         add_arg(value);
     }
 
-    fn sadd<K: ToString, M: ToString>(key: K, member: M) {
+    fn sadd<K: ToRedisArgs, M: ToRedisArgs>(key: K, member: M) {
         add_cmd("SADD");
         add_arg(key);
         add_arg(member);
     }
 
-    fn msadd<K: ToString, M: ToString>(key: K, members: Vec<M>) {
+    fn msadd<K: ToRedisArgs, M: ToRedisArgs>(key: K, members: Vec<M>) {
         add_cmd("SADD");
         add_arg(key);
         add_args(members);
     }
 
-    fn sadd_binary<K: ToString>(key: K, member: &[u8]) {
-        add_cmd("SADD");
-        add_arg(key);
-        add_binary_arg(member);
+    fn scan(cursor: u64, options: &ScanOptions) {
+        add_cmd("SCAN");
+        add_arg(cursor);
+        add_arg(options.get_pattern().map(|pattern| ("MATCH", pattern)));
+        add_arg(options.get_count().map(|count| ("COUNT", count)));
+        add_arg(options.get_type().map(|key_type| ("TYPE", key_type)));
     }
 
-    fn scard<K: ToString>(key: K) {
+    fn scard<K: ToRedisArgs>(key: K) {
         add_cmd("SCARD");
         add_arg(key);
     }
 
+    fn script_exists<H: ToRedisArgs>(sha1s: H) {
+        add_cmd("SCRIPT EXISTS");
+        add_arg(sha1s);
+    }
+
+    fn script_flush() {
+        add_cmd("SCRIPT FLUSH");
+    }
+
+    fn script_load<S: ToRedisArgs>(script: S) {
+        add_cmd("SCRIPT LOAD");
+        add_arg(script);
+    }
+
     fn select(db_index: i32){
         add_cmd("SELECT");
         add_arg(db_index);
     }
 
-    fn set<K: ToString, V: ToString>(key: K, value: V) {
+    fn set<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
         add_cmd("SET");
         add_arg(key);
         add_arg(value);
     }
 
-    fn set_binary<K: ToString>(key: K, value: &[u8]) {
-        add_cmd("SET");
-        add_arg(key);
-        add_binary_arg(value);
-    }
-
-    fn setex<K: ToString, V: ToString>(key: K, value: V, expiry: i64) {
+    fn setex<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V, expiry: i64) {
         add_cmd("SET");
         add_arg(key);
         add_arg(value);
@@ -662,7 +1194,7 @@ generate_command_traits!{
         add_arg(expiry);
     }
 
-    fn psetex<K: ToString, V: ToString>(key: K, value: V, expiry: i64) {
+    fn psetex<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V, expiry: i64) {
         add_cmd("SET");
         add_arg(key);
         add_arg(value);
@@ -670,21 +1202,21 @@ generate_command_traits!{
         add_arg(expiry);
     }
 
-    fn setnx<K: ToString, V: ToString>(key: K, value: V) {
+    fn setnx<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
         add_cmd("SET");
         add_arg(key);
         add_arg(value);
         add_arg("NX");
     }
 
-    fn setxx<K: ToString, V: ToString>(key: K, value: V) {
+    fn setxx<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
         add_cmd("SET");
         add_arg(key);
         add_arg(value);
         add_arg("XX");
     }
 
-    fn setex_nx<K: ToString, V: ToString>(key: K, value: V, expiry: i64) {
+    fn setex_nx<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V, expiry: i64) {
         add_cmd("SET");
         add_arg(key);
         add_arg(value);
@@ -693,7 +1225,7 @@ generate_command_traits!{
         add_arg("NX");
     }
 
-    fn setex_xx<K: ToString, V: ToString>(key: K, value: V, expiry: i64) {
+    fn setex_xx<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V, expiry: i64) {
         add_cmd("SET");
         add_arg(key);
         add_arg(value);
@@ -702,7 +1234,7 @@ generate_command_traits!{
         add_arg("XX");
     }
 
-    fn psetex_nx<K: ToString, V: ToString>(key: K, value: V, expiry: i64) {
+    fn psetex_nx<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V, expiry: i64) {
         add_cmd("SET");
         add_arg(key);
         add_arg(value);
@@ -711,7 +1243,7 @@ generate_command_traits!{
         add_arg("NX");
     }
 
-    fn psetex_xx<K: ToString, V: ToString>(key: K, value: V, expiry: i64) {
+    fn psetex_xx<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V, expiry: i64) {
         add_cmd("SET");
         add_arg(key);
         add_arg(value);
@@ -720,60 +1252,68 @@ generate_command_traits!{
         add_arg("XX");
     }
 
-    fn setbit<K: ToString>(key: K, offset: u32, bit: u8) {
+    fn setbit<K: ToRedisArgs>(key: K, offset: u32, bit: u8) {
         add_cmd("SETBIT");
         add_arg(key);
         add_arg(offset);
         add_arg(bit);
     }
 
-    fn setrange<K: ToString, V: ToString>(key: K, offset: u32, value: V) {
+    fn setrange<K: ToRedisArgs, V: ToRedisArgs>(key: K, offset: u32, value: V) {
         add_cmd("SETRANGE");
         add_arg(key);
         add_arg(offset);
         add_arg(value);
     }
 
-    fn sismember<K: ToString, M: ToString>(key: K, member: M) {
+    fn sismember<K: ToRedisArgs, M: ToRedisArgs>(key: K, member: M) {
         add_cmd("SISMEMBER");
         add_arg(key);
         add_arg(member);
     }
 
-    fn smembers<K: ToString>(key: K) {
+    fn smembers<K: ToRedisArgs>(key: K) {
         add_cmd("SMEMBERS");
         add_arg(key);
     }
 
-    fn spop<K: ToString>(key: K) {
+    fn spop<K: ToRedisArgs>(key: K) {
         add_cmd("SPOP");
         add_arg(key);
     }
 
-    fn spop_count<K: ToString>(key: K, count: u32) {
+    fn spop_count<K: ToRedisArgs>(key: K, count: u32) {
         add_cmd("SPOP");
         add_arg(key);
         add_arg(count);
     }
 
-    fn srem<K: ToString, M: ToString>(key: K, member: M) {
+    fn srem<K: ToRedisArgs, M: ToRedisArgs>(key: K, member: M) {
         add_cmd("SREM");
         add_arg(key);
         add_arg(member);
     }
 
-    fn msrem<K: ToString, M: ToString>(key: K, members: Vec<M>) {
+    fn msrem<K: ToRedisArgs, M: ToRedisArgs>(key: K, members: Vec<M>) {
         add_cmd("SREM");
         add_arg(key);
         add_args(members);
     }
 
-    fn strlen<K: ToString>(key: K) {
+    fn sscan<K: ToRedisArgs>(key: K, cursor: u64, options: &ScanOptions) {
+        add_cmd("SSCAN");
+        add_arg(key);
+        add_arg(cursor);
+        add_arg(options.get_pattern().map(|pattern| ("MATCH", pattern)));
+        add_arg(options.get_count().map(|count| ("COUNT", count)));
+    }
+
+    fn strlen<K: ToRedisArgs>(key: K) {
         add_cmd("STRLEN");
         add_arg(key);
     }
 
-    fn ttl<K: ToString>(key: K) {
+    fn ttl<K: ToRedisArgs>(key: K) {
         add_cmd("TTL");
         add_arg(key);
     }
@@ -782,31 +1322,24 @@ generate_command_traits!{
         add_cmd("UNWATCH");
     }
 
-    fn watch<K: ToString>(key: K) {
+    fn watch<K: ToRedisArgs>(key: K) {
         add_cmd("WATCH");
         add_arg(key);
     }
 
-    fn mwatch<K: ToString>(keys: Vec<K>) {
+    fn mwatch<K: ToRedisArgs>(keys: Vec<K>) {
         add_cmd("WATCH");
         add_args(keys);
     }
 
-    fn zadd<K: ToString, V: ToString>(key: K, score: f64, member: V) {
+    fn zadd<K: ToRedisArgs, V: ToRedisArgs>(key: K, score: f64, member: V) {
         add_cmd("ZADD");
         add_arg(key);
         add_arg(score);
         add_arg(member);
     }
 
-    fn zadd_binary<K: ToString>(key: K, score: f64, member: &[u8]) {
-        add_cmd("ZADD");
-        add_arg(key);
-        add_arg(score);
-        add_binary_arg(member);
-    }
-
-    fn zaddnx<K: ToString, V: ToString>(key: K, score: f64, member: V) {
+    fn zaddnx<K: ToRedisArgs, V: ToRedisArgs>(key: K, score: f64, member: V) {
         add_cmd("ZADD");
         add_arg(key);
         add_arg("NX");
@@ -814,7 +1347,7 @@ generate_command_traits!{
         add_arg(member);
     }
 
-    fn zaddxx<K: ToString, V: ToString>(key: K, score: f64, member: V) {
+    fn zaddxx<K: ToRedisArgs, V: ToRedisArgs>(key: K, score: f64, member: V) {
         add_cmd("ZADD");
         add_arg(key);
         add_arg("XX");
@@ -822,7 +1355,7 @@ generate_command_traits!{
         add_arg(member);
     }
 
-    fn zaddnx_ch<K: ToString, V: ToString>(key: K, score: f64, member: V) {
+    fn zaddnx_ch<K: ToRedisArgs, V: ToRedisArgs>(key: K, score: f64, member: V) {
         add_cmd("ZADD");
         add_arg(key);
         add_arg("NX");
@@ -831,7 +1364,7 @@ generate_command_traits!{
         add_arg(member);
     }
 
-    fn zaddxx_ch<K: ToString, V: ToString>(key: K, score: f64, member: V) {
+    fn zaddxx_ch<K: ToRedisArgs, V: ToRedisArgs>(key: K, score: f64, member: V) {
         add_cmd("ZADD");
         add_arg(key);
         add_arg("XX");
@@ -840,52 +1373,52 @@ generate_command_traits!{
         add_arg(member);
     }
 
-    fn zcard<K: ToString>(key: K) {
+    fn zcard<K: ToRedisArgs>(key: K) {
         add_cmd("ZCARD");
         add_arg(key);
     }
 
-    fn zcount<K: ToString, S: ToString, E: ToString>(key: K, start_range: S, end_range: E) {
+    fn zcount<K: ToRedisArgs, S: ToRedisArgs, E: ToRedisArgs>(key: K, start_range: S, end_range: E) {
         add_cmd("ZCOUNT");
         add_arg(key);
         add_arg(start_range);
         add_arg(end_range);
     }
 
-    fn zincrby<K: ToString, V: ToString>(key: K, increment: f64, member: V) {
+    fn zincrby<K: ToRedisArgs, V: ToRedisArgs>(key: K, increment: f64, member: V) {
         add_cmd("ZINCRBY");
         add_arg(key);
         add_arg(increment);
         add_arg(member);
     }
 
-    fn zlexcount<K: ToString, S: ToString, E: ToString>(key: K, min: S, max: E) {
+    fn zlexcount<K: ToRedisArgs, S: ToRedisArgs, E: ToRedisArgs>(key: K, min: S, max: E) {
         add_cmd("ZLEXCOUNT");
         add_arg(key);
         add_arg(min);
         add_arg(max);
     }
 
-    fn zrem<K: ToString, M: ToString>(key: K, member: M) {
+    fn zrem<K: ToRedisArgs, M: ToRedisArgs>(key: K, member: M) {
         add_cmd("ZREM");
         add_arg(key);
         add_arg(member);
     }
 
-    fn mzrem<K: ToString, M: ToString>(key: K, members: Vec<M>) {
+    fn mzrem<K: ToRedisArgs, M: ToRedisArgs>(key: K, members: Vec<M>) {
         add_cmd("ZREM");
         add_arg(key);
         add_args(members);
     }
 
-    fn zrange<K: ToString>(key: K, start_range: i64, end_range: i64) {
+    fn zrange<K: ToRedisArgs>(key: K, start_range: i64, end_range: i64) {
         add_cmd("ZRANGE");
         add_arg(key);
         add_arg(start_range);
         add_arg(end_range);
     }
 
-    fn zrange_with_scores<K: ToString>(key: K, start_range: i64, end_range: i64) {
+    fn zrange_with_scores<K: ToRedisArgs>(key: K, start_range: i64, end_range: i64) {
         add_cmd("ZRANGE");
         add_arg(key);
         add_arg(start_range);
@@ -893,20 +1426,28 @@ generate_command_traits!{
         add_arg("WITHSCORES");
     }
 
-    fn zrevrange<K: ToString>(key: K, start_range: i64, end_range: i64) {
+    fn zrevrange<K: ToRedisArgs>(key: K, start_range: i64, end_range: i64) {
         add_cmd("ZREVRANGE");
         add_arg(key);
         add_arg(start_range);
         add_arg(end_range);
     }
 
-    fn zrevrange_with_scores<K: ToString>(key: K, start_range: i64, end_range: i64) {
+    fn zrevrange_with_scores<K: ToRedisArgs>(key: K, start_range: i64, end_range: i64) {
         add_cmd("ZREVRANGE");
         add_arg(key);
         add_arg(start_range);
         add_arg(end_range);
         add_arg("WITHSCORES");
     }
+
+    fn zscan<K: ToRedisArgs>(key: K, cursor: u64, options: &ScanOptions) {
+        add_cmd("ZSCAN");
+        add_arg(key);
+        add_arg(cursor);
+        add_arg(options.get_pattern().map(|pattern| ("MATCH", pattern)));
+        add_arg(options.get_count().map(|count| ("COUNT", count)));
+    }
         
 }
 
@@ -1,5 +1,5 @@
+use results::RedisResult;
 use std::error;
-use std::error::Error;
 use std::fmt;
 use std::io;
 use std::num;
@@ -10,55 +10,198 @@ use types::{PubSubType, SenderType};
 #[derive(Debug, Clone)]
 pub enum ParsingError {
     BadIdentifier(String),
+    InvalidValue(String),
+    InvalidUrl(String),
 }
 
 impl fmt::Display for ParsingError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             ParsingError::BadIdentifier(ref err) => write!(f, "Invalid identifer: {}", err),
+            ParsingError::InvalidValue(ref err) => write!(f, "Invalid value: {}", err),
+            ParsingError::InvalidUrl(ref err) => write!(f, "Invalid url: {}", err),
         }
     }
 }
 
-impl error::Error for ParsingError {
-    fn description(&self) -> &str {
-        match *self {
-            ParsingError::BadIdentifier(ref err) => err,
+impl error::Error for ParsingError {}
+
+/// The kind of an error replied by redis, given by the code starting the error message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerErrorKind {
+    Err,
+    WrongType,
+    Moved,
+    Ask,
+    TryAgain,
+    Loading,
+    NoScript,
+    Busy,
+    ReadOnly,
+    NoAuth,
+    ExecAbort,
+    CrossSlot,
+    ClusterDown,
+    MasterDown,
+    NoPerm,
+    WrongPass,
+    NoReplicas,
+    OutOfMemory,
+    /// Any other code, empty when the message does not start with one
+    Other(String),
+}
+
+impl ServerErrorKind {
+    /// Get the kind matching an error code
+    pub fn from_code(code: &str) -> ServerErrorKind {
+        match code {
+            "ERR" => ServerErrorKind::Err,
+            "WRONGTYPE" => ServerErrorKind::WrongType,
+            "MOVED" => ServerErrorKind::Moved,
+            "ASK" => ServerErrorKind::Ask,
+            "TRYAGAIN" => ServerErrorKind::TryAgain,
+            "LOADING" => ServerErrorKind::Loading,
+            "NOSCRIPT" => ServerErrorKind::NoScript,
+            "BUSY" => ServerErrorKind::Busy,
+            "READONLY" => ServerErrorKind::ReadOnly,
+            "NOAUTH" => ServerErrorKind::NoAuth,
+            "EXECABORT" => ServerErrorKind::ExecAbort,
+            "CROSSSLOT" => ServerErrorKind::CrossSlot,
+            "CLUSTERDOWN" => ServerErrorKind::ClusterDown,
+            "MASTERDOWN" => ServerErrorKind::MasterDown,
+            "NOPERM" => ServerErrorKind::NoPerm,
+            "WRONGPASS" => ServerErrorKind::WrongPass,
+            "NOREPLICAS" => ServerErrorKind::NoReplicas,
+            "OOM" => ServerErrorKind::OutOfMemory,
+            code => ServerErrorKind::Other(code.to_string()),
         }
     }
 
-    fn cause(&self) -> Option<&error::Error> {
+    /// Get the error code of the kind
+    pub fn get_code(&self) -> &str {
         match *self {
-            ParsingError::BadIdentifier(ref _err) => Some(self),
+            ServerErrorKind::Err => "ERR",
+            ServerErrorKind::WrongType => "WRONGTYPE",
+            ServerErrorKind::Moved => "MOVED",
+            ServerErrorKind::Ask => "ASK",
+            ServerErrorKind::TryAgain => "TRYAGAIN",
+            ServerErrorKind::Loading => "LOADING",
+            ServerErrorKind::NoScript => "NOSCRIPT",
+            ServerErrorKind::Busy => "BUSY",
+            ServerErrorKind::ReadOnly => "READONLY",
+            ServerErrorKind::NoAuth => "NOAUTH",
+            ServerErrorKind::ExecAbort => "EXECABORT",
+            ServerErrorKind::CrossSlot => "CROSSSLOT",
+            ServerErrorKind::ClusterDown => "CLUSTERDOWN",
+            ServerErrorKind::MasterDown => "MASTERDOWN",
+            ServerErrorKind::NoPerm => "NOPERM",
+            ServerErrorKind::WrongPass => "WRONGPASS",
+            ServerErrorKind::NoReplicas => "NOREPLICAS",
+            ServerErrorKind::OutOfMemory => "OOM",
+            ServerErrorKind::Other(ref code) => code,
+        }
+    }
+}
+
+/// An error replied by redis, split into its kind and the message following the code:
+///
+/// ```
+/// # use redis_client::errors::{ServerError, ServerErrorKind};
+/// let error = ServerError::new("MOVED 3999 127.0.0.1:6381");
+/// assert_eq!(error.kind, ServerErrorKind::Moved);
+/// assert_eq!(error.message, "3999 127.0.0.1:6381");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerError {
+    pub kind: ServerErrorKind,
+    pub message: String,
+}
+
+impl ServerError {
+    /// Parse an error line as sent by redis, without its prefix and its CRLF.
+    /// The code is the first word when it is written in capital letters.
+    pub fn new(error_line: &str) -> ServerError {
+        let error_line = error_line.trim_end_matches(&['\r', '\n'][..]);
+        let (code, message) = match error_line.find(' ') {
+            Some(position) => (&error_line[..position], &error_line[position + 1..]),
+            None => (error_line, ""),
+        };
+
+        let is_code = !code.is_empty() && code.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_' || c == '-');
+        if is_code {
+            ServerError {
+                kind: ServerErrorKind::from_code(code),
+                message: message.to_string(),
+            }
+        } else {
+            ServerError {
+                kind: ServerErrorKind::Other(String::new()),
+                message: error_line.to_string(),
+            }
+        }
+    }
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.kind.get_code(), &self.message[..]) {
+            ("", message) => write!(f, "{}", message),
+            (code, "") => write!(f, "{}", code),
+            (code, message) => write!(f, "{} {}", code, message),
         }
     }
 }
 
+impl error::Error for ServerError {}
+
+/// A response that could not be converted into the requested type
+#[derive(Debug, Clone)]
+pub struct ConversionError {
+    pub target: &'static str,
+    pub value: RedisResult,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot convert {:?} into {}", self.value, self.target)
+    }
+}
+
+impl error::Error for ConversionError {}
+
 #[derive(Debug)]
 pub enum RedisError {
 	Io(io::Error),
 	Utf8(str::Utf8Error),
     ParseInt(num::ParseIntError),
     Parse(ParsingError),
-    Response(String),
+    Response(ServerError),
+    Conversion(ConversionError),
+    /// An argument given to the client which can't be used, detected before anything is sent to redis
+    InvalidInput(String),
+    /// A transaction given up before EXEC, because the watch on its keys can't be trusted anymore
+    TransactionAborted(String),
     MpscRecv(mpsc::RecvError),
-    MpscSendBytes(mpsc::SendError<(SenderType, u32, Vec<u8>)>),
-    MpscSendPubSubBytes(mpsc::SendError<(PubSubType, u32, Vec<u8>)>),
+    MpscSendBytes(mpsc::SendError<(SenderType, u64, Vec<u8>)>),
+    MpscSendPubSubBytes(mpsc::SendError<(PubSubType, u64, Vec<u8>)>),
     MpscTryRecv(mpsc::TryRecvError),
 }
 
 impl Clone for RedisError {
     fn clone(&self) -> RedisError {
         match *self {
-            RedisError::Io(ref err) => RedisError::Io(io::Error::new(err.kind(), err.description())),
-            RedisError::Utf8(ref err) => RedisError::Utf8(err.clone()),
+            RedisError::Io(ref err) => RedisError::Io(io::Error::new(err.kind(), err.to_string())),
+            RedisError::Utf8(err) => RedisError::Utf8(err),
             RedisError::ParseInt(ref err) => RedisError::ParseInt(err.clone()),
             RedisError::Parse(ref err) => RedisError::Parse(err.clone()),
             RedisError::Response(ref err) => RedisError::Response(err.clone()),
-            RedisError::MpscRecv(ref err) => RedisError::MpscRecv(err.clone()),
+            RedisError::Conversion(ref err) => RedisError::Conversion(err.clone()),
+            RedisError::InvalidInput(ref err) => RedisError::InvalidInput(err.clone()),
+            RedisError::TransactionAborted(ref err) => RedisError::TransactionAborted(err.clone()),
+            RedisError::MpscRecv(err) => RedisError::MpscRecv(err),
             RedisError::MpscSendBytes(ref err) => RedisError::MpscSendBytes(err.clone()),
             RedisError::MpscSendPubSubBytes(ref err) => RedisError::MpscSendPubSubBytes(err.clone()),
-            RedisError::MpscTryRecv(ref err) => RedisError::MpscTryRecv(err.clone()),
+            RedisError::MpscTryRecv(err) => RedisError::MpscTryRecv(err),
         }
     }
 }
@@ -71,6 +214,9 @@ impl fmt::Display for RedisError {
             RedisError::ParseInt(ref err) => write!(f, "Parse Int error: {}", err),
             RedisError::Parse(ref err) => write!(f, "Parsing error: {}", err),
             RedisError::Response(ref err) => write!(f, "Response error: {}", err),
+            RedisError::Conversion(ref err) => write!(f, "Conversion error: {}", err),
+            RedisError::InvalidInput(ref err) => write!(f, "Invalid input: {}", err),
+            RedisError::TransactionAborted(ref err) => write!(f, "Transaction aborted: {}", err),
             RedisError::MpscRecv(ref err) => write!(f, "MpscRecv error: {}", err),
             RedisError::MpscSendBytes(ref err) => write!(f, "MpscSendBytes error: {}", err),
             RedisError::MpscSendPubSubBytes(ref err) => write!(f, "MpscSendPubSubBytes error: {}", err),
@@ -80,27 +226,16 @@ impl fmt::Display for RedisError {
 }
 
 impl error::Error for RedisError {
-    fn description(&self) -> &str {
-        match *self {
-            RedisError::Io(ref err) => err.description(),
-            RedisError::Utf8(ref err) => err.description(),
-            RedisError::ParseInt(ref err) => err.description(),
-            RedisError::Parse(ref err) => err.description(),
-            RedisError::Response(ref err) => err,
-            RedisError::MpscRecv(ref err) => err.description(),
-            RedisError::MpscSendBytes(ref err) => err.description(),
-            RedisError::MpscSendPubSubBytes(ref err) => err.description(),
-            RedisError::MpscTryRecv(ref err) => err.description(),
-        }
-    }
-
-    fn cause(&self) -> Option<&error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
             RedisError::Io(ref err) => Some(err),
             RedisError::Utf8(ref err) => Some(err),
             RedisError::ParseInt(ref err) => Some(err),
             RedisError::Parse(ref err) => Some(err),
-            RedisError::Response(ref _err) => Some(self),
+            RedisError::Response(ref err) => Some(err),
+            RedisError::Conversion(ref err) => Some(err),
+            RedisError::InvalidInput(_) => None,
+            RedisError::TransactionAborted(_) => None,
             RedisError::MpscRecv(ref err) => Some(err),
             RedisError::MpscSendBytes(ref err) => Some(err),
             RedisError::MpscSendPubSubBytes(ref err) => Some(err),
@@ -133,8 +268,14 @@ impl From<ParsingError> for RedisError {
     }
 }
 
-impl From<String> for RedisError {
-    fn from(err: String) -> RedisError {
+impl From<ConversionError> for RedisError {
+    fn from(err: ConversionError) -> RedisError {
+        RedisError::Conversion(err)
+    }
+}
+
+impl From<ServerError> for RedisError {
+    fn from(err: ServerError) -> RedisError {
         RedisError::Response(err)
     }
 }
@@ -145,14 +286,14 @@ impl From<mpsc::RecvError> for RedisError {
     }
 }
 
-impl From<mpsc::SendError<(SenderType, u32, Vec<u8>)>> for RedisError {
-    fn from(err: mpsc::SendError<(SenderType, u32, Vec<u8>)>) -> RedisError {
+impl From<mpsc::SendError<(SenderType, u64, Vec<u8>)>> for RedisError {
+    fn from(err: mpsc::SendError<(SenderType, u64, Vec<u8>)>) -> RedisError {
         RedisError::MpscSendBytes(err)
     }
 }
 
-impl From<mpsc::SendError<(PubSubType, u32, Vec<u8>)>> for RedisError {
-    fn from(err: mpsc::SendError<(PubSubType, u32, Vec<u8>)>) -> RedisError {
+impl From<mpsc::SendError<(PubSubType, u64, Vec<u8>)>> for RedisError {
+    fn from(err: mpsc::SendError<(PubSubType, u64, Vec<u8>)>) -> RedisError {
         RedisError::MpscSendPubSubBytes(err)
     }
 }
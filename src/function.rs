@@ -0,0 +1,90 @@
+use cluster::ClusterClient;
+use commands::{CommandBuilder, CommandExecutor, RedisCommand};
+use errors::{ParsingError, RedisError};
+use pool::RedisPool;
+use std::fmt;
+
+/// A FunctionLibrary is the source of a library of redis functions, whose first line names the library:
+///
+/// ```plain
+/// #!lua name=<library name>
+/// ```
+///
+/// Redis keeps the libraries with its data and sends them to the replicas, so a library is loaded once per master:
+/// with load through a RedisClient, load_on_pool for a RedisPool or load_on_cluster on every master of a ClusterClient.
+/// The connections opened afterwards, the reconnections included, can call its functions without loading it again:
+///
+/// ```no_run
+/// # use redis_client::commands::CommandSender;
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// let library = try!(redis_client::FunctionLibrary::new("#!lua name=counters\n\
+///     redis.register_function('add', function(keys, args) return redis.call('INCRBY', keys[1], args[1]) end)"));
+///
+/// let config = redis_client::ConnectionConfig::new("127.0.0.1", 6379);
+/// let pool = try!(redis_client::RedisPool::new(&config, &redis_client::PoolConfig::new()));
+/// try!(library.load_on_pool(&pool));
+/// let counter: i64 = try!(try!(pool.get()).fcall("add", "counter", 5));
+/// # Ok(())}
+/// ```
+#[derive(Clone)]
+pub struct FunctionLibrary {
+    name: String,
+    code: String,
+}
+
+impl FunctionLibrary {
+    /// Create a library from its source, failing when the first line doesn't give the name of the library
+    pub fn new(code: &str) -> Result<FunctionLibrary, RedisError> {
+        let shebang = code.lines().next().unwrap_or("");
+        let name = match shebang.strip_prefix("#!") {
+            Some(header) => header.split_whitespace().filter_map(|word| word.strip_prefix("name=")).next(),
+            None => None,
+        };
+        match name {
+            Some(name) if !name.is_empty() => Ok(FunctionLibrary {
+                name: name.to_string(),
+                code: code.to_string(),
+            }),
+            _ => Err(RedisError::Parse(ParsingError::InvalidValue("the first line of the library has to be #!<engine> name=<library name>".to_string()))),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_code(&self) -> &str {
+        &self.code
+    }
+
+    /// Load the library on the server of the executor, replacing the version it already has
+    pub fn load<E: CommandExecutor>(&self, executor: &mut E) -> Result<(), RedisError> {
+        executor.exec_redis_command(RedisCommand::new().function_load_replace(&self.code[..]))?.try_convert::<String>()?;
+        Ok(())
+    }
+
+    /// Load the library once through a connection of the pool, all of them sharing the server
+    pub fn load_on_pool(&self, pool: &RedisPool) -> Result<(), RedisError> {
+        self.load(&mut pool.get()?)
+    }
+
+    /// Load the library on every master of the cluster, replacing the version they already have. Their replicas get it from them.
+    pub fn load_on_cluster(&self, cluster: &mut ClusterClient) -> Result<(), RedisError> {
+        for reply in cluster.exec_redis_command_on_masters(RedisCommand::new().function_load_replace(&self.code[..]))? {
+            reply.try_convert::<String>()?;
+        }
+        Ok(())
+    }
+
+    /// Delete the library from the server of the executor
+    pub fn delete<E: CommandExecutor>(&self, executor: &mut E) -> Result<(), RedisError> {
+        executor.exec_redis_command(RedisCommand::new().function_delete(&self.name[..]))?.try_convert::<String>()?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FunctionLibrary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis Function Library - NAME = {}", self.name)
+    }
+}
@@ -0,0 +1,131 @@
+use args::ToRedisArgs;
+use commands::{CommandBuilder, CommandExecutor, RedisCommand};
+use errors::RedisError;
+use results::{FromRedisValue, RedisResult};
+use script::Script;
+
+/// A Pipeline sends several commands at once and converts their responses together.
+///
+/// It has a method for each built-in command, so they can be chained like with a RedisCommand. The response of a command
+/// followed by ignore is left out of the results, and an atomic pipeline is sent between MULTI and EXEC.
+///
+/// The results are converted like a single array response, into a tuple or a Vec for example. An error replied to a command
+/// fails the whole conversion unless its type is a `Result<T, RedisError>`, which holds the error of that command only:
+///
+/// ```no_run
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// # let mut client = try!(redis_client::RedisClient::new("127.0.0.1", "6379"));
+/// let (value, length): (Option<String>, Result<i64, redis_client::RedisError>) = try!(redis_client::Pipeline::new()
+///     .set("key", "value").ignore()
+///     .get("key")
+///     .llen("key")
+///     .query(&mut client));
+/// # Ok(())}
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    commands: RedisCommand,
+    ignored: Vec<bool>,
+    atomic: bool,
+    scripts: Vec<Script>,
+}
+
+impl Pipeline {
+    pub fn new() -> Pipeline {
+        Pipeline {
+            commands: RedisCommand::new(),
+            ignored: Vec::new(),
+            atomic: false,
+            scripts: Vec::new(),
+        }
+    }
+
+    /// Send the commands between MULTI and EXEC so they are executed as a transaction.
+    /// The results are the responses of EXEC, without the QUEUED acknowledgements.
+    pub fn atomic(&mut self) -> &mut Pipeline {
+        self.atomic = true;
+        self
+    }
+
+    pub fn is_atomic(&self) -> bool {
+        self.atomic
+    }
+
+    /// Leave the response of the last command out of the results
+    pub fn ignore(&mut self) -> &mut Pipeline {
+        if let Some(ignored) = self.ignored.last_mut() {
+            *ignored = true;
+        }
+        self
+    }
+
+    /// Add the commands of a RedisCommand, to send the commands which are not built-in
+    pub fn add_command(&mut self, redis_command: &RedisCommand) -> &mut Pipeline {
+        self.commands.add_commands(redis_command);
+        self.ignored.resize(self.commands.get_command_nb(), false);
+        self
+    }
+
+    /// Add the invocation of a script with EVALSHA. Before sending the commands, query checks with SCRIPT EXISTS
+    /// that redis knows the scripts of the pipeline and loads the missing ones, so the invocations don't fail with NOSCRIPT.
+    pub fn invoke_script<K: ToRedisArgs, A: ToRedisArgs>(&mut self, script: &Script, keys: K, args: A) -> &mut Pipeline {
+        if !self.scripts.iter().any(|known| known.get_hash() == script.get_hash()) {
+            self.scripts.push(script.clone());
+        }
+        let cmd = &mut RedisCommand::new();
+        cmd.evalsha(script.get_hash(), keys, args);
+        self.add_command(cmd)
+    }
+
+    /// Get the number of commands in the pipeline, ignored ones included
+    pub fn get_command_nb(&self) -> usize {
+        self.commands.get_command_nb()
+    }
+
+    /// Send the commands and convert the responses which are not ignored, as if they were the elements of an array.
+    ///
+    /// A nil EXEC response of an atomic pipeline, meaning a watched key was modified, is converted as it is.
+    pub fn query<T: FromRedisValue, E: CommandExecutor>(&self, executor: &mut E) -> Result<T, RedisError> {
+        let cmd = &mut RedisCommand::new();
+        if self.atomic {
+            cmd.multi().add_commands(&self.commands).exec();
+        } else {
+            cmd.add_commands(&self.commands);
+        }
+        if cmd.get_command_nb() == 0 {
+            return RedisResult::Array(Vec::new()).try_convert::<T>();
+        }
+
+        self.load_scripts(executor)?;
+        let mut results = executor.exec_redis_pipeline_command(cmd)?;
+        if self.atomic {
+            results = match results.pop() {
+                Some(RedisResult::Nil) => return RedisResult::Nil.try_convert::<T>(),
+                Some(exec_result) => exec_result.try_convert::<Vec<RedisResult>>()?,
+                None => Vec::new(),
+            };
+        }
+
+        let results = results.into_iter().zip(self.ignored.iter())
+            .filter(|&(_, ignored)| !ignored)
+            .map(|(result, _)| result)
+            .collect();
+        RedisResult::Array(results).try_convert::<T>()
+    }
+
+    /// Load the scripts of the pipeline which are not in the script cache of redis
+    fn load_scripts<E: CommandExecutor>(&self, executor: &mut E) -> Result<(), RedisError> {
+        if self.scripts.is_empty() {
+            return Ok(());
+        }
+
+        let hashes: Vec<&str> = self.scripts.iter().map(|script| script.get_hash()).collect();
+        let exists = executor.exec_redis_command(RedisCommand::new().script_exists(&hashes))?.try_convert::<Vec<bool>>()?;
+        for (script, exists) in self.scripts.iter().zip(exists) {
+            if !exists {
+                script.load(executor)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,417 @@
+use commands::{CommandBuilder, CommandExecutor, RedisCommand};
+use config::ConnectionConfig;
+use errors::RedisError;
+use redis::RedisClient;
+use results::RedisResult;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// A PoolConfig holds the sizes and the health checks of a RedisPool.
+///
+/// ```
+/// # use std::time::Duration;
+/// let mut pool_config = redis_client::PoolConfig::new();
+/// pool_config.set_max_size(20).set_min_idle(2).set_max_lifetime(Some(Duration::new(1800, 0)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    max_size: usize,
+    min_idle: usize,
+    max_idle: usize,
+    checkout_timeout: Option<Duration>,
+    test_on_checkout: bool,
+    max_lifetime: Option<Duration>,
+}
+
+impl PoolConfig {
+    /// Create a configuration allowing 10 connections, all of them can stay idle.
+    /// A checkout waits 5 seconds at most and pings the connection, which are never closed because of their age.
+    pub fn new() -> PoolConfig {
+        PoolConfig {
+            max_size: 10,
+            min_idle: 0,
+            max_idle: 10,
+            checkout_timeout: Some(Duration::new(5, 0)),
+            test_on_checkout: true,
+            max_lifetime: None,
+        }
+    }
+
+    /// Set the maximum number of connections, idle or checked out
+    pub fn set_max_size(&mut self, max_size: usize) -> &mut PoolConfig {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Set the number of idle connections the pool opens when it is created. When a connection is closed because it is broken,
+    /// too old or doesn't answer to PING, the next checkout opens new ones to get back to min_idle idle connections.
+    pub fn set_min_idle(&mut self, min_idle: usize) -> &mut PoolConfig {
+        self.min_idle = min_idle;
+        self
+    }
+
+    /// Set the maximum number of idle connections, the connections returned to a full pool are closed
+    pub fn set_max_idle(&mut self, max_idle: usize) -> &mut PoolConfig {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Set the maximum duration to wait for a connection when they are all checked out. None means waiting forever.
+    pub fn set_checkout_timeout(&mut self, timeout: Option<Duration>) -> &mut PoolConfig {
+        self.checkout_timeout = timeout;
+        self
+    }
+
+    /// Enable or disable the PING sent to an idle connection before handing it out
+    pub fn set_test_on_checkout(&mut self, test_on_checkout: bool) -> &mut PoolConfig {
+        self.test_on_checkout = test_on_checkout;
+        self
+    }
+
+    /// Set the duration after which a connection is closed instead of being reused. None keeps them forever.
+    pub fn set_max_lifetime(&mut self, max_lifetime: Option<Duration>) -> &mut PoolConfig {
+        self.max_lifetime = max_lifetime;
+        self
+    }
+
+    pub fn get_max_size(&self) -> usize {
+        self.max_size
+    }
+
+    pub fn get_min_idle(&self) -> usize {
+        self.min_idle
+    }
+
+    pub fn get_max_idle(&self) -> usize {
+        self.max_idle
+    }
+
+    pub fn get_checkout_timeout(&self) -> Option<Duration> {
+        self.checkout_timeout
+    }
+
+    pub fn get_test_on_checkout(&self) -> bool {
+        self.test_on_checkout
+    }
+
+    pub fn get_max_lifetime(&self) -> Option<Duration> {
+        self.max_lifetime
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> PoolConfig {
+        PoolConfig::new()
+    }
+}
+
+/// A connection owned by the pool with the time it was opened
+struct PoolEntry {
+    client: RedisClient,
+    created_at: Instant,
+}
+
+/// The connections currently idle and the number of connections opened, idle or not.
+/// `replenish_needed` tells a broken or expired connection was closed when it was returned, the next checkout opens the missing ones.
+struct PoolState {
+    idle: VecDeque<PoolEntry>,
+    connection_nb: usize,
+    replenish_needed: bool,
+}
+
+struct PoolInner {
+    config: ConnectionConfig,
+    pool_config: PoolConfig,
+    state: Mutex<PoolState>,
+    released: Condvar,
+}
+
+/// A RedisPool shares RedisClient connections between threads.
+///
+/// It can be cloned, all the clones using the same connections. The get method borrows a connection
+/// that goes back to the pool when it is dropped:
+///
+/// ```no_run
+/// # use redis_client::commands::CommandSender;
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// let config = redis_client::ConnectionConfig::new("127.0.0.1", 6379);
+/// let pool = redis_client::RedisPool::new(&config, &redis_client::PoolConfig::new())?;
+///
+/// let mut connection = pool.get()?;
+/// let value: Option<String> = connection.get("key")?;
+/// # Ok(())}
+/// ```
+#[derive(Clone)]
+pub struct RedisPool {
+    inner: Arc<PoolInner>,
+}
+
+impl RedisPool {
+    /// Create a pool connecting with the configuration. The min_idle connections are opened right away.
+    pub fn new(config: &ConnectionConfig, pool_config: &PoolConfig) -> Result<RedisPool, RedisError> {
+        let pool = RedisPool {
+            inner: Arc::new(PoolInner {
+                config: config.clone(),
+                pool_config: pool_config.clone(),
+                state: Mutex::new(PoolState {
+                    idle: VecDeque::new(),
+                    connection_nb: 0,
+                    replenish_needed: false,
+                }),
+                released: Condvar::new(),
+            }),
+        };
+        pool.fill_min_idle()?;
+        Ok(pool)
+    }
+
+    /// Borrow a connection. An idle connection is reused when there is one, otherwise a new one is opened
+    /// unless max_size connections are already opened, in which case it waits for a connection to be returned.
+    pub fn get(&self) -> Result<PooledConnection, RedisError> {
+        let deadline = self.inner.pool_config.checkout_timeout.map(|timeout| Instant::now() + timeout);
+        if self.lock_state().replenish_needed {
+            self.replenish();
+        }
+        let mut state = self.lock_state();
+        loop {
+            if let Some(entry) = state.idle.pop_back() {
+                drop(state);
+                match self.check_entry(entry) {
+                    Some(entry) => return Ok(self.wrap(entry)),
+                    None => {
+                        self.discard();
+                        self.replenish();
+                        state = self.lock_state();
+                        continue;
+                    },
+                };
+            }
+
+            if state.connection_nb < self.inner.pool_config.max_size {
+                state.connection_nb += 1;
+                drop(state);
+                return match self.connect() {
+                    Ok(entry) => Ok(self.wrap(entry)),
+                    Err(err) => {
+                        self.discard();
+                        Err(err)
+                    },
+                };
+            }
+
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(RedisError::Io(io::Error::new(io::ErrorKind::TimedOut, "no connection was returned to the pool in time")));
+                    }
+                    self.inner.released.wait_timeout(state, deadline - now).unwrap_or_else(|err| err.into_inner()).0
+                },
+                None => self.inner.released.wait(state).unwrap_or_else(|err| err.into_inner()),
+            };
+        }
+    }
+
+    /// Get the number of connections opened by the pool, idle or checked out
+    pub fn get_connection_nb(&self) -> usize {
+        self.lock_state().connection_nb
+    }
+
+    /// Get the number of idle connections
+    pub fn get_idle_nb(&self) -> usize {
+        self.lock_state().idle.len()
+    }
+
+    pub fn get_config(&self) -> &ConnectionConfig {
+        &self.inner.config
+    }
+
+    pub fn get_pool_config(&self) -> &PoolConfig {
+        &self.inner.pool_config
+    }
+
+    /// Lock the state. A thread panicking while holding the lock leaves it consistent, so the poisoning is ignored.
+    fn lock_state(&self) -> MutexGuard<'_, PoolState> {
+        self.inner.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Open the connections missing to have min_idle idle ones, without exceeding max_size
+    fn fill_min_idle(&self) -> Result<(), RedisError> {
+        loop {
+            {
+                let mut state = self.lock_state();
+                if state.idle.len() >= self.inner.pool_config.min_idle || state.connection_nb >= self.inner.pool_config.max_size {
+                    return Ok(());
+                }
+                state.connection_nb += 1;
+            }
+
+            match self.connect() {
+                // a connection closed right away means max_idle is lower than min_idle
+                Ok(entry) => if self.release(entry, false) {
+                    return Ok(());
+                },
+                Err(err) => {
+                    self.discard();
+                    return Err(err);
+                },
+            };
+        }
+    }
+
+    /// Open the connections missing to have min_idle idle ones after a connection was closed.
+    /// A connection which can't be opened is left to the next checkout.
+    fn replenish(&self) {
+        self.lock_state().replenish_needed = false;
+        let _ = self.fill_min_idle();
+    }
+
+    /// Open a new connection
+    fn connect(&self) -> Result<PoolEntry, RedisError> {
+        Ok(PoolEntry {
+            client: RedisClient::with_config(&self.inner.config)?,
+            created_at: Instant::now(),
+        })
+    }
+
+    /// Check an idle connection before handing it out: it must be younger than max_lifetime and answer to PING
+    fn check_entry(&self, mut entry: PoolEntry) -> Option<PoolEntry> {
+        if self.is_expired(&entry) {
+            return None;
+        }
+        if self.inner.pool_config.test_on_checkout && entry.client.exec_redis_command(RedisCommand::new().ping()).is_err() {
+            return None;
+        }
+        Some(entry)
+    }
+
+    fn is_expired(&self, entry: &PoolEntry) -> bool {
+        match self.inner.pool_config.max_lifetime {
+            Some(max_lifetime) => entry.created_at.elapsed() >= max_lifetime,
+            None => false,
+        }
+    }
+
+    fn wrap(&self, entry: PoolEntry) -> PooledConnection {
+        PooledConnection {
+            pool: self.clone(),
+            entry: Some(entry),
+            broken: false,
+        }
+    }
+
+    /// Take back a connection. It is closed when it is broken, too old or when there are already max_idle idle connections.
+    /// Returns true if it was closed. Nothing is opened here since it runs when a PooledConnection is dropped.
+    fn release(&self, entry: PoolEntry, broken: bool) -> bool {
+        let mut state = self.lock_state();
+        let lost = broken || self.is_expired(&entry);
+        let closed = lost || state.idle.len() >= self.inner.pool_config.max_idle;
+        if closed {
+            state.connection_nb = state.connection_nb.saturating_sub(1);
+            state.replenish_needed |= lost;
+        } else {
+            state.idle.push_back(entry);
+        }
+        drop(state);
+        self.inner.released.notify_one();
+        closed
+    }
+
+    /// Forget a connection that was closed, so another one can be opened in its place
+    fn discard(&self) {
+        let mut state = self.lock_state();
+        state.connection_nb = state.connection_nb.saturating_sub(1);
+        drop(state);
+        self.inner.released.notify_one();
+    }
+}
+
+impl fmt::Debug for RedisPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis Pool - HOST = {} : PORT + {}", self.inner.config.get_host(), self.inner.config.get_port())
+    }
+}
+
+impl fmt::Display for RedisPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis Pool - HOST = {} : PORT + {}", self.inner.config.get_host(), self.inner.config.get_port())
+    }
+}
+
+/// A PooledConnection is a connection borrowed from a RedisPool, it goes back to the pool when it is dropped.
+///
+/// It implements CommandSender and derefs to the RedisClient it wraps, only immutably so every command goes through the connection.
+/// A connection whose command failed for another reason than an error replied by redis (a timeout, a closed socket...) is considered
+/// broken and closed instead of being reused.
+pub struct PooledConnection {
+    pool: RedisPool,
+    entry: Option<PoolEntry>,
+    broken: bool,
+}
+
+impl PooledConnection {
+    /// Execute a RedisCommand
+    pub fn exec_redis_command(&mut self, redis_command: &mut RedisCommand) -> Result<RedisResult, RedisError> {
+        let result = self.client_mut().exec_redis_command(redis_command);
+        self.check_result(result)
+    }
+
+    /// Execute a pipeline of RedisCommand
+    pub fn exec_redis_pipeline_command(&mut self, redis_command: &mut RedisCommand) -> Result<Vec<RedisResult>, RedisError> {
+        let result = self.client_mut().exec_redis_pipeline_command(redis_command);
+        self.check_result(result)
+    }
+
+    fn client_mut(&mut self) -> &mut RedisClient {
+        &mut self.entry.as_mut().expect("the connection is only taken back on drop").client
+    }
+
+    /// Remember whether the connection can still be trusted after a command
+    fn check_result<T>(&mut self, result: Result<T, RedisError>) -> Result<T, RedisError> {
+        match result {
+            Ok(_) | Err(RedisError::Response(_)) => {},
+            Err(_) => self.broken = true,
+        };
+        result
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = RedisClient;
+
+    fn deref(&self) -> &RedisClient {
+        &self.entry.as_ref().expect("the connection is only taken back on drop").client
+    }
+}
+
+impl CommandExecutor for PooledConnection {
+    fn exec_redis_command(&mut self, redis_command: &mut RedisCommand) -> Result<RedisResult, RedisError> {
+        PooledConnection::exec_redis_command(self, redis_command)
+    }
+
+    fn exec_redis_pipeline_command(&mut self, redis_command: &mut RedisCommand) -> Result<Vec<RedisResult>, RedisError> {
+        PooledConnection::exec_redis_pipeline_command(self, redis_command)
+    }
+
+    fn get_key_connection_id(&self, _key: &[u8]) -> Option<u64> {
+        self.get_connection_id()
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            self.pool.release(entry, self.broken);
+        }
+    }
+}
+
+impl fmt::Debug for PooledConnection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Pooled {:?}", self.deref())
+    }
+}
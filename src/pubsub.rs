@@ -0,0 +1,58 @@
+use errors::RedisError;
+use results::{FromRedisValue, RedisResult};
+use std::str;
+
+/// A PubSubMessage is a message published on a channel, given to the subscription callbacks.
+///
+/// The payload is kept as the raw bytes sent by the publisher, so binary formats like MessagePack go through untouched:
+///
+/// ```
+/// let message = redis_client::PubSubMessage::new("news", Some("n*"), b"42".to_vec());
+/// assert_eq!(message.get_payload_str().unwrap(), "42");
+/// assert_eq!(message.decode::<i64>().unwrap(), 42);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PubSubMessage {
+    /// The channel the message was published on
+    pub channel: String,
+    /// The pattern that matched the channel, for the messages received through a pattern subscription
+    pub pattern: Option<String>,
+    pub payload: Vec<u8>,
+}
+
+impl PubSubMessage {
+    pub fn new<C: ToString>(channel: C, pattern: Option<&str>, payload: Vec<u8>) -> PubSubMessage {
+        PubSubMessage {
+            channel: channel.to_string(),
+            pattern: pattern.map(|pattern| pattern.to_string()),
+            payload,
+        }
+    }
+
+    pub fn get_channel(&self) -> &str {
+        &self.channel
+    }
+
+    pub fn get_pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    pub fn get_payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Get the payload as text, failing if it is not valid UTF-8
+    pub fn get_payload_str(&self) -> Result<&str, RedisError> {
+        Ok(str::from_utf8(&self.payload)?)
+    }
+
+    /// Convert the payload like a bulk string response, into a number, a String or any other FromRedisValue
+    pub fn decode<T: FromRedisValue>(&self) -> Result<T, RedisError> {
+        T::from_redis_value(RedisResult::Bytes(self.payload.clone()))
+    }
+
+    /// Take the payload, without copying it
+    pub fn into_payload(self) -> Vec<u8> {
+        self.payload
+    }
+}
@@ -1,122 +1,411 @@
-use errors::{ParsingError, RedisError};
+use errors::{ParsingError, RedisError, ServerError};
 use results::RedisResult;
-use std::io::BufReader;
+use std::io;
 use std::io::prelude::*;
-use std::net::TcpStream;
+use std::str;
 
-/// Reads the TcpStream buffer and parse the result according to the redis protocol specification 
+/// A response parsed from the start of an array of bytes along with the number of bytes it was made of.
+/// The response itself is an error when redis replied with an error.
+type Parsed = (Result<RedisResult, RedisError>, usize);
+
+/// The content of a length prefixed response and the number of bytes it was made of
+type ParsedBlob = (Option<Vec<u8>>, usize);
+
+/// How deep the aggregate responses can be nested. A deeper response is refused, as dropping it could overflow the stack.
+const MAX_NESTING: usize = 512;
+
+/// Reads a buffer and parse the result according to the redis protocol specification
 /// building either RedisResult or RedisError.
+///
+/// The buffer can be anything implementing BufRead: a BufReader wrapping a TcpStream, a file or an in-memory Cursor.
 pub struct Reader;
 
 impl Reader {
-    /// Read the stream expecting one response.
-    /// Determine the type of the response
-    pub fn read(buffer: &mut BufReader<TcpStream>) -> Result<RedisResult, RedisError> {
-        
-        let mut head_line = String::new();
-        try!(buffer.read_line(&mut head_line));
-
-        let identifier = head_line.remove(0);
+    /// Read one response from the buffer, the bytes taken from it being decoded by the decoder.
+    /// When the read fails in the middle of a response, like on a read timeout, the bytes already taken stay in the decoder
+    /// so the next read with the same decoder completes the response.
+    pub fn read<R: BufRead>(buffer: &mut R, decoder: &mut Decoder) -> Result<RedisResult, RedisError> {
+        loop {
+            if let Some(result) = decoder.decode()? {
+                return Ok(result);
+            }
 
-        match identifier{
-            '$' => Reader::read_bulk_string(&head_line, buffer),
-            '*' => Reader::read_array(&head_line, buffer),
-            '+' => Reader::read_string(&head_line),
-            ':' => Reader::read_integer(&head_line),
-            '-' => Reader::read_error(&head_line),
-            _ => Err(RedisError::Parse(ParsingError::BadIdentifier(identifier.to_string()))),
+            let length = {
+                let available = buffer.fill_buf()?;
+                if available.is_empty() {
+                    return Err(Reader::incomplete_error());
+                }
+                decoder.feed(available);
+                available.len()
+            };
+            buffer.consume(length);
         }
     }
 
-    /// Read the stream and expect several responses
-    pub fn read_pipeline(buffer: &mut BufReader<TcpStream>, cmd_nb: usize) -> Result<Vec<RedisResult>, RedisError> {
+    /// Read the buffer and expect several responses. An error replied to one of the commands is kept as a RedisResult::Error.
+    pub fn read_pipeline<R: BufRead>(buffer: &mut R, decoder: &mut Decoder, cmd_nb: usize) -> Result<Vec<RedisResult>, RedisError> {
         let mut results: Vec<RedisResult> = Vec::with_capacity(cmd_nb);
-        let mut remaining_cmd = cmd_nb;
-        loop {
-            if remaining_cmd == 0 {
-                break;
-            }
-            remaining_cmd -= 1;
-
-            match Reader::read(buffer) {
+        for _ in 0..cmd_nb {
+            match Reader::read(buffer, decoder) {
                 Ok(value) => results.push(value),
-                Err(RedisError::Response(err)) => results.push(RedisResult::String(err)),
+                Err(RedisError::Response(err)) => results.push(RedisResult::Error(err)),
                 Err(err) => return Err(err),
             };
         }
         Ok(results)
     }
 
-    /// Read a bulk string response
-    fn read_bulk_string(head_line: & String, buffer: &mut BufReader<TcpStream>) -> Result<RedisResult, RedisError> {
-        let read_byte_nb: i64 = try!(head_line.trim().parse());
+    /// Parse one response from an array of bytes.
+    /// On success it returns the response and the number of bytes it was made of,
+    /// so the remaining bytes can be parsed by another call. The aggregates are decoded without recursion and their nesting
+    /// is limited, so any input can be given to it.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// # use redis_client::reader::Reader;
+    /// let (result, consumed) = Reader::parse(b"+OK\r\n:42\r\n").unwrap();
+    /// assert_eq!(result.convert::<String>(), "OK");
+    /// assert_eq!(consumed, 5);
+    /// ```
+    pub fn parse(bytes: &[u8]) -> Result<(RedisResult, usize), RedisError> {
+        let mut decoder = Decoder::new();
+        decoder.feed(bytes);
+        match decoder.decode()? {
+            Some(result) => Ok((result, bytes.len() - decoder.len())),
+            None => Err(Reader::incomplete_error()),
+        }
+    }
+
+    /// Parse a response which is not an aggregate from the start of an array of bytes.
+    /// Returns None if the bytes do not contain a complete response yet.
+    fn parse_scalar(bytes: &[u8]) -> Result<Option<Parsed>, RedisError> {
+        let (head_line, length) = match Reader::parse_line(bytes)? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
 
-        if read_byte_nb < 0 {
-            Ok(RedisResult::Nil)
-        } else {
-            let mut result: Vec<u8> = Vec::with_capacity((read_byte_nb + 2) as usize);
-            loop {
-                let length = {
-                    let buf = try!(buffer.fill_buf());
-                    result.extend(buf.iter().cloned());
-
-                    buf.len()
-                };
-                
-
-                if result.len() >= (read_byte_nb + 2) as usize {
-                    buffer.consume(length - (result.len() - (read_byte_nb + 2) as usize));
-                    break;
-                } else {
-                    buffer.consume(length);
+        let mut chars = head_line.chars();
+        let identifier = match chars.next() {
+            Some(identifier) => identifier,
+            None => return Err(RedisError::Parse(ParsingError::BadIdentifier(String::new()))),
+        };
+        let head_line = chars.as_str();
+
+        match identifier{
+            '$' => Reader::parse_bulk_string(head_line, bytes, length),
+            '+' => Ok(Some((Reader::read_string(head_line), length))),
+            ':' => Ok(Some((Ok(Reader::read_integer(head_line)?), length))),
+            '-' => Ok(Some((Reader::read_error(head_line), length))),
+            ',' => Ok(Some((Ok(Reader::read_double(head_line)?), length))),
+            '#' => Ok(Some((Ok(Reader::read_boolean(head_line)?), length))),
+            '(' => Ok(Some((Ok(RedisResult::BigNumber(head_line.to_string())), length))),
+            '=' => Reader::parse_verbatim_string(head_line, bytes, length),
+            '!' => Reader::parse_blob_error(head_line, bytes, length),
+            '_' => Ok(Some((Ok(RedisResult::Nil), length))),
+            _ => Err(RedisError::Parse(ParsingError::BadIdentifier(identifier.to_string()))),
+        }
+    }
+
+    /// Parse a line terminated by a CRLF and return it without its line ending along with the length of the whole line
+    fn parse_line(bytes: &[u8]) -> Result<Option<(&str, usize)>, RedisError> {
+        match bytes.windows(2).position(|window| window == b"\r\n") {
+            Some(position) => Ok(Some((str::from_utf8(&bytes[..position])?, position + 2))),
+            None => Ok(None),
+        }
+    }
+
+    /// Parse a bulk string response whose content starts after the head line
+    fn parse_bulk_string(head_line: &str, bytes: &[u8], head_length: usize) -> Result<Option<Parsed>, RedisError> {
+        match Reader::parse_blob(head_line, bytes, head_length)? {
+            Some((Some(blob), length)) => Ok(Some((Ok(RedisResult::Bytes(blob)), length))),
+            Some((None, length)) => Ok(Some((Ok(RedisResult::Nil), length))),
+            None => Ok(None),
+        }
+    }
+
+    /// Parse a verbatim string response: a blob starting with its format and a colon
+    fn parse_verbatim_string(head_line: &str, bytes: &[u8], head_length: usize) -> Result<Option<Parsed>, RedisError> {
+        match Reader::parse_blob(head_line, bytes, head_length)? {
+            Some((Some(blob), length)) => {
+                if blob.len() < 4 || blob[3] != b':' {
+                    return Err(RedisError::Parse(ParsingError::InvalidValue(String::from_utf8_lossy(&blob).into_owned())));
                 }
-            }
-            result.truncate(read_byte_nb as usize);
+                let format = str::from_utf8(&blob[..3])?.to_string();
+                let text = str::from_utf8(&blob[4..])?.to_string();
+                Ok(Some((Ok(RedisResult::VerbatimString(format, text)), length)))
+            },
+            Some((None, length)) => Ok(Some((Ok(RedisResult::Nil), length))),
+            None => Ok(None),
+        }
+    }
+
+    /// Parse a blob error response
+    fn parse_blob_error(head_line: &str, bytes: &[u8], head_length: usize) -> Result<Option<Parsed>, RedisError> {
+        match Reader::parse_blob(head_line, bytes, head_length)? {
+            Some((blob, length)) => {
+                let error = String::from_utf8(blob.unwrap_or_default()).map_err(|err| err.utf8_error())?;
+                Ok(Some((Reader::read_error(&error), length)))
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Parse the content of a length prefixed response (bulk string, verbatim string, blob error), which has to end with a CRLF.
+    /// The content is None for a negative length.
+    fn parse_blob(head_line: &str, bytes: &[u8], head_length: usize) -> Result<Option<ParsedBlob>, RedisError> {
+        let read_byte_nb: i64 = head_line.parse()?;
+
+        if read_byte_nb < 0 {
+            return Ok(Some((None, head_length)));
+        }
+
+        let end = head_length.saturating_add(read_byte_nb as usize);
+        if bytes.len() < end.saturating_add(2) {
+            return Ok(None);
+        }
+        if &bytes[end..end + 2] != b"\r\n" {
+            return Err(RedisError::Parse(ParsingError::InvalidValue(format!("the content of {} bytes is not followed by a CRLF", read_byte_nb))));
+        }
+        Ok(Some((Some(bytes[head_length..end].to_vec()), end + 2)))
+    }
 
-            Ok(RedisResult::Bytes(result))
+    /// Group the elements of a map by key/value pairs
+    fn into_pairs(elements: Vec<RedisResult>) -> Vec<(RedisResult, RedisResult)> {
+        let mut pairs = Vec::with_capacity(elements.len() / 2);
+        let mut elements = elements.into_iter();
+        while let (Some(key), Some(value)) = (elements.next(), elements.next()) {
+            pairs.push((key, value));
         }
+        pairs
     }
 
     /// Read a simple string response
-    fn read_string(simple_str: & String) -> Result<RedisResult, RedisError> {
-        Ok(RedisResult::String(simple_str.trim().to_string()))
+    fn read_string(simple_str: &str) -> Result<RedisResult, RedisError> {
+        Ok(RedisResult::String(simple_str.to_string()))
     }
 
     /// Read an integer response
-    fn read_integer(integer_str: & String) -> Result<RedisResult, RedisError> {
-        Ok(RedisResult::Int(try!(integer_str.trim().parse::<i64>())))
+    fn read_integer(integer_str: &str) -> Result<RedisResult, RedisError> {
+        Ok(RedisResult::Int(integer_str.parse::<i64>()?))
+    }
+
+    /// Read a double response
+    fn read_double(double_str: &str) -> Result<RedisResult, RedisError> {
+        double_str.parse::<f64>()
+            .map(RedisResult::Double)
+            .map_err(|_| RedisError::Parse(ParsingError::InvalidValue(double_str.to_string())))
+    }
+
+    /// Read a boolean response
+    fn read_boolean(boolean_str: &str) -> Result<RedisResult, RedisError> {
+        match boolean_str {
+            "t" => Ok(RedisResult::Boolean(true)),
+            "f" => Ok(RedisResult::Boolean(false)),
+            _ => Err(RedisError::Parse(ParsingError::InvalidValue(boolean_str.to_string()))),
+        }
     }
 
     /// Read an error response
-    fn read_error(error_str: & String) -> Result<RedisResult, RedisError> {
-        Err(RedisError::Response(error_str.to_string()))
-    }
-
-    /// Read an array response
-    fn read_array(array_str: & String, buffer: &mut BufReader<TcpStream>) -> Result<RedisResult, RedisError> {
-        let mut read_elmt_nb: i64 = try!(array_str.trim().parse());
-
-        if read_elmt_nb < 0 {
-            Ok(RedisResult::Nil)
-        } else if read_elmt_nb == 0 {
-            Ok(RedisResult::Array(Vec::new()))
-        }else {
-            let mut result: Vec<RedisResult> = Vec::with_capacity(read_elmt_nb as usize);
-
-            loop {
-                match Reader::read(buffer) {
-                    Ok(value) => result.push(value),
-                    Err(RedisError::Response(err)) => result.push(RedisResult::String(err)),
-                    Err(err) => return Err(err),
-                };
-
-                read_elmt_nb -= 1;
-                if read_elmt_nb == 0 {
-                    break;
-                }
+    fn read_error(error_str: &str) -> Result<RedisResult, RedisError> {
+        Err(RedisError::Response(ServerError::new(error_str)))
+    }
+
+    /// The error returned when the buffer ends in the middle of a response
+    fn incomplete_error() -> RedisError {
+        RedisError::Io(io::Error::new(io::ErrorKind::UnexpectedEof, "incomplete response"))
+    }
+}
+
+/// A Decoder is a stateful parser that accepts bytes as they arrive and returns complete responses.
+///
+/// Unlike the Reader it never blocks: when the bytes received so far do not contain a complete response
+/// it keeps them and tells the caller to feed more.
+///
+/// Example:
+///
+/// ```
+/// # use redis_client::reader::Decoder;
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// let mut decoder = Decoder::new();
+/// decoder.feed(b"$5\r\nhel");
+/// assert!(decoder.decode()?.is_none());
+///
+/// decoder.feed(b"lo\r\n+OK\r\n");
+/// let first: String = decoder.decode()?.unwrap().convert();
+/// let second: String = decoder.decode()?.unwrap().convert();
+/// assert_eq!((first.as_str(), second.as_str()), ("hello", "OK"));
+/// # Ok(())}
+/// ```
+#[derive(Debug, Default)]
+pub struct Decoder {
+    buffer: Vec<u8>,
+    /// Where the response being decoded starts
+    start: usize,
+    /// Where the next element to decode starts
+    position: usize,
+    /// The aggregates containing the next element, the innermost last
+    aggregates: Vec<PartialAggregate>,
+}
+
+/// What decoding the element at the position of a Decoder gave
+enum Step {
+    /// The element is not complete yet
+    Incomplete,
+    /// The element is the head of an aggregate whose elements follow
+    Aggregate,
+    /// The element is a complete response
+    Value(Result<RedisResult, RedisError>),
+}
+
+/// An aggregate response (array, set, push, map or attribute) whose elements are being decoded
+#[derive(Debug)]
+struct PartialAggregate {
+    identifier: char,
+    remaining: usize,
+    elements: Vec<RedisResult>,
+}
+
+impl PartialAggregate {
+    /// Build the response once every element is decoded
+    fn into_result(mut self) -> Result<RedisResult, RedisError> {
+        match self.identifier {
+            '~' => Ok(RedisResult::Set(self.elements)),
+            '>' => Ok(RedisResult::Push(self.elements)),
+            '%' => Ok(RedisResult::Map(Reader::into_pairs(self.elements))),
+            '|' => match self.elements.pop() {
+                Some(RedisResult::Error(err)) => Err(RedisError::Response(err)),
+                value => Ok(RedisResult::Attribute(Box::new(value.unwrap_or(RedisResult::Nil)), Reader::into_pairs(self.elements))),
+            },
+            _ => Ok(RedisResult::Array(self.elements)),
+        }
+    }
+}
+
+impl Decoder {
+    pub fn new() -> Decoder {
+        Decoder {
+            buffer: Vec::new(),
+            start: 0,
+            position: 0,
+            aggregates: Vec::new(),
+        }
+    }
+
+    /// Append received bytes to the ones waiting to be decoded
+    pub fn feed(&mut self, bytes: &[u8]) {
+        if self.start > 0 && self.start >= self.buffer.len() / 2 {
+            self.buffer.drain(..self.start);
+            self.position -= self.start;
+            self.start = 0;
+        }
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Decode the next response.
+    ///
+    /// Returns Ok(None) if more bytes are needed to complete it. The elements of an aggregate response decoded so far
+    /// are kept, so each call only decodes the bytes received since the previous one.
+    /// An error response from redis is returned as an Err and consumed like any other response. Any other error means
+    /// the bytes are not valid redis protocol: they are all discarded as the decoder cannot find the start of the next
+    /// response anymore.
+    pub fn decode(&mut self) -> Result<Option<RedisResult>, RedisError> {
+        loop {
+            let result = match self.decode_element() {
+                Ok(Step::Incomplete) => return Ok(None),
+                Ok(Step::Aggregate) => continue,
+                Ok(Step::Value(result)) => result,
+                Err(err) => {
+                    self.clear();
+                    return Err(err);
+                },
+            };
+
+            if let Some(result) = self.add_to_aggregates(result) {
+                self.start = self.position;
+                return result.map(Some);
             }
-            Ok(RedisResult::Array(result))
         }
     }
 
-}
\ No newline at end of file
+    /// Decode the element at the position: a whole response, or only the head of an aggregate which then waits for its elements
+    fn decode_element(&mut self) -> Result<Step, RedisError> {
+        let bytes = &self.buffer[self.position..];
+        let (head_line, length) = match Reader::parse_line(bytes)? {
+            Some(line) => line,
+            None => return Ok(Step::Incomplete),
+        };
+
+        let mut chars = head_line.chars();
+        let identifier = match chars.next() {
+            Some(identifier @ '*') | Some(identifier @ '~') | Some(identifier @ '>') | Some(identifier @ '%') | Some(identifier @ '|') => identifier,
+            _ => return match Reader::parse_scalar(bytes)? {
+                Some((result, length)) => {
+                    self.position += length;
+                    Ok(Step::Value(result))
+                },
+                None => Ok(Step::Incomplete),
+            },
+        };
+
+        let entry_nb: i64 = chars.as_str().parse()?;
+        self.position += length;
+        if entry_nb < 0 && identifier == '*' {
+            return Ok(Step::Value(Ok(RedisResult::Nil)));
+        }
+
+        // a map or an attribute holds a key and a value by entry, and an attribute is followed by the response it describes
+        let elmt_by_entry = if identifier == '%' || identifier == '|' { 2 } else { 1 };
+        let remaining = (entry_nb.max(0) as usize).saturating_mul(elmt_by_entry) + if identifier == '|' { 1 } else { 0 };
+        let aggregate = PartialAggregate {
+            identifier,
+            remaining,
+            elements: Vec::with_capacity(remaining.min(1024)),
+        };
+        if remaining == 0 {
+            return Ok(Step::Value(aggregate.into_result()));
+        }
+        if self.aggregates.len() >= MAX_NESTING {
+            return Err(RedisError::Parse(ParsingError::InvalidValue(format!("a response is nested more than {} levels deep", MAX_NESTING))));
+        }
+        self.aggregates.push(aggregate);
+        Ok(Step::Aggregate)
+    }
+
+    /// Add a decoded element to the innermost aggregate, building the aggregates it completes.
+    /// Returns the response once the outermost one is complete, or the element itself if it is not in an aggregate.
+    fn add_to_aggregates(&mut self, mut result: Result<RedisResult, RedisError>) -> Option<Result<RedisResult, RedisError>> {
+        while let Some(mut aggregate) = self.aggregates.pop() {
+            aggregate.elements.push(match result {
+                Ok(value) => value,
+                Err(RedisError::Response(err)) => RedisResult::Error(err),
+                Err(err) => return Some(Err(err)),
+            });
+            aggregate.remaining -= 1;
+            if aggregate.remaining > 0 {
+                self.aggregates.push(aggregate);
+                return None;
+            }
+            result = aggregate.into_result();
+        }
+        Some(result)
+    }
+
+    /// Get the number of bytes received but not decoded yet, counting the ones of the response being decoded
+    pub fn len(&self) -> usize {
+        self.buffer.len() - self.start
+    }
+
+    /// Check if there is no bytes waiting to be decoded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Discard every bytes waiting to be decoded
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.start = 0;
+        self.position = 0;
+        self.aggregates.clear();
+    }
+}
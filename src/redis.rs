@@ -1,57 +1,420 @@
-extern crate rand;
-
-use commands::RedisCommand;
+use args::ToRedisArgs;
+use commands::{CommandExecutor, RedisCommand};
+use config::ConnectionConfig;
 use errors::RedisError;
-use reader::Reader;
-use results::RedisResult;
-use self::rand::Rng;
-use std::collections::HashMap;
+use pubsub::PubSubMessage;
+use reader::Decoder;
+use results::{FromRedisValue, RedisResult};
+use scan::{ScanIter, ScanOptions};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::fmt;
-use std::io::BufReader;
+use std::io;
 use std::io::prelude::*;
-use std::net::TcpStream;
+use std::rc::Rc;
 use std::sync::mpsc::*;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
-use std::u32;
-use types::{PubSubType, SenderType};
+use std::time::{Duration, Instant};
+use stream::Stream;
+use types::{ConnectionEvent, ProtocolVersion, PubSubType, SenderType};
+
+type CommandCallback = Box<dyn FnOnce(Result<RedisResult, RedisError>)>;
+type PipelineCallback = Box<dyn FnOnce(Result<Vec<RedisResult>, RedisError>)>;
+/// A subscription callback, shared by the channels or patterns subscribed together
+type SubscriptionCallback = Rc<RefCell<Box<dyn FnMut(PubSubMessage)>>>;
+
+/// The id given to the next connection opened by a RedisClient
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+fn next_connection_id() -> u64 {
+    NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed)
+}
 
 pub struct RedisClient {
-    port: &'static str,
-    host: &'static str,
-    buffer: BufReader<TcpStream>,
+    config: ConnectionConfig,
+    stream: Stream,
+    decoder: Decoder,
+    pending_replies: usize,
+    connected: bool,
+    connection_id: u64,
 }
 
 pub struct RedisClientAsync {
-    port: &'static str,
-    host: &'static str,
-    sender: Sender<(SenderType, u32, Vec<u8>)>,
-    callbacks: HashMap<u32, Box<Fn(Result<RedisResult, RedisError>)>>,
-    receiver: Receiver<(u32, Result<RedisResult, RedisError>)>,
-    pipe_callbacks: HashMap<u32, Box<Fn(Result<Vec<RedisResult>, RedisError>)>>,
-    pipe_receiver: Receiver<(u32, Result<Vec<RedisResult>, RedisError>)>
+    config: ConnectionConfig,
+    sender: Sender<(SenderType, u64, Vec<u8>)>,
+    callbacks: HashMap<u64, CommandCallback>,
+    receiver: Receiver<(u64, Result<RedisResult, RedisError>)>,
+    pipe_callbacks: HashMap<u64, PipelineCallback>,
+    pipe_receiver: Receiver<(u64, Result<Vec<RedisResult>, RedisError>)>,
+    last_request_id: u64
 }
 
 pub struct PubSubArg {
     pub pubsub_type: PubSubType,
-    pub callback: Option<Box<Fn(RedisResult)>>
+    pub callback: Option<Box<dyn FnMut(PubSubMessage)>>
+}
+
+/// What the pubsub threads send back: the response of a command with its request id, a message pushed by redis
+/// or a change of the subscriptions confirmed by redis
+enum PubSubReply {
+    Command(u64, Result<RedisResult, RedisError>),
+    Message(PubSubMessage),
+    ShardMessage(PubSubMessage),
+    Confirmation(String, String),
+}
+
+/// A frame read on a subscribed connection
+pub(crate) enum PubSubFrame {
+    /// A message published on a channel, with the pattern it matched for a pmessage
+    Message(PubSubMessage),
+    /// A message published on a sharded channel
+    ShardMessage(PubSubMessage),
+    /// The confirmation of a subscription or an unsubscription with its kind, the channel or pattern (nil when unsubscribing from nothing)
+    /// and the number of subscriptions left
+    Confirmation(String, Option<String>, i64, RedisResult),
+    /// The response of PING, or of any other command
+    Reply(RedisResult),
+    /// A push the client doesn't handle, like a client side caching invalidation
+    Ignored,
+}
+
+impl PubSubFrame {
+    /// Tell what a frame is. With RESP3 only the pushes can be messages or confirmations, with RESP2 they are arrays starting with their kind.
+    pub(crate) fn classify(frame: RedisResult, protocol: ProtocolVersion) -> PubSubFrame {
+        let (elements, is_push) = match frame {
+            RedisResult::Push(elements) => (elements, true),
+            RedisResult::Array(elements) if protocol == ProtocolVersion::Resp2 => (elements, false),
+            other => return PubSubFrame::Reply(other),
+        };
+
+        let kind = elements.first().cloned().and_then(|kind| kind.try_convert::<String>().ok()).unwrap_or_default().to_lowercase();
+        match (kind.as_str(), elements.len()) {
+            ("message", 3) | ("smessage", 3) => {
+                let mut elements = elements.into_iter().skip(1);
+                let channel = elements.next().map(|channel| channel.convert::<String>()).unwrap_or_default();
+                let message = PubSubMessage::new(channel, None, PubSubFrame::payload(elements.next()));
+                if kind == "smessage" {
+                    PubSubFrame::ShardMessage(message)
+                } else {
+                    PubSubFrame::Message(message)
+                }
+            },
+            ("pmessage", 4) => {
+                let mut elements = elements.into_iter().skip(1);
+                let pattern = elements.next().map(|pattern| pattern.convert::<String>());
+                let channel = elements.next().map(|channel| channel.convert::<String>()).unwrap_or_default();
+                PubSubFrame::Message(PubSubMessage::new(channel, pattern.as_deref(), PubSubFrame::payload(elements.next())))
+            },
+            ("subscribe", 3) | ("psubscribe", 3) | ("ssubscribe", 3) | ("unsubscribe", 3) | ("punsubscribe", 3) | ("sunsubscribe", 3) => {
+                let name = elements[1].clone().try_convert::<Option<String>>().unwrap_or_default();
+                let count = elements[2].clone().try_convert::<i64>().unwrap_or_default();
+                PubSubFrame::Confirmation(kind, name, count, if is_push { RedisResult::Push(elements) } else { RedisResult::Array(elements) })
+            },
+            _ if is_push && kind != "pong" => PubSubFrame::Ignored,
+            _ => PubSubFrame::Reply(if is_push { RedisResult::Push(elements) } else { RedisResult::Array(elements) }),
+        }
+    }
+
+    /// Get the bytes of a payload without going through a String, which would break the binary ones
+    fn payload(payload: Option<RedisResult>) -> Vec<u8> {
+        payload.and_then(|payload| payload.try_convert::<Vec<u8>>().ok()).unwrap_or_default()
+    }
+}
+
+/// The number of frames redis replies to a command sent on a subscribed connection
+#[derive(Clone, Copy)]
+enum ExpectedReplies {
+    /// One frame per channel or pattern, one for the other commands
+    Count(usize),
+    /// An unsubscribe-all gets one confirmation per subscription of its kind, which is unknown when it is sent
+    UnsubscribeAll,
+    PUnsubscribeAll,
+    SUnsubscribeAll,
+}
+
+impl ExpectedReplies {
+    fn new(pubsub_type: &PubSubType) -> ExpectedReplies {
+        match *pubsub_type {
+            PubSubType::Channels(ref names) | PubSubType::Patterns(ref names) | PubSubType::ShardChannels(ref names) => ExpectedReplies::Count(names.len().max(1)),
+            PubSubType::Unsubscribe(ref names) if names.is_empty() => ExpectedReplies::UnsubscribeAll,
+            PubSubType::PUnsubscribe(ref names) if names.is_empty() => ExpectedReplies::PUnsubscribeAll,
+            PubSubType::SUnsubscribe(ref names) if names.is_empty() => ExpectedReplies::SUnsubscribeAll,
+            PubSubType::Unsubscribe(ref names) | PubSubType::PUnsubscribe(ref names) | PubSubType::SUnsubscribe(ref names) => ExpectedReplies::Count(names.len()),
+            PubSubType::Simple | PubSubType::Channel(_) | PubSubType::Pattern(_) | PubSubType::ShardChannel(_) => ExpectedReplies::Count(1),
+        }
+    }
+}
+
+/// A command sent on a pubsub connection and still waiting for all its replies
+struct PendingCommand {
+    /// The request id, None for the subscriptions sent again after a reconnection
+    id: Option<u64>,
+    expected: ExpectedReplies,
+    replies: Vec<RedisResult>,
+}
+
+/// The commands written on the connection of a PubSubClientAsync and not answered yet, the oldest first
+type PendingCommands = Arc<Mutex<VecDeque<PendingCommand>>>;
+
+/// The handle the commands of a PubSubClientAsync are written on, or the error which closed the connection for good.
+/// The reader locks it while it opens a new connection, so no command is written on a lost connection.
+type PubSubWriter = Arc<Mutex<Result<Stream, RedisError>>>;
+
+/// Lock a value shared by the pubsub threads, even if one of them panicked while holding it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn closed_error() -> RedisError {
+    RedisError::Io(io::Error::new(io::ErrorKind::NotConnected, "the pubsub connection is closed"))
+}
+
+/// The thread writing the commands of a PubSubClientAsync as soon as they are queued. Each command is registered
+/// as pending before it is written, so the reader always knows which command a reply belongs to.
+struct PubSubSender {
+    commands: Receiver<(PubSubType, u64, Vec<u8>)>,
+    writer: PubSubWriter,
+    pending: PendingCommands,
+    replies: Sender<PubSubReply>,
+}
+
+impl PubSubSender {
+    /// Run until the client is dropped, then close the connection so the reader stops too
+    fn run(self) {
+        while let Ok((pubsub_type, id, buf)) = self.commands.recv() {
+            self.send_command(&pubsub_type, id, &buf[..]);
+        }
+
+        let mut writer = lock(&self.writer);
+        if let Ok(ref stream) = *writer {
+            let _res = stream.shutdown();
+        }
+        *writer = Err(closed_error());
+    }
+
+    /// Write a command, answering it with the error of the write if it failed, or with the error which closed the connection
+    /// once every attempt to open it again failed
+    fn send_command(&self, pubsub_type: &PubSubType, id: u64, buf: &[u8]) {
+        let mut writer = lock(&self.writer);
+        let stream = match *writer {
+            Ok(ref mut stream) => stream,
+            Err(ref err) => {
+                let _res = self.replies.send(PubSubReply::Command(id, Err(err.clone())));
+                return;
+            },
+        };
+
+        lock(&self.pending).push_back(PendingCommand {
+            id: Some(id),
+            expected: ExpectedReplies::new(pubsub_type),
+            replies: Vec::new(),
+        });
+        if let Err(err) = stream.write_all(buf) {
+            lock(&self.pending).pop_back();
+            // the reader notices the connection is lost and opens a new one
+            let _res = stream.shutdown();
+            let _res = self.replies.send(PubSubReply::Command(id, Err(RedisError::Io(err))));
+        }
+    }
+}
+
+/// The thread reading the connection of a PubSubClientAsync. It routes the replies to the oldest command waiting for one
+/// and the messages to the client, and opens a new connection when it is lost.
+struct PubSubReader {
+    client: RedisClient,
+    writer: PubSubWriter,
+    pending: PendingCommands,
+    replies: Sender<PubSubReply>,
+    channels: HashSet<String>,
+    patterns: HashSet<String>,
+    shard_channels: HashSet<String>,
+}
+
+impl PubSubReader {
+    /// Run until the client is dropped or the connection can't be opened again
+    fn run(mut self) {
+        while self.read_frame() {}
+
+        let mut writer = lock(&self.writer);
+        if let Ok(ref stream) = *writer {
+            let _res = stream.shutdown();
+            *writer = Err(closed_error());
+        }
+    }
+
+    /// Read a frame and route it. Returns false once the client is dropped or the connection can't be opened again.
+    fn read_frame(&mut self) -> bool {
+        let frame = match self.client.read_message() {
+            Ok(frame) => PubSubFrame::classify(frame, self.client.config.get_protocol()),
+            Err(RedisError::Response(err)) => return self.end_command(Err(RedisError::Response(err))),
+            // a read timeout leaves the connection opened
+            Err(err) => return self.client.is_connected() || self.reconnect(&err),
+        };
+
+        match frame {
+            PubSubFrame::Message(message) => self.replies.send(PubSubReply::Message(message)).is_ok(),
+            PubSubFrame::ShardMessage(message) => self.replies.send(PubSubReply::ShardMessage(message)).is_ok(),
+            PubSubFrame::Confirmation(kind, name, count, confirmation) => {
+                if let Some(name) = name {
+                    self.update_subscriptions(&kind, &name);
+                    if self.replies.send(PubSubReply::Confirmation(kind, name)).is_err() {
+                        return false;
+                    }
+                }
+                self.confirm(count, confirmation)
+            },
+            PubSubFrame::Reply(reply) => self.end_command(Ok(reply)),
+            PubSubFrame::Ignored => true,
+        }
+    }
+
+    /// Remember the subscriptions confirmed by redis, to subscribe again after a reconnection
+    fn update_subscriptions(&mut self, kind: &str, name: &str) {
+        match kind {
+            "subscribe" => { self.channels.insert(name.to_string()); },
+            "psubscribe" => { self.patterns.insert(name.to_string()); },
+            "ssubscribe" => { self.shard_channels.insert(name.to_string()); },
+            "unsubscribe" => { self.channels.remove(name); },
+            "punsubscribe" => { self.patterns.remove(name); },
+            "sunsubscribe" => { self.shard_channels.remove(name); },
+            _ => {},
+        };
+    }
+
+    /// Add a confirmation to the replies of the oldest command, which ends once it got all of them.
+    /// The count of an unsubscription is the number of subscriptions left: an unsubscribe-all is over when only the other kinds remain.
+    fn confirm(&mut self, count: i64, confirmation: RedisResult) -> bool {
+        let mut pending = lock(&self.pending);
+        let complete = match pending.front_mut() {
+            Some(command) => {
+                command.replies.push(confirmation);
+                match command.expected {
+                    ExpectedReplies::Count(expected) => command.replies.len() >= expected,
+                    ExpectedReplies::UnsubscribeAll => count as usize <= self.patterns.len(),
+                    ExpectedReplies::PUnsubscribeAll => count as usize <= self.channels.len(),
+                    ExpectedReplies::SUnsubscribeAll => count <= 0,
+                }
+            },
+            // an unsubscription initiated by redis, when a sharded channel moved to another node
+            None => return true,
+        };
+
+        if complete {
+            let mut command = pending.pop_front().expect("the command was just found");
+            drop(pending);
+            let reply = if command.replies.len() == 1 { command.replies.remove(0) } else { RedisResult::Array(command.replies) };
+            return self.send_reply(command.id, Ok(reply));
+        }
+        true
+    }
+
+    /// End the oldest command with a reply, or with the error replied to it
+    fn end_command(&mut self, result: Result<RedisResult, RedisError>) -> bool {
+        let command = lock(&self.pending).pop_front();
+        match command {
+            Some(command) => self.send_reply(command.id, result),
+            None => true,
+        }
+    }
+
+    fn send_reply(&self, id: Option<u64>, result: Result<RedisResult, RedisError>) -> bool {
+        match id {
+            Some(id) => self.replies.send(PubSubReply::Command(id, result)).is_ok(),
+            None => true,
+        }
+    }
+
+    /// End the commands waiting for a reply on a connection that was lost
+    fn fail_pending(&self, err: &RedisError) {
+        let commands: Vec<PendingCommand> = lock(&self.pending).drain(..).collect();
+        for command in commands {
+            let _res = self.send_reply(command.id, Err(err.clone()));
+        }
+    }
+
+    /// Open a new connection after the previous one was lost, and subscribe again to the channels, the patterns and the sharded channels.
+    /// The writer is locked meanwhile, so the commands queued are written once the subscriptions are sent.
+    /// Returns false if the client was dropped or if every attempt failed.
+    fn reconnect(&mut self, err: &RedisError) -> bool {
+        let writer = self.writer.clone();
+        let mut writer = lock(&writer);
+        if writer.is_err() {
+            // closed by the sender once the client was dropped
+            return false;
+        }
+        self.fail_pending(err);
+
+        if let Err(err) = self.client.ensure_connected() {
+            *writer = Err(err);
+            return false;
+        }
+        match self.client.stream.try_clone() {
+            Ok(stream) => *writer = Ok(stream),
+            Err(err) => {
+                *writer = Err(err);
+                return false;
+            },
+        };
+
+        let subscriptions = [
+            ("SUBSCRIBE", &self.channels),
+            ("PSUBSCRIBE", &self.patterns),
+            ("SSUBSCRIBE", &self.shard_channels),
+        ];
+        let mut commands = Vec::new();
+        for &(name, subscribed) in subscriptions.iter() {
+            if !subscribed.is_empty() {
+                let names: Vec<&String> = subscribed.iter().collect();
+                commands.push((subscribed.len(), <&[u8]>::from(RedisCommand::new().add_cmd(name).add_arg(names).end()).to_vec()));
+            }
+        }
+        for (count, buf) in commands {
+            lock(&self.pending).push_back(PendingCommand {
+                id: None,
+                expected: ExpectedReplies::Count(count),
+                replies: Vec::new(),
+            });
+            if self.client.send_command(&buf[..]).is_err() {
+                // the next read notices the connection is lost
+                return true;
+            }
+        }
+        true
+    }
+}
+
+pub struct PubSub {
+    client: RedisClient,
+    messages: VecDeque<PubSubMessage>,
+    channels: BTreeSet<String>,
+    patterns: BTreeSet<String>,
+    failed: bool,
 }
 
 pub struct PubSubClientAsync {
-    port: &'static str,
-    host: &'static str,
-    cmd_sender: Sender<(PubSubType, u32, Vec<u8>)>,
-    receiver: Receiver<(u32, Result<RedisResult, RedisError>)>,
-    cmd_callbacks: HashMap<u32, Box<Fn(Result<RedisResult, RedisError>)>>,
-    channel_callbacks: HashMap<String, Box<Fn(RedisResult)>>,
-    pattern_callbacks: HashMap<String, Box<Fn(RedisResult)>>
+    config: ConnectionConfig,
+    cmd_sender: Sender<(PubSubType, u64, Vec<u8>)>,
+    receiver: Receiver<PubSubReply>,
+    cmd_callbacks: HashMap<u64, CommandCallback>,
+    channel_callbacks: HashMap<String, SubscriptionCallback>,
+    pattern_callbacks: HashMap<String, SubscriptionCallback>,
+    shard_callbacks: HashMap<String, SubscriptionCallback>,
+    channels: BTreeSet<String>,
+    patterns: BTreeSet<String>,
+    shard_channels: BTreeSet<String>,
+    last_request_id: u64
 }
 
 /// A RedisClient is a structure to send command to redis and receive the response.
 /// All RedisClient's methods are performed synchronously.
 /// 
 /// When creating a RedisClient it will automatically create a connection. Therefore when
-/// it is created it uses the host and the port.
+/// it is created it uses either the host and the port, or a ConnectionConfig.
+///
+/// When the connection drops, the command that noticed it fails and the next one reconnects first,
+/// following the ReconnectPolicy of the configuration.
 ///
 /// Example:
 ///
@@ -61,40 +424,230 @@ pub struct PubSubClientAsync {
 /// # Ok(())}
 /// ```
 impl RedisClient {
-    pub fn new(host: &'static str, port: &'static str) -> Result<RedisClient, RedisError> {
-        TcpStream::connect(&*format!("{}:{}", host, port))
-            .map(|tcp_stream| {
-                    // TODO better timeout init
-                    let _res_write = tcp_stream.set_write_timeout(Some(Duration::new(5, 0)));
-                    let _res_read = tcp_stream.set_read_timeout(Some(Duration::new(1, 0)));
-                    RedisClient {
-                        port: port,
-                        host: host,
-                        buffer: BufReader::new(tcp_stream),
-                }
-            })
-            .map_err(|err| RedisError::Io(err))
+    pub fn new<H: ToString, P: ToString>(host: H, port: P) -> Result<RedisClient, RedisError> {
+        RedisClient::new_with_protocol(host, port, ProtocolVersion::Resp2)
+    }
+
+    /// Create a RedisClient speaking the given version of the redis protocol.
+    /// For RESP3 the connection is switched with the HELLO command once it is created.
+    pub fn new_with_protocol<H: ToString, P: ToString>(host: H, port: P, protocol: ProtocolVersion) -> Result<RedisClient, RedisError> {
+        let mut config = ConnectionConfig::new(host, port.to_string().parse::<u16>()?);
+        config.set_protocol(protocol);
+        RedisClient::with_config(&config)
+    }
+
+    /// Create a RedisClient from a ConnectionConfig.
+    /// Once connected it authenticates, selects the database and sets the client name if the configuration requires it.
+    pub fn with_config(config: &ConnectionConfig) -> Result<RedisClient, RedisError> {
+        let mut config = config.clone();
+        config.resolve_address();
+        let mut client = RedisClient {
+            stream: Stream::connect(&config)?,
+            config,
+            decoder: Decoder::new(),
+            pending_replies: 0,
+            connected: true,
+            connection_id: next_connection_id(),
+        };
+        client.init_connection()?;
+        Ok(client)
+    }
+
+    /// Tell whether the connection is opened. It is closed once an I/O error other than a timeout occurred
+    /// and until the next command reconnects.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Get the id of the opened connection, None while it is closed.
+    /// Every connection gets a new id, so a different one tells the state kept by redis for the previous one, like WATCH, is lost.
+    pub fn get_connection_id(&self) -> Option<u64> {
+        if self.connected { Some(self.connection_id) } else { None }
+    }
+
+    pub fn get_config(&self) -> &ConnectionConfig {
+        &self.config
+    }
+
+    /// Turn the client into a PubSub handle, to subscribe to channels and patterns and wait for their messages
+    pub fn into_pubsub(self) -> PubSub {
+        PubSub {
+            client: self,
+            messages: VecDeque::new(),
+            channels: BTreeSet::new(),
+            patterns: BTreeSet::new(),
+            failed: false,
+        }
+    }
+
+    /// Send the commands preparing a new connection as described by the configuration
+    fn init_connection(&mut self) -> Result<(), RedisError> {
+        let cmd = &mut self.config.get_init_commands();
+        let cmd_nb = cmd.get_command_nb();
+        if cmd_nb == 0 {
+            return Ok(());
+        }
+
+        for result in self.run_pipeline_command(cmd.into(), cmd_nb)? {
+            if let RedisResult::Error(err) = result {
+                return Err(RedisError::Response(err));
+            }
+        }
+        Ok(())
+    }
+
+    /// Open a new connection if the previous one was lost or if the address resolver moved the client to another address,
+    /// giving up after the attempts allowed by the ReconnectPolicy
+    fn ensure_connected(&mut self) -> Result<(), RedisError> {
+        if self.config.resolve_address() {
+            self.connected = false;
+        }
+        if self.connected {
+            return Ok(());
+        }
+
+        let policy = self.config.get_reconnect_policy().clone();
+        let mut last_error = RedisError::Io(io::Error::new(io::ErrorKind::NotConnected, "the connection was lost"));
+        for attempt in 0..policy.get_max_attempts() {
+            thread::sleep(policy.get_delay(attempt));
+            match self.reconnect() {
+                Ok(()) => {
+                    self.config.notify(ConnectionEvent::Reconnected(attempt + 1));
+                    return Ok(());
+                },
+                Err(err) => last_error = err,
+            };
+        }
+
+        if policy.get_max_attempts() > 0 {
+            self.config.notify(ConnectionEvent::ReconnectFailed(last_error.clone()));
+        }
+        Err(last_error)
+    }
+
+    /// Replace the stream with a new connection, then authenticate, select the database and set the name again
+    fn reconnect(&mut self) -> Result<(), RedisError> {
+        self.config.resolve_address();
+        self.stream = Stream::connect(&self.config)?;
+        self.decoder = Decoder::new();
+        self.pending_replies = 0;
+        self.connection_id = next_connection_id();
+        self.init_connection()?;
+        self.connected = true;
+        Ok(())
+    }
+
+    /// Mark the connection as lost when the error comes from the socket. A timeout leaves it opened, the late replies are skipped later.
+    fn check_connection<T>(&mut self, result: Result<T, RedisError>) -> Result<T, RedisError> {
+        if let Err(RedisError::Io(ref err)) = result {
+            match err.kind() {
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => {},
+                _ => {
+                    if self.connected {
+                        self.connected = false;
+                        self.config.notify(ConnectionEvent::Disconnected(RedisError::Io(io::Error::new(err.kind(), err.to_string()))));
+                    }
+                },
+            };
+        }
+        result
     }
 
     /// write a command to the stream
-    fn write_command(&mut self, buf_to_send: &[u8]) -> Result<usize, RedisError> {
-        let mut writer = self.buffer.get_mut() as &mut Write;
-        let size = try!(writer.write(buf_to_send));
-        Ok(size)
+    fn write_command(&mut self, buf_to_send: &[u8], cmd_nb: usize) -> Result<(), RedisError> {
+        self.stream.write_all(buf_to_send)?;
+        self.pending_replies += cmd_nb;
+        Ok(())
     }
 
-    /// Execute a command received as an array of bytes
+    /// Read the next response from the stream.
+    /// The bytes already received are kept by the decoder when the read times out, so a response is never cut.
+    fn read_response(&mut self) -> Result<RedisResult, RedisError> {
+        let mut chunk = [0; 4096];
+        loop {
+            if let Some(result) = self.decoder.decode()? {
+                return Ok(result);
+            }
+
+            let size = self.stream.read(&mut chunk)?;
+            if size == 0 {
+                return Err(RedisError::Io(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed by the server")));
+            }
+            self.decoder.feed(&chunk[..size]);
+        }
+    }
+
+    /// Read the response of the oldest command still waiting for one
+    fn read_reply(&mut self) -> Result<RedisResult, RedisError> {
+        let result = self.read_response();
+        match result {
+            Ok(_) | Err(RedisError::Response(_)) => self.pending_replies = self.pending_replies.saturating_sub(1),
+            Err(RedisError::Io(_)) => {},
+            Err(_) => self.pending_replies = 0,
+        };
+        result
+    }
+
+    /// Read and drop the responses of the commands whose read failed before the response arrived,
+    /// leaving only the responses of the last command sent
+    fn skip_stale_replies(&mut self, cmd_nb: usize) -> Result<(), RedisError> {
+        while self.pending_replies > cmd_nb {
+            match self.read_reply() {
+                Ok(_) | Err(RedisError::Response(_)) => {},
+                Err(err) => return Err(err),
+            };
+        }
+        Ok(())
+    }
+
+    /// Execute a command received as an array of bytes, reconnecting first if the connection was lost
     fn exec_command(&mut self, buf_to_send: &[u8]) -> Result<RedisResult, RedisError> {
-        try!(self.write_command(buf_to_send));
-        
-        Reader::read(&mut self.buffer)
+        self.ensure_connected()?;
+        let result = self.run_command(buf_to_send);
+        self.check_connection(result)
     }
 
-    /// Execute a pipeline command received as an array of bytes
+    /// Execute a pipeline command received as an array of bytes, reconnecting first if the connection was lost
     fn exec_pipeline_command(&mut self, buf_to_send: &[u8], cmd_nb: usize) -> Result<Vec<RedisResult>, RedisError> {
-        try!(self.write_command(buf_to_send));
+        self.ensure_connected()?;
+        let result = self.run_pipeline_command(buf_to_send, cmd_nb);
+        self.check_connection(result)
+    }
 
-        Reader::read_pipeline(&mut self.buffer, cmd_nb)
+    /// Write a command without reading its response, the subscribed connections read their frames with read_message
+    fn send_command(&mut self, buf_to_send: &[u8]) -> Result<(), RedisError> {
+        let result = self.stream.write_all(buf_to_send).map_err(RedisError::from);
+        self.check_connection(result)
+    }
+
+    /// Read the next frame of a subscribed connection, a reply or a message pushed by redis
+    fn read_message(&mut self) -> Result<RedisResult, RedisError> {
+        let result = self.read_response();
+        self.check_connection(result)
+    }
+
+    /// Send a command and read its response on the current connection
+    fn run_command(&mut self, buf_to_send: &[u8]) -> Result<RedisResult, RedisError> {
+        self.write_command(buf_to_send, 1)?;
+        self.skip_stale_replies(1)?;
+
+        self.read_reply()
+    }
+
+    /// Send a pipeline command and read its responses on the current connection
+    fn run_pipeline_command(&mut self, buf_to_send: &[u8], cmd_nb: usize) -> Result<Vec<RedisResult>, RedisError> {
+        self.write_command(buf_to_send, cmd_nb)?;
+        self.skip_stale_replies(cmd_nb)?;
+
+        let mut results: Vec<RedisResult> = Vec::with_capacity(cmd_nb);
+        for _ in 0..cmd_nb {
+            match self.read_reply() {
+                Ok(value) => results.push(value),
+                Err(RedisError::Response(err)) => results.push(RedisResult::Error(err)),
+                Err(err) => return Err(err),
+            };
+        }
+        Ok(results)
     }
 
     /// Execute a RedisCommand
@@ -111,17 +664,51 @@ impl RedisClient {
         self.exec_pipeline_command(redis_command.into(), cmd_nb)
     }
 
+    /// Iterate over the keys of the database with SCAN, each key being converted to T
+    pub fn scan_iter<T: FromRedisValue>(&mut self, options: &ScanOptions) -> ScanIter<'_, T> {
+        ScanIter::keys(self, options)
+    }
+
+    /// Iterate over the members of the set stored at key with SSCAN, each member being converted to T
+    pub fn sscan_iter<T: FromRedisValue, K: ToRedisArgs>(&mut self, key: K, options: &ScanOptions) -> ScanIter<'_, T> {
+        ScanIter::set(self, key.to_redis_args(), options)
+    }
+
+    /// Iterate over the fields of the hash stored at key with HSCAN, each `(field, value)` pair being converted to T
+    pub fn hscan_iter<T: FromRedisValue, K: ToRedisArgs>(&mut self, key: K, options: &ScanOptions) -> ScanIter<'_, T> {
+        ScanIter::hash(self, key.to_redis_args(), options)
+    }
+
+    /// Iterate over the members of the sorted set stored at key with ZSCAN, each `(member, score)` pair being converted to T
+    pub fn zscan_iter<T: FromRedisValue, K: ToRedisArgs>(&mut self, key: K, options: &ScanOptions) -> ScanIter<'_, T> {
+        ScanIter::sorted_set(self, key.to_redis_args(), options)
+    }
+
+}
+
+impl CommandExecutor for RedisClient {
+    fn exec_redis_command(&mut self, redis_command: &mut RedisCommand) -> Result<RedisResult, RedisError> {
+        RedisClient::exec_redis_command(self, redis_command)
+    }
+
+    fn exec_redis_pipeline_command(&mut self, redis_command: &mut RedisCommand) -> Result<Vec<RedisResult>, RedisError> {
+        RedisClient::exec_redis_pipeline_command(self, redis_command)
+    }
+
+    fn get_key_connection_id(&self, _key: &[u8]) -> Option<u64> {
+        self.get_connection_id()
+    }
 }
 
 impl fmt::Debug for RedisClient {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Redis Client - HOST = {} : PORT + {}", self.host, self.port)
+        write!(f, "Redis Client - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
     }
 }
 
 impl fmt::Display for RedisClient {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Redis Client - HOST = {} : PORT + {}", self.host, self.port)
+        write!(f, "Redis Client - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
     }
 }
 
@@ -138,30 +725,40 @@ impl fmt::Display for RedisClient {
 /// # Ok(())}
 /// ```
 impl RedisClientAsync {
-    pub fn new(host: &'static str, port: &'static str) -> Result<RedisClientAsync, RedisError> {
-        let (sender_tx, sender_rx) = channel::<(SenderType, u32, Vec<u8>)>();
+    pub fn new<H: ToString, P: ToString>(host: H, port: P) -> Result<RedisClientAsync, RedisError> {
+        RedisClientAsync::new_with_protocol(host, port, ProtocolVersion::Resp2)
+    }
+
+    /// Create a RedisClientAsync speaking the given version of the redis protocol.
+    /// For RESP3 the connection is switched with the HELLO command once it is created.
+    pub fn new_with_protocol<H: ToString, P: ToString>(host: H, port: P, protocol: ProtocolVersion) -> Result<RedisClientAsync, RedisError> {
+        let mut config = ConnectionConfig::new(host, port.to_string().parse::<u16>()?);
+        config.set_protocol(protocol);
+        RedisClientAsync::with_config(&config)
+    }
+
+    /// Create a RedisClientAsync from a ConnectionConfig.
+    /// Once connected it authenticates, selects the database and sets the client name if the configuration requires it.
+    pub fn with_config(config: &ConnectionConfig) -> Result<RedisClientAsync, RedisError> {
+        let client_config = config.clone();
+        let (sender_tx, sender_rx) = channel::<(SenderType, u64, Vec<u8>)>();
         let (init_tx, init_rx) = channel::<Option<RedisError>>();
-        let (receiver_tx, receiver_rx) = channel::<(u32, Result<RedisResult, RedisError>)>();
-        let (pipe_receiver_tx, pipe_receiver_rx) = channel::<(u32, Result<Vec<RedisResult>, RedisError>)>();
+        let (receiver_tx, receiver_rx) = channel::<(u64, Result<RedisResult, RedisError>)>();
+        let (pipe_receiver_tx, pipe_receiver_rx) = channel::<(u64, Result<Vec<RedisResult>, RedisError>)>();
 
         thread::spawn(move || {
-            let _client = RedisClient::new(host, port)
+            let _client = RedisClient::with_config(&client_config)
             .map(|mut redis_client| {
                 init_tx.send(None)
                 .map(|_| {
-                    loop {
-                        match sender_rx.recv() {
-                            Ok(value) => {
-                                match value.0 {
-                                    SenderType::Simple => {
-                                        let _res = receiver_tx.send((value.1, redis_client.exec_command(&value.2[..])));
-                                    },
-                                    SenderType::Pipe(cmd_nb) => {
-                                        let _res = pipe_receiver_tx.send((value.1, redis_client.exec_pipeline_command(&value.2[..], cmd_nb)));
-                                    },
-                                };
+                    while let Ok(value) = sender_rx.recv() {
+                        match value.0 {
+                            SenderType::Simple => {
+                                let _res = receiver_tx.send((value.1, redis_client.exec_command(&value.2[..])));
+                            },
+                            SenderType::Pipe(cmd_nb) => {
+                                let _res = pipe_receiver_tx.send((value.1, redis_client.exec_pipeline_command(&value.2[..], cmd_nb)));
                             },
-                            Err(_) => break,
                         };
                     }
                 })
@@ -174,13 +771,13 @@ impl RedisClientAsync {
         match init_rx.recv() {
             Ok(None) => {
                 Ok(RedisClientAsync {
-                    port: port,
-                    host: host,
+                    config: config.clone(),
                     sender: sender_tx,
                     receiver: receiver_rx,
                     callbacks: HashMap::new(),
                     pipe_receiver: pipe_receiver_rx,
-                    pipe_callbacks: HashMap::new()
+                    pipe_callbacks: HashMap::new(),
+                    last_request_id: 0
                 })
             },
             Ok(Some(err)) =>  Err(err),
@@ -191,11 +788,10 @@ impl RedisClientAsync {
     /// Execute a redis pipeline command. The callback will be called once the command execution is over and the pump method is called.
     /// The return value indicates if the command was successfully launched.
     pub fn exec_redis_pipeline_command_async<F>(&mut self, redis_command: &mut RedisCommand, callback: F) 
-        -> Result<(), RedisError> where F: Fn(Result<Vec<RedisResult>, RedisError>), F: Send + 'static
+        -> Result<(), RedisError> where F: FnOnce(Result<Vec<RedisResult>, RedisError>), F: Send + 'static
     {
-        let mut rng = rand::thread_rng();
-        let key = rng.gen::<u32>();
-        try!(self.sender.send((SenderType::Pipe(redis_command.get_command_nb()), key, redis_command.into())));
+        let key = self.next_request_id();
+        self.sender.send((SenderType::Pipe(redis_command.get_command_nb()), key, redis_command.into()))?;
         self.pipe_callbacks.insert(key, Box::new(callback));
         Ok(())
     }
@@ -203,38 +799,36 @@ impl RedisClientAsync {
     /// Execute a redis command. The callback will be called once the command execution is over and the pump method is called.
     /// The return value indicates if the command was successfully launched.
     pub fn exec_redis_command_async<F>(&mut self, redis_command: &mut RedisCommand, callback: F) 
-        -> Result<(), RedisError> where F: Fn(Result<RedisResult, RedisError>), F: Send + 'static
+        -> Result<(), RedisError> where F: FnOnce(Result<RedisResult, RedisError>), F: Send + 'static
     {
-        let mut rng = rand::thread_rng();
-        let key = rng.gen::<u32>();
-        try!(self.sender.send((SenderType::Simple, key, redis_command.into())));
+        let key = self.next_request_id();
+        self.sender.send((SenderType::Simple, key, redis_command.into()))?;
         self.callbacks.insert(key, Box::new(callback));
         Ok(())
     }
 
+    /// Get the id of a new request. The ids are increasing so two pending requests never share one.
+    fn next_request_id(&mut self) -> u64 {
+        self.last_request_id += 1;
+        self.last_request_id
+    }
+
     /// Pump the result and execute the callbacks with them. If no result are ready this function will return.
+    /// Each callback is called exactly once, either with the response or with the error the command ended with.
     pub fn pump(&mut self) -> Result<(), RedisError> {
         loop {
             match self.receiver.try_recv() {
                 Ok(result) => {
-                    self.callbacks.remove(&result.0) 
-                        .map(|callback| {
-                            if result.1.is_ok() {
-
-                                callback(result.1.clone());
-                            }
-                        });
+                    if let Some(callback) = self.callbacks.remove(&result.0) {
+                        callback(result.1);
+                    }
                 },
                 Err(TryRecvError::Empty) => {
                     match self.pipe_receiver.try_recv() {
                         Ok(result) => {
-                            self.pipe_callbacks.remove(&result.0) 
-                                .map(|callback| {
-                                    if result.1.is_ok() {
-
-                                        callback(result.1.clone());
-                                    }
-                                });
+                            if let Some(callback) = self.pipe_callbacks.remove(&result.0) {
+                                callback(result.1);
+                            }
                         },
                         Err(TryRecvError::Empty) => return Ok(()),
                         Err(err) => return Err(RedisError::MpscTryRecv(err))
@@ -248,13 +842,13 @@ impl RedisClientAsync {
 
 impl fmt::Debug for RedisClientAsync {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Redis Client Async - HOST = {} : PORT + {}", self.host, self.port)
+        write!(f, "Redis Client Async - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
     }
 }
 
 impl fmt::Display for RedisClientAsync {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Redis Client Async - HOST = {} : PORT + {}", self.host, self.port)
+        write!(f, "Redis Client Async - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
     }
 }
 
@@ -272,45 +866,75 @@ impl fmt::Display for RedisClientAsync {
 /// # Ok(())}
 /// ```
 impl PubSubClientAsync {
-    pub fn new(host: &'static str, port: &'static str) -> Result<PubSubClientAsync, RedisError> {
+    pub fn new<H: ToString, P: ToString>(host: H, port: P) -> Result<PubSubClientAsync, RedisError> {
+        PubSubClientAsync::new_with_protocol(host, port, ProtocolVersion::Resp2)
+    }
+
+    /// Create a PubSubClientAsync speaking the given version of the redis protocol.
+    /// For RESP3 the connection is switched with the HELLO command once it is created.
+    pub fn new_with_protocol<H: ToString, P: ToString>(host: H, port: P, protocol: ProtocolVersion) -> Result<PubSubClientAsync, RedisError> {
+        let mut config = ConnectionConfig::new(host, port.to_string().parse::<u16>()?);
+        config.set_protocol(protocol);
+        PubSubClientAsync::with_config(&config)
+    }
+
+    /// Create a PubSubClientAsync from a ConnectionConfig.
+    /// Once connected it authenticates, selects the database and sets the client name if the configuration requires it.
+    ///
+    /// A thread writes each command as soon as it is queued while another one blocks on the connection, reading the replies
+    /// and the messages. A TLS connection can't be shared between them, so it is refused.
+    pub fn with_config(config: &ConnectionConfig) -> Result<PubSubClientAsync, RedisError> {
+        let client_config = config.clone();
         let (init_tx, init_rx) = channel::<Option<RedisError>>();
-        let (sender_tx, sender_rx) = channel::<(PubSubType, u32, Vec<u8>)>();
-        let (receiver_tx, receiver_rx) = channel::<(u32, Result<RedisResult, RedisError>)>();
+        let (sender_tx, sender_rx) = channel::<(PubSubType, u64, Vec<u8>)>();
+        let (receiver_tx, receiver_rx) = channel::<PubSubReply>();
 
         thread::spawn(move || {
-            let _client = RedisClient::new(host, port)
-            .map(|mut redis_client| {
-                init_tx.send(None)
-                .map(|_| {
-                    loop {
-                        match sender_rx.try_recv() {
-                            Ok(value) => {
-                                let _res = receiver_tx.send((value.1, redis_client.exec_command(&value.2[..])));
-                            },
-                            Err(_) => {
-                                if let Ok(res) = Reader::read(&mut redis_client.buffer) {
-                                    let _res = receiver_tx.send((0, Ok(res)));
-                                }
-                            }
-                        };
+            let connection = RedisClient::with_config(&client_config)
+                .and_then(|redis_client| redis_client.stream.try_clone().map(|stream| (redis_client, stream)));
+            match connection {
+                Ok((redis_client, stream)) => {
+                    let writer = Arc::new(Mutex::new(Ok(stream)));
+                    let pending = Arc::new(Mutex::new(VecDeque::new()));
+                    let sender = PubSubSender {
+                        commands: sender_rx,
+                        writer: writer.clone(),
+                        pending: pending.clone(),
+                        replies: receiver_tx.clone(),
+                    };
+                    if init_tx.send(None).is_ok() {
+                        thread::spawn(move || sender.run());
+                        PubSubReader {
+                            client: redis_client,
+                            writer,
+                            pending,
+                            replies: receiver_tx,
+                            channels: HashSet::new(),
+                            patterns: HashSet::new(),
+                            shard_channels: HashSet::new(),
+                        }.run();
                     }
-                })
-            })
-            .map_err(|error| {
-                let _res = init_tx.send(Some(error));
-            });
+                },
+                Err(err) => {
+                    let _res = init_tx.send(Some(err));
+                },
+            };
         });
 
         match init_rx.recv() {
             Ok(None) => {
                 Ok(PubSubClientAsync {
-                    port: port,
-                    host: host,
+                    config: config.clone(),
                     cmd_sender: sender_tx,
                     receiver: receiver_rx,
                     cmd_callbacks: HashMap::new(),
                     channel_callbacks: HashMap::new(),
-                    pattern_callbacks: HashMap::new()
+                    pattern_callbacks: HashMap::new(),
+                    shard_callbacks: HashMap::new(),
+                    channels: BTreeSet::new(),
+                    patterns: BTreeSet::new(),
+                    shard_channels: BTreeSet::new(),
+                    last_request_id: 0
                 })
             },
             Ok(Some(err)) =>  Err(err),
@@ -321,51 +945,92 @@ impl PubSubClientAsync {
     /// Execute a redis command. The cmd_callback will be called once the command execution is over and the pump method is called.
     /// The return value indicates if the command was successfully launched.
     pub fn exec_redis_command_async<F>(&mut self, redis_command: &mut RedisCommand, cmd_callback: F, pubsub_arg: PubSubArg) 
-        -> Result<(), RedisError> where F: Fn(Result<RedisResult, RedisError>), F: Send + 'static
+        -> Result<(), RedisError> where F: FnOnce(Result<RedisResult, RedisError>), F: Send + 'static
     {
-        let mut rng = rand::thread_rng();
-        let key: u32 = rng.gen_range(1, u32::MAX);
+        let key = self.next_request_id();
 
         let pubsub_type = pubsub_arg.pubsub_type.clone();
-        try!(self.cmd_sender.send((pubsub_type, key, redis_command.into())));
+        self.cmd_sender.send((pubsub_type, key, redis_command.into()))?;
         self.cmd_callbacks.insert(key, Box::new(cmd_callback));
 
         if let Some(callback) = pubsub_arg.callback {
-            if let PubSubType::Channel(value) = pubsub_arg.pubsub_type {
-                self.channel_callbacks.insert(value, callback);
+            let callback: SubscriptionCallback = Rc::new(RefCell::new(callback));
+            let (callbacks, names) = match pubsub_arg.pubsub_type {
+                PubSubType::Channel(name) => (&mut self.channel_callbacks, vec![name]),
+                PubSubType::Pattern(name) => (&mut self.pattern_callbacks, vec![name]),
+                PubSubType::ShardChannel(name) => (&mut self.shard_callbacks, vec![name]),
+                PubSubType::Channels(names) => (&mut self.channel_callbacks, names),
+                PubSubType::Patterns(names) => (&mut self.pattern_callbacks, names),
+                PubSubType::ShardChannels(names) => (&mut self.shard_callbacks, names),
+                _ => return Ok(()),
+            };
+            for name in names {
+                callbacks.insert(name, callback.clone());
             }
-            else if let PubSubType::Pattern(value) = pubsub_arg.pubsub_type {
-                self.pattern_callbacks.insert(value, callback);
-            }          
         }
 
         Ok(())
     }
 
+    /// Get the channels, the patterns and the sharded channels subscribed, as confirmed by redis when pump was last called
+    pub fn get_subscriptions(&self) -> Vec<PubSubType> {
+        let channels = self.channels.iter().map(|name| PubSubType::Channel(name.clone()));
+        let patterns = self.patterns.iter().map(|name| PubSubType::Pattern(name.clone()));
+        let shard_channels = self.shard_channels.iter().map(|name| PubSubType::ShardChannel(name.clone()));
+        channels.chain(patterns).chain(shard_channels).collect()
+    }
+
+    /// Keep track of a subscription confirmed by redis. The callback of a channel or a pattern is removed once its unsubscription is confirmed.
+    fn confirm_subscription(&mut self, kind: &str, name: String) {
+        match kind {
+            "subscribe" => { self.channels.insert(name); },
+            "psubscribe" => { self.patterns.insert(name); },
+            "ssubscribe" => { self.shard_channels.insert(name); },
+            "unsubscribe" => {
+                self.channel_callbacks.remove(&name);
+                self.channels.remove(&name);
+            },
+            "punsubscribe" => {
+                self.pattern_callbacks.remove(&name);
+                self.patterns.remove(&name);
+            },
+            "sunsubscribe" => {
+                self.shard_callbacks.remove(&name);
+                self.shard_channels.remove(&name);
+            },
+            _ => {},
+        };
+    }
+
+    /// Get the id of a new request. The ids are increasing so two pending requests never share one.
+    fn next_request_id(&mut self) -> u64 {
+        self.last_request_id += 1;
+        self.last_request_id
+    }
+
     /// Pump the result and the received value and execute the callbacks with them. If no result or received value are ready this function will return.
+    /// Each command callback is called exactly once, either with the response or with the error the command ended with.
     pub fn pump(&mut self) -> Result<(), RedisError> {
         loop {
             match self.receiver.try_recv() {
-                Ok(result) => {
-                    if result.0 == 0 {
-                        if let Ok(res) = result.1 {
-                            let array = res.convert::<Vec<String>>();
-                            if !array.is_empty() {
-                                if array[0] == "message" && array.len() == 3 && self.channel_callbacks.contains_key(&array[1]) {
-                                    self.channel_callbacks[&array[1]](RedisResult::String(array[2].clone()));
-                                } else if array[0] == "pmessage" && array.len() == 4 && self.pattern_callbacks.contains_key(&array[1]) {
-                                    self.pattern_callbacks[&array[1]](RedisResult::String(array[3].clone()));
-                                }
-                            }
-                        } 
-                    } else {
-                        self.cmd_callbacks.remove(&result.0) 
-                            .map(|callback| {
-                                if result.1.is_ok() {
-
-                                    callback(result.1.clone());
-                                }
-                            });
+                Ok(PubSubReply::Message(message)) => {
+                    let callback = match message.pattern {
+                        Some(ref pattern) => self.pattern_callbacks.get(pattern),
+                        None => self.channel_callbacks.get(&message.channel),
+                    };
+                    if let Some(callback) = callback {
+                        (callback.borrow_mut())(message);
+                    }
+                },
+                Ok(PubSubReply::ShardMessage(message)) => {
+                    if let Some(callback) = self.shard_callbacks.get(&message.channel) {
+                        (callback.borrow_mut())(message);
+                    }
+                },
+                Ok(PubSubReply::Confirmation(kind, name)) => self.confirm_subscription(&kind, name),
+                Ok(PubSubReply::Command(key, result)) => {
+                    if let Some(callback) = self.cmd_callbacks.remove(&key) {
+                        callback(result);
                     }
                 },
                 Err(TryRecvError::Empty) => return Ok(()),
@@ -377,12 +1042,226 @@ impl PubSubClientAsync {
 
 impl fmt::Debug for PubSubClientAsync {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Redis Client Async - HOST = {} : PORT + {}", self.host, self.port)
+        write!(f, "Redis Client Async - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
     }
 }
 
 impl fmt::Display for PubSubClientAsync {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Redis Client Async - HOST = {} : PORT + {}", self.host, self.port)
+        write!(f, "Redis Client Async - HOST = {} : PORT + {}", self.config.get_host(), self.config.get_port())
+    }
+}
+
+/// A PubSub is a RedisClient in subscriber mode, receiving the messages without callbacks nor pump.
+///
+/// It is created with RedisClient::into_pubsub. The subscription methods wait for the confirmations of redis,
+/// then the messages are read one at a time with get_message, by iterating on the PubSub or from a Receiver:
+///
+/// ```no_run
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// let mut pubsub = try!(redis_client::RedisClient::new("127.0.0.1", "6379")).into_pubsub();
+/// try!(pubsub.subscribe("news"));
+///
+/// for message in pubsub.by_ref().take(10) {
+///     let message = try!(message);
+///     println!("{} on {}", try!(message.get_payload_str()), message.get_channel());
+/// }
+///
+/// let receiver = pubsub.into_receiver();
+/// let message = receiver.recv();
+/// # Ok(())}
+/// ```
+///
+/// When the connection drops, the next read reconnects and subscribes again to the channels and the patterns.
+impl PubSub {
+    /// Subscribe to one or more channels
+    pub fn subscribe<C: ToRedisArgs>(&mut self, channels: C) -> Result<(), RedisError> {
+        self.change_subscriptions("SUBSCRIBE", channels)
+    }
+
+    /// Subscribe to one or more patterns
+    pub fn psubscribe<P: ToRedisArgs>(&mut self, patterns: P) -> Result<(), RedisError> {
+        self.change_subscriptions("PSUBSCRIBE", patterns)
+    }
+
+    /// Unsubscribe from one or more channels
+    pub fn unsubscribe<C: ToRedisArgs>(&mut self, channels: C) -> Result<(), RedisError> {
+        self.change_subscriptions("UNSUBSCRIBE", channels)
+    }
+
+    /// Unsubscribe from all the channels
+    pub fn unsubscribe_all(&mut self) -> Result<(), RedisError> {
+        self.change_subscriptions("UNSUBSCRIBE", Vec::<String>::new())
+    }
+
+    /// Unsubscribe from one or more patterns
+    pub fn punsubscribe<P: ToRedisArgs>(&mut self, patterns: P) -> Result<(), RedisError> {
+        self.change_subscriptions("PUNSUBSCRIBE", patterns)
+    }
+
+    /// Unsubscribe from all the patterns
+    pub fn punsubscribe_all(&mut self) -> Result<(), RedisError> {
+        self.change_subscriptions("PUNSUBSCRIBE", Vec::<String>::new())
+    }
+
+    /// Get the channels and the patterns subscribed
+    pub fn get_subscriptions(&self) -> Vec<PubSubType> {
+        let channels = self.channels.iter().map(|name| PubSubType::Channel(name.clone()));
+        let patterns = self.patterns.iter().map(|name| PubSubType::Pattern(name.clone()));
+        channels.chain(patterns).collect()
+    }
+
+    /// Wait for the next message, at most for the timeout or forever with None. Returns None if no message arrived in time.
+    pub fn get_message(&mut self, timeout: Option<Duration>) -> Result<Option<PubSubMessage>, RedisError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            if let Some(message) = self.messages.pop_front() {
+                return Ok(Some(message));
+            }
+            if !self.client.is_connected() {
+                self.resubscribe()?;
+                continue;
+            }
+
+            let read_timeout = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Ok(None);
+                    }
+                    Some(deadline - now)
+                },
+                None => None,
+            };
+            self.client.stream.set_read_timeout(read_timeout)?;
+            match self.read_frame() {
+                Ok(_) => {},
+                Err(RedisError::Io(ref err)) if err.kind() == io::ErrorKind::WouldBlock || err.kind() == io::ErrorKind::TimedOut => {},
+                Err(_) if !self.client.is_connected() => {},
+                Err(err) => return Err(err),
+            };
+        }
+    }
+
+    /// Move the PubSub to a thread sending each message to the returned Receiver.
+    /// The thread stops on the first error, e.g. when the connection can't be opened again, or when the next message arrives
+    /// after the Receiver was dropped. The Receiver is disconnected once the thread stopped.
+    pub fn into_receiver(self) -> Receiver<PubSubMessage> {
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            for message in self {
+                let message = match message {
+                    Ok(message) => message,
+                    Err(_) => return,
+                };
+                if sender.send(message).is_err() {
+                    return;
+                }
+            }
+        });
+        receiver
+    }
+
+    /// Send a subscription command and wait for its confirmations, keeping the messages received meanwhile
+    fn change_subscriptions<N: ToRedisArgs>(&mut self, command: &str, names: N) -> Result<(), RedisError> {
+        let names = names.to_redis_args();
+        if !self.client.is_connected() {
+            self.resubscribe()?;
+        }
+        self.send_subscriptions(command, &names)
+    }
+
+    fn send_subscriptions(&mut self, command: &str, names: &[Vec<u8>]) -> Result<(), RedisError> {
+        let cmd = &mut RedisCommand::new();
+        cmd.add_cmd(command).add_arg(names).end();
+        self.client.send_command(cmd.into())?;
+        self.client.stream.set_read_timeout(self.client.config.get_read_timeout())?;
+
+        let kind = command.to_lowercase();
+        let mut confirmation_nb = 0;
+        loop {
+            if let Some((confirmed_kind, count)) = self.read_frame()? {
+                if confirmed_kind != kind {
+                    continue;
+                }
+                confirmation_nb += 1;
+                // an unsubscription from everything is over once only the subscriptions of the other kind remain
+                let complete = match (kind.as_str(), names.is_empty()) {
+                    (_, false) => confirmation_nb >= names.len(),
+                    ("unsubscribe", true) => count as usize <= self.patterns.len(),
+                    (_, true) => count as usize <= self.channels.len(),
+                };
+                if complete {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Reconnect and subscribe again to the channels and the patterns
+    fn resubscribe(&mut self) -> Result<(), RedisError> {
+        self.client.ensure_connected()?;
+        let channels: Vec<Vec<u8>> = self.channels.iter().map(|name| name.clone().into_bytes()).collect();
+        let patterns: Vec<Vec<u8>> = self.patterns.iter().map(|name| name.clone().into_bytes()).collect();
+        if !channels.is_empty() {
+            self.send_subscriptions("SUBSCRIBE", &channels)?;
+        }
+        if !patterns.is_empty() {
+            self.send_subscriptions("PSUBSCRIBE", &patterns)?;
+        }
+        Ok(())
+    }
+
+    /// Read a frame, keeping the messages and the subscriptions. Returns the kind and the count of a confirmation.
+    fn read_frame(&mut self) -> Result<Option<(String, i64)>, RedisError> {
+        let frame = self.client.read_message()?;
+        match PubSubFrame::classify(frame, self.client.config.get_protocol()) {
+            PubSubFrame::Message(message) | PubSubFrame::ShardMessage(message) => self.messages.push_back(message),
+            PubSubFrame::Confirmation(kind, name, count, _) => {
+                if let Some(name) = name {
+                    match kind.as_str() {
+                        "subscribe" => { self.channels.insert(name); },
+                        "psubscribe" => { self.patterns.insert(name); },
+                        "unsubscribe" => { self.channels.remove(&name); },
+                        "punsubscribe" => { self.patterns.remove(&name); },
+                        _ => {},
+                    };
+                }
+                return Ok(Some((kind, count)));
+            },
+            PubSubFrame::Reply(_) | PubSubFrame::Ignored => {},
+        };
+        Ok(None)
+    }
+}
+
+/// Iterating on a PubSub waits for each message. An error, e.g. when the connection can't be opened again, is returned
+/// as an item and ends the iteration.
+impl Iterator for PubSub {
+    type Item = Result<PubSubMessage, RedisError>;
+
+    fn next(&mut self) -> Option<Result<PubSubMessage, RedisError>> {
+        if self.failed {
+            return None;
+        }
+        match self.get_message(None) {
+            Ok(message) => message.map(Ok),
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            },
+        }
+    }
+}
+
+impl fmt::Debug for PubSub {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis PubSub - HOST = {} : PORT + {}", self.client.config.get_host(), self.client.config.get_port())
+    }
+}
+
+impl fmt::Display for PubSub {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis PubSub - HOST = {} : PORT + {}", self.client.config.get_host(), self.client.config.get_port())
     }
 }
@@ -1,7 +1,14 @@
-use std::collections::HashMap;
+use errors::{ConversionError, RedisError, ServerError};
+use std::any;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
 use std::fmt;
+use std::hash::{BuildHasher, Hash};
 use std::str;
 
+/// A response from redis.
+///
+/// The first variants are the RESP2 types. The other ones are only received once the connection switched to RESP3 with HELLO.
 #[derive(Debug, Clone)]
 pub enum RedisResult {
     Array(Vec<RedisResult>),
@@ -9,13 +16,307 @@ pub enum RedisResult {
     String(String),
     Int(i64),
     Nil,
+    /// A RESP3 map as a list of key/value pairs in the order they were received
+    Map(Vec<(RedisResult, RedisResult)>),
+    Set(Vec<RedisResult>),
+    Double(f64),
+    Boolean(bool),
+    /// A RESP3 big number kept as its decimal representation
+    BigNumber(String),
+    /// A RESP3 verbatim string with its format (txt, mkd...) and its text
+    VerbatimString(String, String),
+    /// A RESP3 response with its attributes
+    Attribute(Box<RedisResult>, Vec<(RedisResult, RedisResult)>),
+    /// A RESP3 out of band data pushed by the server, like a pubsub message
+    Push(Vec<RedisResult>),
+    /// An error replied to one command of a pipeline or a transaction, or nested in an aggregate response
+    Error(ServerError),
 }
 
 impl RedisResult {
-    /// Method to convert a RedisResult into another type
+    /// Method to convert a RedisResult into another type.
+    /// The conversion never fails and falls back on a default value, try_convert reports the mismatches instead.
     pub fn convert<T: From<RedisResult>>(self) -> T {
         self.into()
     }
+
+    /// Method to convert a RedisResult into another type, failing with a RedisError::Conversion if the response
+    /// does not fit the type.
+    ///
+    /// ```
+    /// # use redis_client::results::RedisResult;
+    /// let count: i64 = RedisResult::Int(42).try_convert().unwrap();
+    /// assert_eq!(count, 42);
+    /// assert!(RedisResult::Nil.try_convert::<i64>().is_err());
+    /// ```
+    pub fn try_convert<T: FromRedisValue>(self) -> Result<T, RedisError> {
+        T::from_redis_value(self)
+    }
+}
+
+/// The trait FromRedisValue converts a response into a type, returning an error when the response does not match it.
+///
+/// It is implemented for the integers (except u8 so that `Vec<u8>` means binary data), the floats, bool, String, `Vec<u8>`,
+/// `Option<T>` (None for a nil response), `Vec<T>`, the tuples, `HashMap`, `HashSet`, `BTreeMap`, `BTreeSet`,
+/// () (accepting any response but an error) and RedisResult itself.
+///
+/// A RedisResult::Error, like the reply of a failed command in a pipeline, is converted into a RedisError::Response.
+///
+/// The RESP3 attributes are ignored, only the response they are attached to is converted.
+pub trait FromRedisValue: Sized {
+    fn from_redis_value(value: RedisResult) -> Result<Self, RedisError>;
+}
+
+/// Build the error returned when a value cannot be converted into T.
+/// An error replied by redis is returned as it is instead of a conversion error.
+fn conversion_error<T>(value: RedisResult) -> RedisError {
+    match value {
+        RedisResult::Error(err) => RedisError::Response(err),
+        value => RedisError::Conversion(ConversionError {
+            target: any::type_name::<T>(),
+            value,
+        }),
+    }
+}
+
+/// Get the text of a response holding a single string
+fn into_text<T>(value: RedisResult) -> Result<String, RedisError> {
+    match value {
+        RedisResult::Bytes(bytes) => String::from_utf8(bytes).map_err(|err| conversion_error::<T>(RedisResult::Bytes(err.into_bytes()))),
+        RedisResult::String(value) | RedisResult::BigNumber(value) | RedisResult::VerbatimString(_, value) => Ok(value),
+        RedisResult::Attribute(value, _attributes) => into_text::<T>(*value),
+        value => Err(conversion_error::<T>(value)),
+    }
+}
+
+/// Get the elements of an aggregate response, the maps being flattened into key, value, key, value...
+fn into_elements<T>(value: RedisResult) -> Result<Vec<RedisResult>, RedisError> {
+    match value {
+        RedisResult::Array(values) | RedisResult::Set(values) | RedisResult::Push(values) => Ok(values),
+        RedisResult::Map(pairs) => Ok(pairs.into_iter().flat_map(|(key, value)| vec![key, value]).collect()),
+        RedisResult::Nil => Ok(vec![]),
+        RedisResult::Attribute(value, _attributes) => into_elements::<T>(*value),
+        value => Err(conversion_error::<T>(value)),
+    }
+}
+
+/// Get the key/value pairs of a map response or of an array alternating keys and values
+fn into_pairs<T>(value: RedisResult) -> Result<Vec<(RedisResult, RedisResult)>, RedisError> {
+    match value {
+        RedisResult::Map(pairs) => Ok(pairs),
+        RedisResult::Array(values) => {
+            if values.len() % 2 != 0 {
+                return Err(conversion_error::<T>(RedisResult::Array(values)));
+            }
+            let mut pairs = Vec::with_capacity(values.len() / 2);
+            let mut values = values.into_iter();
+            while let (Some(key), Some(value)) = (values.next(), values.next()) {
+                pairs.push((key, value));
+            }
+            Ok(pairs)
+        },
+        RedisResult::Nil => Ok(vec![]),
+        RedisResult::Attribute(value, _attributes) => into_pairs::<T>(*value),
+        value => Err(conversion_error::<T>(value)),
+    }
+}
+
+impl FromRedisValue for RedisResult {
+    fn from_redis_value(value: RedisResult) -> Result<RedisResult, RedisError> {
+        Ok(value)
+    }
+}
+
+impl FromRedisValue for () {
+    /// Any response except an error
+    fn from_redis_value(value: RedisResult) -> Result<(), RedisError> {
+        match value {
+            RedisResult::Error(err) => Err(RedisError::Response(err)),
+            _ => Ok(()),
+        }
+    }
+}
+
+macro_rules! from_redis_value_for_integers {
+    ($($int:ty),*) => {
+        $(
+            impl FromRedisValue for $int {
+                fn from_redis_value(value: RedisResult) -> Result<$int, RedisError> {
+                    match value {
+                        RedisResult::Int(int) => <$int>::try_from(int).map_err(|_| conversion_error::<$int>(RedisResult::Int(int))),
+                        RedisResult::Boolean(boolean) => Ok(boolean as $int),
+                        RedisResult::Attribute(value, _attributes) => <$int>::from_redis_value(*value),
+                        value @ RedisResult::Bytes(_) | value @ RedisResult::String(_) | value @ RedisResult::BigNumber(_) => {
+                            let text = into_text::<$int>(value.clone())?;
+                            text.parse::<$int>().map_err(|_| conversion_error::<$int>(value))
+                        },
+                        value => Err(conversion_error::<$int>(value)),
+                    }
+                }
+            }
+        )*
+    }
+}
+
+from_redis_value_for_integers!(i8, i16, i32, i64, i128, isize, u16, u32, u64, u128, usize);
+
+macro_rules! from_redis_value_for_floats {
+    ($($float:ty),*) => {
+        $(
+            impl FromRedisValue for $float {
+                fn from_redis_value(value: RedisResult) -> Result<$float, RedisError> {
+                    match value {
+                        RedisResult::Double(double) => Ok(double as $float),
+                        RedisResult::Int(int) => Ok(int as $float),
+                        RedisResult::Attribute(value, _attributes) => <$float>::from_redis_value(*value),
+                        value @ RedisResult::Bytes(_) | value @ RedisResult::String(_) | value @ RedisResult::BigNumber(_) => {
+                            let text = into_text::<$float>(value.clone())?;
+                            match &text[..] {
+                                "inf" | "+inf" => Ok(<$float>::INFINITY),
+                                "-inf" => Ok(<$float>::NEG_INFINITY),
+                                text => text.parse::<$float>().map_err(|_| conversion_error::<$float>(value)),
+                            }
+                        },
+                        value => Err(conversion_error::<$float>(value)),
+                    }
+                }
+            }
+        )*
+    }
+}
+
+from_redis_value_for_floats!(f32, f64);
+
+impl FromRedisValue for bool {
+    /// A nil response is false, "OK" is true and the integers are true when they are not 0.
+    fn from_redis_value(value: RedisResult) -> Result<bool, RedisError> {
+        match value {
+            RedisResult::Boolean(boolean) => Ok(boolean),
+            RedisResult::Int(int) => Ok(int != 0),
+            RedisResult::Nil => Ok(false),
+            RedisResult::Attribute(value, _attributes) => bool::from_redis_value(*value),
+            value @ RedisResult::Bytes(_) | value @ RedisResult::String(_) => {
+                match &into_text::<bool>(value.clone())?[..] {
+                    "OK" | "1" => Ok(true),
+                    "0" => Ok(false),
+                    _ => Err(conversion_error::<bool>(value)),
+                }
+            },
+            value => Err(conversion_error::<bool>(value)),
+        }
+    }
+}
+
+impl FromRedisValue for String {
+    /// The numbers are converted into their text representation.
+    fn from_redis_value(value: RedisResult) -> Result<String, RedisError> {
+        match value {
+            RedisResult::Int(int) => Ok(int.to_string()),
+            RedisResult::Double(double) => Ok(double.to_string()),
+            value => into_text::<String>(value),
+        }
+    }
+}
+
+impl FromRedisValue for Vec<u8> {
+    /// The binary data of a string response.
+    fn from_redis_value(value: RedisResult) -> Result<Vec<u8>, RedisError> {
+        match value {
+            RedisResult::Bytes(bytes) => Ok(bytes),
+            RedisResult::String(value) | RedisResult::BigNumber(value) | RedisResult::VerbatimString(_, value) => Ok(value.into_bytes()),
+            RedisResult::Int(int) => Ok(int.to_string().into_bytes()),
+            RedisResult::Double(double) => Ok(double.to_string().into_bytes()),
+            RedisResult::Attribute(value, _attributes) => Vec::<u8>::from_redis_value(*value),
+            value => Err(conversion_error::<Vec<u8>>(value)),
+        }
+    }
+}
+
+impl<T: FromRedisValue> FromRedisValue for Option<T> {
+    fn from_redis_value(value: RedisResult) -> Result<Option<T>, RedisError> {
+        match value {
+            RedisResult::Nil => Ok(None),
+            RedisResult::Attribute(value, _attributes) => Option::<T>::from_redis_value(*value),
+            value => T::from_redis_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: FromRedisValue> FromRedisValue for Result<T, RedisError> {
+    /// The conversion of a single response, so an error replied to one command of a pipeline doesn't fail the others.
+    fn from_redis_value(value: RedisResult) -> Result<Result<T, RedisError>, RedisError> {
+        Ok(T::from_redis_value(value))
+    }
+}
+
+impl<T: FromRedisValue> FromRedisValue for Vec<T> {
+    /// A nil response is an empty vector and a map is flattened into its keys and values.
+    fn from_redis_value(value: RedisResult) -> Result<Vec<T>, RedisError> {
+        into_elements::<Vec<T>>(value)?.into_iter().map(T::from_redis_value).collect()
+    }
+}
+
+impl<T: FromRedisValue + Eq + Hash, S: BuildHasher + Default> FromRedisValue for HashSet<T, S> {
+    fn from_redis_value(value: RedisResult) -> Result<HashSet<T, S>, RedisError> {
+        into_elements::<HashSet<T, S>>(value)?.into_iter().map(T::from_redis_value).collect()
+    }
+}
+
+impl<T: FromRedisValue + Ord> FromRedisValue for BTreeSet<T> {
+    fn from_redis_value(value: RedisResult) -> Result<BTreeSet<T>, RedisError> {
+        into_elements::<BTreeSet<T>>(value)?.into_iter().map(T::from_redis_value).collect()
+    }
+}
+
+impl<K: FromRedisValue + Eq + Hash, V: FromRedisValue, S: BuildHasher + Default> FromRedisValue for HashMap<K, V, S> {
+    /// A map response or an array alternating the keys and the values.
+    fn from_redis_value(value: RedisResult) -> Result<HashMap<K, V, S>, RedisError> {
+        into_pairs::<HashMap<K, V, S>>(value)?.into_iter()
+            .map(|(key, value)| Ok((K::from_redis_value(key)?, V::from_redis_value(value)?)))
+            .collect()
+    }
+}
+
+impl<K: FromRedisValue + Ord, V: FromRedisValue> FromRedisValue for BTreeMap<K, V> {
+    /// A map response or an array alternating the keys and the values.
+    fn from_redis_value(value: RedisResult) -> Result<BTreeMap<K, V>, RedisError> {
+        into_pairs::<BTreeMap<K, V>>(value)?.into_iter()
+            .map(|(key, value)| Ok((K::from_redis_value(key)?, V::from_redis_value(value)?)))
+            .collect()
+    }
+}
+
+macro_rules! from_redis_value_for_tuples {
+    ($(($($name:ident),+) with $len:expr;)+) => {
+        $(
+            impl<$($name: FromRedisValue),+> FromRedisValue for ($($name,)+) {
+                /// An aggregate response with exactly as many elements as the tuple.
+                fn from_redis_value(value: RedisResult) -> Result<($($name,)+), RedisError> {
+                    let elements = into_elements::<($($name,)+)>(value)?;
+                    if elements.len() != $len {
+                        return Err(conversion_error::<($($name,)+)>(RedisResult::Array(elements)));
+                    }
+                    let mut elements = elements.into_iter();
+                    Ok(($($name::from_redis_value(elements.next().unwrap_or(RedisResult::Nil))?,)+))
+                }
+            }
+        )+
+    }
+}
+
+from_redis_value_for_tuples! {
+    (A) with 1;
+    (A, B) with 2;
+    (A, B, C) with 3;
+    (A, B, C, D) with 4;
+    (A, B, C, D, E) with 5;
+    (A, B, C, D, E, F) with 6;
+    (A, B, C, D, E, F, G) with 7;
+    (A, B, C, D, E, F, G, H) with 8;
+    (A, B, C, D, E, F, G, H, I) with 9;
+    (A, B, C, D, E, F, G, H, I, J) with 10;
+    (A, B, C, D, E, F, G, H, I, J, K) with 11;
+    (A, B, C, D, E, F, G, H, I, J, K, L) with 12;
 }
 
 impl fmt::Display for RedisResult {
@@ -26,6 +327,15 @@ impl fmt::Display for RedisResult {
             RedisResult::String(ref value) => write!(f, "{:?}", value),
             RedisResult::Int(ref value) => write!(f, "{:?}", value),
             RedisResult::Nil => write!(f, "null"),
+            RedisResult::Map(ref value) => write!(f, "{:?}", value),
+            RedisResult::Set(ref value) => write!(f, "{:?}", value),
+            RedisResult::Double(ref value) => write!(f, "{:?}", value),
+            RedisResult::Boolean(ref value) => write!(f, "{:?}", value),
+            RedisResult::BigNumber(ref value) => write!(f, "{}", value),
+            RedisResult::VerbatimString(ref _format, ref value) => write!(f, "{:?}", value),
+            RedisResult::Attribute(ref value, ref _attributes) => write!(f, "{}", value),
+            RedisResult::Push(ref value) => write!(f, "{:?}", value),
+            RedisResult::Error(ref value) => write!(f, "{}", value),
         }
     }
 }
@@ -33,12 +343,20 @@ impl fmt::Display for RedisResult {
 impl From<RedisResult> for String {
     fn from(result: RedisResult) -> String {
         match result {
-            RedisResult::Array(value) => {
+            RedisResult::Array(value) | RedisResult::Set(value) | RedisResult::Push(value) => {
                 let mut retval: String = "[".to_string();
                 for res in value {
-                    retval = retval + &res.convert::<String>() + &",".to_string();
+                    retval = retval + &res.convert::<String>() + ",";
+                }
+                retval += "]";
+                retval
+            },
+            RedisResult::Map(value) => {
+                let mut retval: String = "{".to_string();
+                for (key, res) in value {
+                    retval = retval + &key.convert::<String>() + ":" + &res.convert::<String>() + ",";
                 }
-                retval = retval + &"]".to_string();
+                retval += "}";
                 retval
             },
             RedisResult::Bytes(value) => {
@@ -51,6 +369,12 @@ impl From<RedisResult> for String {
             RedisResult::String(value) => value,
             RedisResult::Int(value) => value.to_string(),
             RedisResult::Nil => "null".to_string(),
+            RedisResult::Double(value) => value.to_string(),
+            RedisResult::Boolean(value) => value.to_string(),
+            RedisResult::BigNumber(value) => value,
+            RedisResult::VerbatimString(_format, value) => value,
+            RedisResult::Attribute(value, _attributes) => value.convert::<String>(),
+            RedisResult::Error(value) => value.to_string(),
         }
     }
 }
@@ -59,11 +383,10 @@ impl From<RedisResult> for String {
 impl From<RedisResult> for Vec<u8> {
     fn from(result: RedisResult) -> Vec<u8> {
         match result {
-            RedisResult::Array(value) => RedisResult::Array(value).convert::<String>().into_bytes(),
             RedisResult::Bytes(value) => value,
-            RedisResult::String(value) => value.into_bytes(),
-            RedisResult::Int(value) => value.to_string().into_bytes(),
             RedisResult::Nil => vec![],
+            RedisResult::Attribute(value, _attributes) => value.convert::<Vec<u8>>(),
+            value => value.convert::<String>().into_bytes(),
         }
     }
 }
@@ -71,13 +394,21 @@ impl From<RedisResult> for Vec<u8> {
 impl From<RedisResult> for Vec<String> {
     fn from(result: RedisResult) -> Vec<String> {
         match result {
-            RedisResult::Array(value) => {
+            RedisResult::Array(value) | RedisResult::Set(value) | RedisResult::Push(value) => {
                 let mut retval = Vec::new();
                 for res in value {
                     retval.push(res.convert::<String>());
                 }
                 retval
             },
+            RedisResult::Map(value) => {
+                let mut retval = Vec::new();
+                for (key, res) in value {
+                    retval.push(key.convert::<String>());
+                    retval.push(res.convert::<String>());
+                }
+                retval
+            },
             RedisResult::Bytes(value) => {
                 let result = str::from_utf8(&value);
                 match result {
@@ -85,9 +416,9 @@ impl From<RedisResult> for Vec<String> {
                     Err(err) => vec![err.to_string()],
                 }
             },
-            RedisResult::String(value) => vec![value],
-            RedisResult::Int(value) => vec![value.to_string()],
             RedisResult::Nil => vec![],
+            RedisResult::Attribute(value, _attributes) => value.convert::<Vec<String>>(),
+            value => vec![value.convert::<String>()],
         }
     }
 }
@@ -107,25 +438,16 @@ impl From<RedisResult> for HashMap<String, String> {
                 }
                 retval
             },
-            RedisResult::Bytes(value) => {
-                let str_value = RedisResult::Bytes(value).convert::<String>();
-                let mut retval = HashMap::new();
-                retval.insert("Stringified value".to_string(), str_value);
-                retval
-            },
-            RedisResult::String(value) => {
+            RedisResult::Map(value) => {
                 let mut retval = HashMap::new();
-                retval.insert("Stringified value".to_string(), value);
-                retval
-            },
-            RedisResult::Int(value) => {
-                let str_value = RedisResult::Int(value).convert::<String>();
-                let mut retval = HashMap::new();
-                retval.insert("Stringified value".to_string(), str_value);
+                for (key, res) in value {
+                    retval.insert(key.convert::<String>(), res.convert::<String>());
+                }
                 retval
             },
-            RedisResult::Nil => {
-                let str_value = RedisResult::Nil.convert::<String>();
+            RedisResult::Attribute(value, _attributes) => value.convert::<HashMap<String, String>>(),
+            value => {
+                let str_value = value.convert::<String>();
                 let mut retval = HashMap::new();
                 retval.insert("Stringified value".to_string(), str_value);
                 retval
@@ -137,12 +459,10 @@ impl From<RedisResult> for HashMap<String, String> {
 impl From<RedisResult> for i64 {
     fn from(result: RedisResult) -> i64 {
         match result {
-            RedisResult::Array(_value) => 0,
-            RedisResult::Bytes(_value) => 0,
-            RedisResult::String(_value) => 0,
             RedisResult::Int(value) => value,
-            RedisResult::Nil => 0,
+            RedisResult::Boolean(value) => value as i64,
+            RedisResult::Attribute(value, _attributes) => value.convert::<i64>(),
+            _ => 0,
         }
     }
 }
-
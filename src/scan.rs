@@ -0,0 +1,187 @@
+use commands::{CommandBuilder, RedisCommand};
+use errors::{ConversionError, RedisError};
+use redis::RedisClient;
+use results::{FromRedisValue, RedisResult};
+use std::any;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+/// The ScanOptions hold the MATCH, COUNT and TYPE options of the SCAN family of commands, none of them being set by default.
+///
+/// ```
+/// # use redis_client::commands::CommandBuilder;
+/// let cmd = &mut redis_client::RedisCommand::new();
+/// cmd.scan(0, redis_client::ScanOptions::new().set_pattern("user:*").set_count(100).set_type("hash"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    pattern: Option<String>,
+    count: Option<u32>,
+    key_type: Option<String>,
+}
+
+impl ScanOptions {
+    pub fn new() -> ScanOptions {
+        ScanOptions {
+            pattern: None,
+            count: None,
+            key_type: None,
+        }
+    }
+
+    /// Only return the elements matching the glob-style pattern. The filter is applied after the elements are read,
+    /// so an iteration may return nothing while the cursor is not 0.
+    pub fn set_pattern<P: ToString>(&mut self, pattern: P) -> &mut ScanOptions {
+        self.pattern = Some(pattern.to_string());
+        self
+    }
+
+    /// Set how many elements redis reads at each iteration, 10 when it is not set
+    pub fn set_count(&mut self, count: u32) -> &mut ScanOptions {
+        self.count = Some(count);
+        self
+    }
+
+    /// Only return the keys holding the type (string, list, set, zset, hash, stream...). It is only used by SCAN.
+    pub fn set_type<T: ToString>(&mut self, key_type: T) -> &mut ScanOptions {
+        self.key_type = Some(key_type.to_string());
+        self
+    }
+
+    pub fn get_pattern(&self) -> Option<&str> {
+        self.pattern.as_ref().map(|pattern| &pattern[..])
+    }
+
+    pub fn get_count(&self) -> Option<u32> {
+        self.count
+    }
+
+    pub fn get_type(&self) -> Option<&str> {
+        self.key_type.as_ref().map(|key_type| &key_type[..])
+    }
+}
+
+/// What is scanned: the keys of the database or the elements of the collection stored at a key
+enum ScanTarget {
+    Keys,
+    Set(Vec<Vec<u8>>),
+    Hash(Vec<Vec<u8>>),
+    SortedSet(Vec<Vec<u8>>),
+}
+
+/// A ScanIter follows the cursor of a SCAN, SSCAN, HSCAN or ZSCAN command, sending the next command each time the elements
+/// returned by the previous one are consumed. It ends once redis returns the cursor 0.
+///
+/// Each element is converted to T: a key or a member for SCAN and SSCAN, a `(field, value)` pair for HSCAN and a `(member, score)`
+/// pair for ZSCAN. An error, when sending a command or converting an element, is returned as an item and ends the iteration
+/// if it comes from a command. An element may be returned several times when the collection changes during the iteration.
+///
+/// ```no_run
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// # let mut client = try!(redis_client::RedisClient::new("127.0.0.1", "6379"));
+/// for field_value in client.hscan_iter::<(String, i64), _>("scores", &redis_client::ScanOptions::new()) {
+///     let (field, value) = try!(field_value);
+/// }
+/// # Ok(())}
+/// ```
+pub struct ScanIter<'a, T> {
+    client: &'a mut RedisClient,
+    target: ScanTarget,
+    options: ScanOptions,
+    cursor: u64,
+    finished: bool,
+    elements: VecDeque<RedisResult>,
+    element_type: PhantomData<T>,
+}
+
+impl<'a, T: FromRedisValue> ScanIter<'a, T> {
+    fn new(client: &'a mut RedisClient, target: ScanTarget, options: &ScanOptions) -> ScanIter<'a, T> {
+        ScanIter {
+            client,
+            target,
+            options: options.clone(),
+            cursor: 0,
+            finished: false,
+            elements: VecDeque::new(),
+            element_type: PhantomData,
+        }
+    }
+
+    pub(crate) fn keys(client: &'a mut RedisClient, options: &ScanOptions) -> ScanIter<'a, T> {
+        ScanIter::new(client, ScanTarget::Keys, options)
+    }
+
+    pub(crate) fn set(client: &'a mut RedisClient, key: Vec<Vec<u8>>, options: &ScanOptions) -> ScanIter<'a, T> {
+        ScanIter::new(client, ScanTarget::Set(key), options)
+    }
+
+    pub(crate) fn hash(client: &'a mut RedisClient, key: Vec<Vec<u8>>, options: &ScanOptions) -> ScanIter<'a, T> {
+        ScanIter::new(client, ScanTarget::Hash(key), options)
+    }
+
+    pub(crate) fn sorted_set(client: &'a mut RedisClient, key: Vec<Vec<u8>>, options: &ScanOptions) -> ScanIter<'a, T> {
+        ScanIter::new(client, ScanTarget::SortedSet(key), options)
+    }
+
+    /// Send the command for the current cursor and keep the elements it returns, paired for HSCAN and ZSCAN
+    fn fetch(&mut self) -> Result<(), RedisError> {
+        let cmd = &mut RedisCommand::new();
+        let paired = match self.target {
+            ScanTarget::Keys => {
+                cmd.scan(self.cursor, &self.options);
+                false
+            },
+            ScanTarget::Set(ref key) => {
+                cmd.sscan(key, self.cursor, &self.options);
+                false
+            },
+            ScanTarget::Hash(ref key) => {
+                cmd.hscan(key, self.cursor, &self.options);
+                true
+            },
+            ScanTarget::SortedSet(ref key) => {
+                cmd.zscan(key, self.cursor, &self.options);
+                true
+            },
+        };
+
+        let (cursor, elements) = self.client.exec_redis_command(cmd)?.try_convert::<(u64, Vec<RedisResult>)>()?;
+        if paired {
+            if elements.len() % 2 != 0 {
+                return Err(RedisError::Conversion(ConversionError {
+                    target: any::type_name::<Vec<(RedisResult, RedisResult)>>(),
+                    value: RedisResult::Array(elements),
+                }));
+            }
+            let mut elements = elements.into_iter();
+            while let (Some(first), Some(second)) = (elements.next(), elements.next()) {
+                self.elements.push_back(RedisResult::Array(vec![first, second]));
+            }
+        } else {
+            self.elements.extend(elements);
+        }
+
+        self.cursor = cursor;
+        self.finished = cursor == 0;
+        Ok(())
+    }
+}
+
+impl<'a, T: FromRedisValue> Iterator for ScanIter<'a, T> {
+    type Item = Result<T, RedisError>;
+
+    fn next(&mut self) -> Option<Result<T, RedisError>> {
+        loop {
+            if let Some(element) = self.elements.pop_front() {
+                return Some(element.try_convert::<T>());
+            }
+            if self.finished {
+                return None;
+            }
+            if let Err(err) = self.fetch() {
+                self.finished = true;
+                return Some(Err(err));
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+extern crate sha1_smol;
+
+use args::ToRedisArgs;
+use commands::{CommandBuilder, CommandExecutor, RedisCommand};
+use errors::{RedisError, ServerErrorKind};
+use results::{FromRedisValue, RedisResult};
+use std::fmt;
+
+use self::sha1_smol::Sha1;
+
+/// A Script is a Lua script invoked by its SHA1, so its source is only sent when redis doesn't know it yet.
+///
+/// The SHA1 is computed locally when the Script is created. It is invoked with EVALSHA and when redis replies NOSCRIPT,
+/// because its script cache was flushed or the server restarted, the script is sent again with EVAL, which loads it and runs it.
+///
+/// ```no_run
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// # let mut client = try!(redis_client::RedisClient::new("127.0.0.1", "6379"));
+/// let script = redis_client::Script::new("return redis.call('INCRBY', KEYS[1], ARGV[1])");
+/// let counter: i64 = try!(script.invoke(&mut client, "counter", 5));
+/// # Ok(())}
+/// ```
+#[derive(Clone)]
+pub struct Script {
+    code: String,
+    hash: String,
+}
+
+impl Script {
+    pub fn new(code: &str) -> Script {
+        Script {
+            code: code.to_string(),
+            hash: Sha1::from(code).digest().to_string(),
+        }
+    }
+
+    pub fn get_code(&self) -> &str {
+        &self.code
+    }
+
+    /// Get the SHA1 of the script, in hexadecimal
+    pub fn get_hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// Run the script with the keys and the arguments, each one producing as many KEYS or ARGV as ToRedisArgs gives
+    pub fn invoke<T: FromRedisValue, K: ToRedisArgs, A: ToRedisArgs, E: CommandExecutor>(&self, executor: &mut E, keys: K, args: A) -> Result<T, RedisError> {
+        let result = match executor.exec_redis_command(RedisCommand::new().evalsha(&self.hash[..], &keys, &args)) {
+            Err(RedisError::Response(ref err)) | Ok(RedisResult::Error(ref err)) if err.kind == ServerErrorKind::NoScript => {
+                executor.exec_redis_command(RedisCommand::new().eval(&self.code[..], &keys, &args))?
+            },
+            result => result?,
+        };
+        result.try_convert::<T>()
+    }
+
+    /// Load the script in the script cache of redis without running it
+    pub fn load<E: CommandExecutor>(&self, executor: &mut E) -> Result<(), RedisError> {
+        executor.exec_redis_command(RedisCommand::new().script_load(&self.code[..]))?.try_convert::<String>()?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Redis Script - SHA1 = {}", self.hash)
+    }
+}
@@ -0,0 +1,293 @@
+use commands::RedisCommand;
+use config::ConnectionConfig;
+use errors::{ParsingError, RedisError};
+use pool::{PoolConfig, RedisPool};
+use redis::{RedisClient, RedisClientAsync};
+use results::RedisResult;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+
+/// The channel where the sentinels announce the promotion of a new master
+const SWITCH_MASTER_CHANNEL: &str = "+switch-master";
+
+/// How long the watcher waits for a message before checking that someone still uses the master address
+const WATCH_INTERVAL_MS: u64 = 200;
+
+/// The host and the port of a redis node
+type NodeAddress = (String, u16);
+
+/// What the SentinelClient shares with its watcher thread and with the address resolvers of its clients
+struct SentinelState {
+    master_name: String,
+    sentinels: Vec<ConnectionConfig>,
+    config: ConnectionConfig,
+    master: Mutex<NodeAddress>,
+    next_replica: AtomicUsize,
+}
+
+impl SentinelState {
+    fn get_master(&self) -> NodeAddress {
+        self.master.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+
+    fn set_master(&self, address: NodeAddress) {
+        *self.master.lock().unwrap_or_else(|err| err.into_inner()) = address;
+    }
+
+    /// Ask the sentinels for the master, the first one giving the address of a node whose ROLE is master wins
+    fn resolve_master(&self) -> Result<NodeAddress, RedisError> {
+        let mut last_error = RedisError::Io(io::Error::new(io::ErrorKind::NotConnected, "no sentinel could be reached"));
+        for sentinel in &self.sentinels {
+            match self.query_master(sentinel) {
+                Ok(address) => return Ok(address),
+                Err(err) => last_error = err,
+            };
+        }
+        Err(last_error)
+    }
+
+    fn query_master(&self, sentinel: &ConnectionConfig) -> Result<NodeAddress, RedisError> {
+        let mut client = RedisClient::with_config(sentinel)?;
+        let address = client.exec_redis_command(RedisCommand::new().add_cmd("SENTINEL").add_arg("get-master-addr-by-name").add_arg(&self.master_name[..]).end())?
+            .try_convert::<Option<NodeAddress>>()?;
+        let (host, port) = match address {
+            Some(address) => address,
+            None => return Err(RedisError::Parse(ParsingError::InvalidValue(format!("the sentinel doesn't monitor the master {}", self.master_name)))),
+        };
+
+        let mut config = self.config.clone();
+        config.set_host(&host).set_port(port);
+        let role = RedisClient::with_config(&config)?.exec_redis_command(RedisCommand::new().add_cmd("ROLE").end())?;
+        match role.try_convert::<Vec<RedisResult>>()?.into_iter().next().map(|role| role.try_convert::<String>()) {
+            Some(Ok(ref role)) if role == "master" => Ok((host, port)),
+            _ => Err(RedisError::Parse(ParsingError::InvalidValue(format!("{} is not a master", config)))),
+        }
+    }
+
+    /// Ask the sentinels for the replicas of the master which are neither down nor disconnected
+    fn query_replicas(&self) -> Result<Vec<NodeAddress>, RedisError> {
+        let mut last_error = RedisError::Io(io::Error::new(io::ErrorKind::NotConnected, "no sentinel could be reached"));
+        for sentinel in &self.sentinels {
+            let result = RedisClient::with_config(sentinel).and_then(|mut client| {
+                match client.exec_redis_command(RedisCommand::new().add_cmd("SENTINEL").add_arg("replicas").add_arg(&self.master_name[..]).end()) {
+                    // the sentinels older than redis 5 only know the SLAVES subcommand
+                    Err(RedisError::Response(_)) => client.exec_redis_command(RedisCommand::new().add_cmd("SENTINEL").add_arg("slaves").add_arg(&self.master_name[..]).end()),
+                    result => result,
+                }
+            });
+            match result.and_then(|reply| reply.try_convert::<Vec<HashMap<String, String>>>()) {
+                Ok(replicas) => return Ok(replicas.iter().filter_map(parse_replica).collect()),
+                Err(err) => last_error = err,
+            };
+        }
+        Err(last_error)
+    }
+}
+
+/// Get the address of a replica described by SENTINEL REPLICAS, None when it can't be used
+fn parse_replica(replica: &HashMap<String, String>) -> Option<NodeAddress> {
+    let flags = replica.get("flags").map(|flags| &flags[..]).unwrap_or("");
+    if flags.split(',').any(|flag| flag == "s_down" || flag == "o_down" || flag == "disconnected") {
+        return None;
+    }
+    Some((replica.get("ip")?.clone(), replica.get("port")?.parse::<u16>().ok()?))
+}
+
+/// Get the address of the new master from a +switch-master message: `<master name> <old ip> <old port> <new ip> <new port>`
+fn parse_switch_master(payload: &str, master_name: &str) -> Option<NodeAddress> {
+    let words: Vec<&str> = payload.split_whitespace().collect();
+    match words[..] {
+        [name, _, _, host, port] if name == master_name => Some((host.to_string(), port.parse::<u16>().ok()?)),
+        _ => None,
+    }
+}
+
+/// Listen to the +switch-master messages of the sentinels, one sentinel at a time, and update the master address.
+/// It stops once the SentinelClient and all the clients it created are dropped.
+fn watch_switch_master(weak_state: Weak<SentinelState>) {
+    loop {
+        let sentinels = match weak_state.upgrade() {
+            Some(state) => state.sentinels.clone(),
+            None => return,
+        };
+
+        for sentinel in &sentinels {
+            let mut pubsub = match RedisClient::with_config(sentinel) {
+                Ok(client) => client.into_pubsub(),
+                Err(_) => continue,
+            };
+            if pubsub.subscribe(SWITCH_MASTER_CHANNEL).is_err() {
+                continue;
+            }
+
+            // the master may have switched while no sentinel was listened to
+            match weak_state.upgrade() {
+                Some(state) => {
+                    if let Ok(address) = state.resolve_master() {
+                        state.set_master(address);
+                    }
+                },
+                None => return,
+            };
+
+            loop {
+                let message = pubsub.get_message(Some(Duration::from_millis(WATCH_INTERVAL_MS)));
+                let state = match weak_state.upgrade() {
+                    Some(state) => state,
+                    None => return,
+                };
+                match message {
+                    Ok(Some(message)) => {
+                        if let Some(address) = message.get_payload_str().ok().and_then(|payload| parse_switch_master(payload, &state.master_name)) {
+                            state.set_master(address);
+                        }
+                    },
+                    Ok(None) => {},
+                    Err(_) => break,
+                };
+            }
+        }
+        thread::sleep(Duration::from_millis(WATCH_INTERVAL_MS));
+    }
+}
+
+/// A SentinelClient finds the master of a redis deployment monitored by sentinels and follows its failovers.
+///
+/// The master address is asked to the sentinels with `SENTINEL get-master-addr-by-name`, then checked with ROLE.
+/// A background thread subscribes to the +switch-master channel of a sentinel and updates the address when a replica is promoted.
+///
+/// The RedisClient, RedisClientAsync and RedisPool it creates resolve the master address before each command:
+/// after a failover, their connections are closed and opened again to the new master.
+///
+/// ```no_run
+/// # use redis_client::commands::CommandSender;
+/// # fn function() -> Result<(), redis_client::errors::RedisError> {
+/// let sentinel = try!(redis_client::SentinelClient::new(&["redis://127.0.0.1:26379", "redis://127.0.0.1:26380"], "mymaster"));
+/// let mut client = try!(sentinel.get_client());
+/// let value: Option<String> = try!(client.get("key"));
+/// # Ok(())}
+/// ```
+pub struct SentinelClient {
+    state: Arc<SentinelState>,
+}
+
+impl SentinelClient {
+    /// Create a SentinelClient from the URLs of some of the sentinels and the name of the master they monitor.
+    /// The master and the replicas are reached with the default settings of ConnectionConfig::new.
+    pub fn new<U: AsRef<str>>(urls: &[U], master_name: &str) -> Result<SentinelClient, RedisError> {
+        let mut sentinels = Vec::with_capacity(urls.len());
+        for url in urls {
+            sentinels.push(ConnectionConfig::from_url(url.as_ref())?);
+        }
+        SentinelClient::with_configs(&sentinels, master_name, &ConnectionConfig::new("127.0.0.1", 6379))
+    }
+
+    /// Create a SentinelClient from the configurations of some of the sentinels and the name of the master they monitor.
+    /// The host and the port of the config are replaced by the ones of the master or of a replica, its other settings are kept.
+    pub fn with_configs(sentinels: &[ConnectionConfig], master_name: &str, config: &ConnectionConfig) -> Result<SentinelClient, RedisError> {
+        if sentinels.is_empty() {
+            return Err(RedisError::InvalidInput("at least one sentinel is needed".to_string()));
+        }
+
+        let mut state = SentinelState {
+            master_name: master_name.to_string(),
+            sentinels: sentinels.to_vec(),
+            config: config.clone(),
+            master: Mutex::new((config.get_host().to_string(), config.get_port())),
+            next_replica: AtomicUsize::new(0),
+        };
+        let master = state.resolve_master()?;
+        state.master = Mutex::new(master);
+
+        let state = Arc::new(state);
+        let weak_state = Arc::downgrade(&state);
+        thread::spawn(move || watch_switch_master(weak_state));
+        Ok(SentinelClient { state })
+    }
+
+    /// Ask the sentinels for the master again, without waiting for a +switch-master message
+    pub fn refresh_master(&self) -> Result<(), RedisError> {
+        let address = self.state.resolve_master()?;
+        self.state.set_master(address);
+        Ok(())
+    }
+
+    /// Get the host and the port of the current master
+    pub fn get_master(&self) -> (String, u16) {
+        self.state.get_master()
+    }
+
+    pub fn get_master_name(&self) -> &str {
+        &self.state.master_name
+    }
+
+    pub fn get_config(&self) -> &ConnectionConfig {
+        &self.state.config
+    }
+
+    /// Get a configuration connecting to the current master, whose address resolver follows the failovers
+    pub fn get_master_config(&self) -> ConnectionConfig {
+        let (host, port) = self.state.get_master();
+        let state = self.state.clone();
+        let mut config = self.state.config.clone();
+        config.set_host(host).set_port(port).set_address_resolver(move || Some(state.get_master()));
+        config
+    }
+
+    /// Create a RedisClient connected to the master
+    pub fn get_client(&self) -> Result<RedisClient, RedisError> {
+        RedisClient::with_config(&self.get_master_config())
+    }
+
+    /// Create a RedisClientAsync connected to the master
+    pub fn get_async_client(&self) -> Result<RedisClientAsync, RedisError> {
+        RedisClientAsync::with_config(&self.get_master_config())
+    }
+
+    /// Create a RedisPool of connections to the master
+    pub fn get_pool(&self, pool_config: &PoolConfig) -> Result<RedisPool, RedisError> {
+        RedisPool::new(&self.get_master_config(), pool_config)
+    }
+
+    /// Get the host and the port of the replicas which are neither down nor disconnected
+    pub fn get_replicas(&self) -> Result<Vec<(String, u16)>, RedisError> {
+        self.state.query_replicas()
+    }
+
+    /// Create a RedisClient connected to a replica, for the read-only commands. The replicas are picked in turn.
+    /// The connection stays on the replica, it doesn't follow the failovers.
+    pub fn get_replica_client(&self) -> Result<RedisClient, RedisError> {
+        let replicas = self.state.query_replicas()?;
+        let mut last_error = RedisError::Io(io::Error::new(io::ErrorKind::NotFound, format!("the master {} has no available replica", self.state.master_name)));
+        let first = self.state.next_replica.fetch_add(1, Ordering::SeqCst);
+        for offset in 0..replicas.len() {
+            let (ref host, port) = replicas[(first + offset) % replicas.len()];
+            let mut config = self.state.config.clone();
+            config.set_host(host).set_port(port);
+            match RedisClient::with_config(&config) {
+                Ok(client) => return Ok(client),
+                Err(err) => last_error = err,
+            };
+        }
+        Err(last_error)
+    }
+}
+
+impl fmt::Debug for SentinelClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (host, port) = self.state.get_master();
+        write!(f, "Redis Sentinel Client - MASTER = {} - HOST = {} : PORT + {}", self.state.master_name, host, port)
+    }
+}
+
+impl fmt::Display for SentinelClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (host, port) = self.state.get_master();
+        write!(f, "Redis Sentinel Client - MASTER = {} - HOST = {} : PORT + {}", self.state.master_name, host, port)
+    }
+}
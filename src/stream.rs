@@ -0,0 +1,125 @@
+extern crate socket2;
+#[cfg(feature = "tls")]
+extern crate native_tls;
+
+use config::ConnectionConfig;
+use errors::RedisError;
+use self::socket2::{SockRef, TcpKeepalive};
+use std::io;
+use std::io::prelude::*;
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// The stream a client talks to redis through: a plain TCP connection or, with the tls feature, a TLS one.
+pub enum Stream {
+    Tcp(TcpStream),
+    #[cfg(feature = "tls")]
+    Tls(Box<native_tls::TlsStream<TcpStream>>),
+}
+
+impl Stream {
+    /// Open a connection to the host of the configuration and apply its socket options
+    pub fn connect(config: &ConnectionConfig) -> Result<Stream, RedisError> {
+        let tcp_stream = Stream::connect_tcp(config)?;
+
+        tcp_stream.set_read_timeout(config.get_read_timeout())?;
+        tcp_stream.set_write_timeout(config.get_write_timeout())?;
+        tcp_stream.set_nodelay(config.get_nodelay())?;
+        if let Some(keepalive) = config.get_keepalive() {
+            SockRef::from(&tcp_stream).set_tcp_keepalive(&TcpKeepalive::new().with_time(keepalive))?;
+        }
+
+        if config.get_tls() {
+            Stream::connect_tls(config, tcp_stream)
+        } else {
+            Ok(Stream::Tcp(tcp_stream))
+        }
+    }
+
+    /// Change the read timeout of an opened connection
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<(), RedisError> {
+        match *self {
+            Stream::Tcp(ref tcp_stream) => tcp_stream.set_read_timeout(timeout)?,
+            #[cfg(feature = "tls")]
+            Stream::Tls(ref tls_stream) => tls_stream.get_ref().set_read_timeout(timeout)?,
+        };
+        Ok(())
+    }
+
+    /// Open another handle on the connection, to write on it while another thread reads it. A TLS connection can't be shared.
+    pub fn try_clone(&self) -> Result<Stream, RedisError> {
+        match *self {
+            Stream::Tcp(ref tcp_stream) => Ok(Stream::Tcp(tcp_stream.try_clone()?)),
+            #[cfg(feature = "tls")]
+            Stream::Tls(_) => Err(RedisError::InvalidInput("a TLS connection can't be shared between threads".to_string())),
+        }
+    }
+
+    /// Close both directions of the connection, waking the reads blocked on it
+    pub fn shutdown(&self) -> Result<(), RedisError> {
+        match *self {
+            Stream::Tcp(ref tcp_stream) => tcp_stream.shutdown(Shutdown::Both)?,
+            #[cfg(feature = "tls")]
+            Stream::Tls(ref tls_stream) => tls_stream.get_ref().shutdown(Shutdown::Both)?,
+        };
+        Ok(())
+    }
+
+    /// Try each address the host resolves to until a connection is established
+    fn connect_tcp(config: &ConnectionConfig) -> Result<TcpStream, RedisError> {
+        let mut last_error = io::Error::new(io::ErrorKind::InvalidInput, format!("could not resolve {}", config));
+        for address in (config.get_host(), config.get_port()).to_socket_addrs()? {
+            let result = match config.get_connect_timeout() {
+                Some(timeout) => TcpStream::connect_timeout(&address, timeout),
+                None => TcpStream::connect(address),
+            };
+            match result {
+                Ok(tcp_stream) => return Ok(tcp_stream),
+                Err(err) => last_error = err,
+            };
+        }
+        Err(RedisError::Io(last_error))
+    }
+
+    #[cfg(feature = "tls")]
+    fn connect_tls(config: &ConnectionConfig, tcp_stream: TcpStream) -> Result<Stream, RedisError> {
+        let connector = native_tls::TlsConnector::new()
+            .map_err(|err| RedisError::Io(io::Error::other(err.to_string())))?;
+        connector.connect(config.get_host(), tcp_stream)
+            .map(|tls_stream| Stream::Tls(Box::new(tls_stream)))
+            .map_err(|err| RedisError::Io(io::Error::other(err.to_string())))
+    }
+
+    #[cfg(not(feature = "tls"))]
+    fn connect_tls(_config: &ConnectionConfig, _tcp_stream: TcpStream) -> Result<Stream, RedisError> {
+        Err(RedisError::Io(io::Error::new(io::ErrorKind::Unsupported, "TLS connections need the tls feature")))
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            Stream::Tcp(ref mut tcp_stream) => tcp_stream.read(buf),
+            #[cfg(feature = "tls")]
+            Stream::Tls(ref mut tls_stream) => tls_stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            Stream::Tcp(ref mut tcp_stream) => tcp_stream.write(buf),
+            #[cfg(feature = "tls")]
+            Stream::Tls(ref mut tls_stream) => tls_stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            Stream::Tcp(ref mut tcp_stream) => tcp_stream.flush(),
+            #[cfg(feature = "tls")]
+            Stream::Tls(ref mut tls_stream) => tls_stream.flush(),
+        }
+    }
+}
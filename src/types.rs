@@ -1,13 +1,45 @@
+use errors::RedisError;
+
 #[derive(Debug, Clone)]
 pub enum SenderType {
     Simple,
     Pipe(usize)
 }
 
-#[derive(Debug, Clone)]
+/// What a command sent by a PubSubClientAsync does to its subscriptions
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PubSubType {
 	Simple,
 	Channel(String),
-	Pattern(String)
+	Pattern(String),
+	/// A sharded channel, subscribed with SSUBSCRIBE
+	ShardChannel(String),
+	Channels(Vec<String>),
+	Patterns(Vec<String>),
+	ShardChannels(Vec<String>),
+	/// An unsubscription from the channels, from all of them when the list is empty
+	Unsubscribe(Vec<String>),
+	/// An unsubscription from the patterns, from all of them when the list is empty
+	PUnsubscribe(Vec<String>),
+	/// An unsubscription from the sharded channels, from all of them when the list is empty
+	SUnsubscribe(Vec<String>),
 }
 
+
+/// The version of the redis protocol spoken on a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolVersion {
+    Resp2,
+    Resp3,
+}
+
+/// An event on the connection of a client, given to the event handler of its ConnectionConfig
+#[derive(Debug, Clone)]
+pub enum ConnectionEvent {
+    /// The connection dropped because of the error
+    Disconnected(RedisError),
+    /// The connection was opened again after the number of attempts
+    Reconnected(u32),
+    /// All the attempts allowed by the ReconnectPolicy failed, the last one with the error
+    ReconnectFailed(RedisError),
+}
@@ -0,0 +1,194 @@
+//! The tests `aio` mod is checking the futures of the MultiplexedClient and the stream of the aio PubSub.
+
+extern crate redis_client;
+extern crate tokio;
+
+use redis_client::aio::{MultiplexedClient, PubSub};
+use redis_client::commands::{CommandBuilder, CommandSenderFuture};
+use redis_client::errors::RedisError;
+use redis_client::results::RedisResult;
+use redis_client::{PubSubMessage, RedisCommand};
+
+use self::tokio::runtime::Runtime;
+
+use std::future::poll_fn;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use support::{command_name, wait_for, FakeServer};
+
+fn runtime() -> Runtime {
+    Runtime::new().unwrap()
+}
+
+#[test]
+fn concurrent_commands_are_answered_in_order_on_one_connection() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let server_counter = counter.clone();
+    let server = FakeServer::start(move |args| {
+        match command_name(args).as_str() {
+            "GET" => Some(format!(":{}\r\n", server_counter.fetch_add(1, Ordering::SeqCst) + 1).into_bytes()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let runtime = runtime();
+    let client = runtime.block_on(MultiplexedClient::new(server.get_host(), server.get_port()).unwrap()).unwrap();
+
+    let futures: Vec<_> = (0..50).map(|_| client.get::<i64, _>("counter")).collect();
+    for (index, future) in futures.into_iter().enumerate() {
+        assert_eq!(runtime.block_on(future).unwrap(), index as i64 + 1);
+    }
+    assert_eq!(server.get_connection_nb(), 1);
+}
+
+#[test]
+fn cloned_clients_share_the_connection_between_tasks() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "GET" => {
+                let mut reply = format!("${}\r\n", args[1].len()).into_bytes();
+                reply.extend_from_slice(&args[1]);
+                reply.extend_from_slice(b"\r\n");
+                Some(reply)
+            },
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let runtime = runtime();
+    let client = runtime.block_on(MultiplexedClient::new(server.get_host(), server.get_port()).unwrap()).unwrap();
+
+    let tasks: Vec<_> = (0..10).map(|index| {
+        let client = client.clone();
+        runtime.spawn(client.get::<String, _>(format!("key{}", index)))
+    }).collect();
+    for (index, task) in tasks.into_iter().enumerate() {
+        assert_eq!(runtime.block_on(task).unwrap().unwrap(), format!("key{}", index));
+    }
+    assert_eq!(server.get_connection_nb(), 1);
+}
+
+#[test]
+fn error_responses_fail_their_future_only() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "GET" => Some(b"-ERR wrong\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let runtime = runtime();
+    let client = runtime.block_on(MultiplexedClient::new(server.get_host(), server.get_port()).unwrap()).unwrap();
+
+    let get = client.get::<String, _>("key");
+    let set = client.set::<String, _, _>("key", "value");
+    match runtime.block_on(get) {
+        Err(RedisError::Response(err)) => assert_eq!(err.to_string(), "ERR wrong"),
+        other => panic!("the command should fail, got {:?}", other),
+    };
+    assert_eq!(runtime.block_on(set).unwrap(), "OK");
+
+    let cmd = &mut RedisCommand::new();
+    cmd.get("key").set("key", "value");
+    let results = runtime.block_on(client.exec_redis_pipeline_command(cmd)).unwrap();
+    match (&results[0], &results[1]) {
+        (RedisResult::Error(err), RedisResult::String(status)) => assert_eq!((err.to_string().as_str(), status.as_str()), ("ERR wrong", "OK")),
+        other => panic!("the pipeline should keep the error, got {:?}", other),
+    };
+}
+
+#[test]
+fn dropped_connection_fails_the_pending_futures() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "GET" => None,
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let runtime = runtime();
+    let client = runtime.block_on(MultiplexedClient::new(server.get_host(), server.get_port()).unwrap()).unwrap();
+
+    match runtime.block_on(client.get::<String, _>("key")) {
+        Err(RedisError::Io(_)) => {},
+        other => panic!("the command should fail, got {:?}", other),
+    };
+    assert!(wait_for(|| !client.is_connected(), Duration::new(5, 0)));
+    match runtime.block_on(client.set::<String, _, _>("key", "value")) {
+        Err(RedisError::Io(_)) => {},
+        other => panic!("the command should fail, got {:?}", other),
+    };
+}
+
+#[test]
+fn protocol_errors_keep_their_kind_in_every_pending_future() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "GET" => Some(b"?not resp\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let runtime = runtime();
+    let client = runtime.block_on(MultiplexedClient::new(server.get_host(), server.get_port()).unwrap()).unwrap();
+
+    let first = client.get::<String, _>("key");
+    let second = client.get::<String, _>("key");
+    for future in [first, second] {
+        match runtime.block_on(future) {
+            Err(RedisError::Parse(_)) => {},
+            other => panic!("the command should fail with a parsing error, got {:?}", other),
+        };
+    }
+}
+
+#[test]
+fn connection_is_initialized_from_the_config() {
+    let commands = Arc::new(Mutex::new(Vec::new()));
+    let server_commands = commands.clone();
+    let server = FakeServer::start(move |args| {
+        server_commands.lock().unwrap().push(command_name(args));
+        match command_name(args).as_str() {
+            "AUTH" if args[1] == b"wrong" => Some(b"-WRONGPASS invalid password\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let runtime = runtime();
+
+    let mut config = server.get_config();
+    config.set_password("secret").set_db(2).set_client_name("worker");
+    let client = runtime.block_on(MultiplexedClient::with_config(&config)).unwrap();
+    assert_eq!(runtime.block_on(client.ping::<String>()).unwrap(), "OK");
+    assert_eq!(*commands.lock().unwrap(), vec!["AUTH", "SELECT", "CLIENT", "PING"]);
+
+    config.set_password("wrong");
+    match runtime.block_on(MultiplexedClient::with_config(&config)) {
+        Err(RedisError::Response(err)) => assert_eq!(err.to_string(), "WRONGPASS invalid password"),
+        other => panic!("the connection should fail, got {:?}", other),
+    };
+
+    config.set_tls(true);
+    assert!(runtime.block_on(MultiplexedClient::with_config(&config)).is_err());
+}
+
+#[test]
+fn pubsub_stream_receives_the_messages() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => Some(b"*3\r\n$9\r\nsubscribe\r\n$3\r\none\r\n:1\r\n*3\r\n$7\r\nmessage\r\n$3\r\none\r\n$5\r\nfirst\r\n\
+                                 *3\r\n$9\r\nsubscribe\r\n$3\r\ntwo\r\n:2\r\n".to_vec()),
+            "PSUBSCRIBE" => Some(b"*3\r\n$10\r\npsubscribe\r\n$2\r\nt*\r\n:3\r\n*4\r\n$8\r\npmessage\r\n$2\r\nt*\r\n$3\r\ntwo\r\n$6\r\nsecond\r\n".to_vec()),
+            "UNSUBSCRIBE" => Some(b"-ERR unknown\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let runtime = runtime();
+    let mut pubsub = runtime.block_on(PubSub::new(server.get_host(), server.get_port()).unwrap()).unwrap();
+
+    runtime.block_on(pubsub.subscribe(["one", "two"])).unwrap();
+    runtime.block_on(pubsub.psubscribe("t*")).unwrap();
+    assert!(runtime.block_on(pubsub.unsubscribe("one")).is_err());
+    assert!(runtime.block_on(pubsub.punsubscribe(Vec::<String>::new())).is_err());
+
+    let first = runtime.block_on(poll_fn(|cx| pubsub.poll_message(cx))).unwrap();
+    assert_eq!(first, PubSubMessage::new("one", None, b"first".to_vec()));
+    let second = runtime.block_on(poll_fn(|cx| pubsub.poll_message(cx))).unwrap();
+    assert_eq!(second, PubSubMessage::new("two", Some("t*"), b"second".to_vec()));
+}
@@ -0,0 +1,72 @@
+//! The tests `args` mod is checking if the values are correctly converted into command arguments.
+
+extern crate redis_client;
+
+use redis_client::ToRedisArgs;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// This function converts the value into arguments and compares them with the expected ones.
+fn check_args<T: ToRedisArgs + ?Sized>(value: &T, expected: &[&[u8]]) {
+    let args = value.to_redis_args();
+    assert_eq!(args, expected.iter().map(|arg| arg.to_vec()).collect::<Vec<Vec<u8>>>());
+}
+
+#[test]
+fn string_args_work() {
+    check_args("key", &[b"key"]);
+    check_args(&"key".to_string(), &[b"key"]);
+    check_args("", &[b""]);
+}
+
+#[test]
+fn binary_args_work() {
+    check_args(&[0u8, 255, 13, 10][..], &[b"\x00\xff\r\n"]);
+    check_args(&vec![0u8, 255], &[b"\x00\xff"]);
+    check_args(b"bytes", &[b"bytes"]);
+    check_args(&Vec::<u8>::new(), &[b""]);
+}
+
+#[test]
+fn number_args_work() {
+    check_args(&42u8, &[b"42"]);
+    check_args(&-7i64, &[b"-7"]);
+    check_args(&usize::MAX, &[usize::MAX.to_string().as_bytes()]);
+    check_args(&2.5f64, &[b"2.5"]);
+    check_args(&f64::INFINITY, &[b"inf"]);
+}
+
+#[test]
+fn bool_args_work() {
+    check_args(&true, &[b"1"]);
+    check_args(&false, &[b"0"]);
+}
+
+#[test]
+fn option_args_work() {
+    check_args(&Some("key"), &[b"key"]);
+    check_args(&None::<&str>, &[]);
+}
+
+#[test]
+fn sequence_args_work() {
+    check_args(&vec!["a", "b"], &[b"a", b"b"]);
+    check_args(&["a", "b"][..], &[b"a", b"b"]);
+    check_args(&vec![1i64, 2], &[b"1", b"2"]);
+    check_args(&vec![b"a".to_vec(), b"b".to_vec()], &[b"a", b"b"]);
+    check_args(&vec!["a", "b"].into_iter().collect::<BTreeSet<_>>(), &[b"a", b"b"]);
+}
+
+#[test]
+fn tuple_args_work() {
+    check_args(&("field", 3, &b"value"[..]), &[b"field", b"3", b"value"]);
+    check_args(&vec![("a", 1), ("b", 2)], &[b"a", b"1", b"b", b"2"]);
+}
+
+#[test]
+fn map_args_work() {
+    let mut map = BTreeMap::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    check_args(&map, &[b"a", b"1", b"b", b"2"]);
+}
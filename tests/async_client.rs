@@ -0,0 +1,155 @@
+//! The tests `async_client` mod is checking how the asynchronous clients deliver the responses to the callbacks.
+
+extern crate redis_client;
+
+use redis_client::commands::{CommandBuilder, CommandSenderAsync, PubSubCommandAsync, RedisCommand};
+use redis_client::errors::{RedisError, ServerErrorKind};
+use redis_client::{PubSubClientAsync, RedisClientAsync};
+
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+use support::{command_name, pump_until, FakeServer};
+
+fn timeout() -> Duration {
+    Duration::new(5, 0)
+}
+
+#[test]
+fn async_response_reaches_callback() {
+    let server = FakeServer::start(|_args| Some(b"$5\r\nvalue\r\n".to_vec()));
+    let mut client = RedisClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (tx, rx) = channel();
+    client.get("key", move |result| { let _ = tx.send(result); }).unwrap();
+
+    let result = pump_until(|| client.pump().unwrap(), &rx, timeout());
+    assert_eq!(result.unwrap().convert::<String>(), "value");
+}
+
+#[test]
+fn async_error_response_reaches_callback() {
+    let server = FakeServer::start(|_args| Some(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n".to_vec()));
+    let mut client = RedisClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (tx, rx) = channel();
+    client.get("key", move |result| { let _ = tx.send(result); }).unwrap();
+
+    match pump_until(|| client.pump().unwrap(), &rx, timeout()) {
+        Err(RedisError::Response(err)) => assert_eq!(err.kind, ServerErrorKind::WrongType),
+        other => panic!("expected a response error, got {:?}", other),
+    };
+}
+
+#[test]
+fn async_io_error_reaches_callback() {
+    let server = FakeServer::start(|_args| None);
+    let mut client = RedisClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (tx, rx) = channel();
+    client.get("key", move |result| { let _ = tx.send(result); }).unwrap();
+
+    match pump_until(|| client.pump().unwrap(), &rx, timeout()) {
+        Err(RedisError::Io(_)) => {},
+        other => panic!("expected an io error, got {:?}", other),
+    };
+}
+
+#[test]
+fn async_pipeline_error_reaches_callback() {
+    let server = FakeServer::start(|_args| None);
+    let mut client = RedisClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (tx, rx) = channel();
+    let cmd = &mut RedisCommand::new();
+    cmd.get("key").get("other_key");
+    client.exec_redis_pipeline_command_async(cmd, move |result| { let _ = tx.send(result); }).unwrap();
+
+    assert!(pump_until(|| client.pump().unwrap(), &rx, timeout()).is_err());
+}
+
+#[test]
+fn async_callbacks_are_called_once_each() {
+    let server = FakeServer::start(|args| {
+        if command_name(args) == "GET" {
+            Some(b"-ERR failure\r\n".to_vec())
+        } else {
+            Some(b"+OK\r\n".to_vec())
+        }
+    });
+    let mut client = RedisClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (tx, rx) = channel();
+    for index in 0..10 {
+        let tx = tx.clone();
+        if index % 2 == 0 {
+            client.get("key", move |result| { let _ = tx.send(result.is_ok()); }).unwrap();
+        } else {
+            client.set("key", "value", move |result| { let _ = tx.send(result.is_ok()); }).unwrap();
+        }
+    }
+
+    let mut results = Vec::new();
+    for _ in 0..10 {
+        results.push(pump_until(|| client.pump().unwrap(), &rx, timeout()));
+    }
+    assert_eq!(results.iter().filter(|ok| **ok).count(), 5);
+    client.pump().unwrap();
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn pubsub_error_response_reaches_callback() {
+    let server = FakeServer::start(|_args| Some(b"-ERR unknown command\r\n".to_vec()));
+    let mut client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (tx, rx) = channel();
+    client.publish("channel", "message", move |result| { let _ = tx.send(result); }).unwrap();
+
+    match pump_until(|| client.pump().unwrap(), &rx, timeout()) {
+        Err(RedisError::Response(err)) => assert_eq!(err.to_string(), "ERR unknown command"),
+        other => panic!("expected a response error, got {:?}", other),
+    };
+}
+
+#[test]
+fn async_replies_match_their_commands() {
+    let server = FakeServer::start(|args| {
+        let value = &args[1];
+        let mut reply = format!("${}\r\n", value.len()).into_bytes();
+        reply.extend_from_slice(value);
+        reply.extend_from_slice(b"\r\n");
+        Some(reply)
+    });
+    let mut client = RedisClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (tx, rx) = channel();
+    for index in 0..500 {
+        let tx = tx.clone();
+        client.get(index, move |result| { let _ = tx.send((index, result.unwrap().convert::<String>())); }).unwrap();
+    }
+
+    for expected in 0..500 {
+        let (index, value) = pump_until(|| client.pump().unwrap(), &rx, timeout());
+        assert_eq!(index, expected);
+        assert_eq!(value, index.to_string());
+    }
+    client.pump().unwrap();
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn async_callbacks_can_move_their_captures_out() {
+    let server = FakeServer::start(|_args| Some(b"$5\r\nvalue\r\n".to_vec()));
+    let mut client = RedisClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (tx, rx) = channel();
+    let values = vec!["first".to_string()];
+    client.get("key", move |result| {
+        let mut values = values;
+        values.push(result.unwrap().convert::<String>());
+        let _ = tx.send(values);
+    }).unwrap();
+
+    assert_eq!(pump_until(|| client.pump().unwrap(), &rx, timeout()), vec!["first", "value"]);
+}
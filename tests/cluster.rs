@@ -0,0 +1,351 @@
+//! The tests `cluster` mod is checking the slot hashing and how the ClusterClient routes the commands between the nodes.
+
+extern crate redis_client;
+
+use redis_client::cluster::{crc16, key_slot};
+use redis_client::commands::CommandSender;
+use redis_client::{ClusterClient, ConnectionConfig, Pipeline, RedisError};
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use support::{command_name, wait_for, FakeServer};
+
+/// The handler of a fake cluster node, called with the index of the node, the ports of all the nodes and the command
+type NodeHandler = Arc<dyn Fn(usize, &[u16], &[Vec<u8>]) -> Option<Vec<u8>> + Send + Sync>;
+
+/// The commands received by the nodes, with the index of the node
+type ReceivedCommands = Arc<Mutex<Vec<(usize, String)>>>;
+
+/// Start the nodes of a fake cluster, each one recording the commands it receives
+fn start_cluster<F>(node_nb: usize, handler: F) -> (Vec<FakeServer>, ReceivedCommands)
+    where F: Fn(usize, &[u16], &[Vec<u8>]) -> Option<Vec<u8>> + Send + Sync + 'static
+{
+    let handler: NodeHandler = Arc::new(handler);
+    let ports = Arc::new(Mutex::new(Vec::new()));
+    let commands = Arc::new(Mutex::new(Vec::new()));
+
+    let servers: Vec<FakeServer> = (0..node_nb).map(|node| {
+        let handler = handler.clone();
+        let ports = ports.clone();
+        let commands = commands.clone();
+        FakeServer::start(move |args| {
+            commands.lock().unwrap().push((node, command_name(args)));
+            let ports = ports.lock().unwrap().clone();
+            handler(node, &ports, args)
+        })
+    }).collect();
+    *ports.lock().unwrap() = servers.iter().map(|server| server.get_port()).collect();
+    (servers, commands)
+}
+
+fn bulk(value: &str) -> Vec<u8> {
+    format!("${}\r\n{}\r\n", value.len(), value).into_bytes()
+}
+
+/// Build a CLUSTER SLOTS response giving each range to the node listening on the port
+fn slots_reply(ranges: &[(u16, u16, u16)]) -> Vec<u8> {
+    let mut reply = format!("*{}\r\n", ranges.len()).into_bytes();
+    for &(start, end, port) in ranges {
+        reply.extend(format!("*3\r\n:{}\r\n:{}\r\n*2\r\n$9\r\n127.0.0.1\r\n:{}\r\n", start, end, port).into_bytes());
+    }
+    reply
+}
+
+/// The node owning a slot when the slots are split in halves between two nodes
+fn half_owner(slot: u16) -> usize {
+    if slot < 8192 { 0 } else { 1 }
+}
+
+/// A node of a cluster split in halves: GET answers the name of the node owning the key, or redirects to it
+fn half_node(node: usize, ports: &[u16], args: &[Vec<u8>]) -> Option<Vec<u8>> {
+    match command_name(args).as_str() {
+        "CLUSTER" => Some(slots_reply(&[(0, 8191, ports[0]), (8192, 16383, ports[1])])),
+        "GET" => {
+            let slot = key_slot(&args[1]);
+            let owner = half_owner(slot);
+            if owner == node {
+                Some(bulk(&format!("node{}", node)))
+            } else {
+                Some(format!("-MOVED {} 127.0.0.1:{}\r\n", slot, ports[owner]).into_bytes())
+            }
+        },
+        _ => Some(b"+OK\r\n".to_vec()),
+    }
+}
+
+fn cluster_client(servers: &[FakeServer]) -> ClusterClient {
+    let configs: Vec<ConnectionConfig> = servers.iter().map(|server| server.get_config()).collect();
+    ClusterClient::with_configs(&configs).unwrap()
+}
+
+#[test]
+fn key_slots_are_computed_with_hashtags() {
+    assert_eq!(crc16(b"123456789"), 0x31c3);
+    assert_eq!(key_slot(b"foo"), 12182);
+    assert_eq!(key_slot(b"bar"), 5061);
+    assert_eq!(key_slot(b"{user1000}.following"), key_slot(b"user1000"));
+    assert_eq!(key_slot(b"foo{}{bar}"), crc16(b"foo{}{bar}") % 16384);
+    assert_eq!(key_slot(b"foo{{bar}}zap"), key_slot(b"{bar"));
+    assert_eq!(key_slot(b"foo{bar}{zap}"), key_slot(b"bar"));
+}
+
+#[test]
+fn slots_are_loaded_from_the_seed_nodes() {
+    let (servers, _commands) = start_cluster(2, half_node);
+    let client = ClusterClient::new(&[format!("redis://127.0.0.1:{}", servers[1].get_port())]).unwrap();
+
+    assert_eq!(client.get_slot_node(0), Some(("127.0.0.1", servers[0].get_port())));
+    assert_eq!(client.get_slot_node(8191), Some(("127.0.0.1", servers[0].get_port())));
+    assert_eq!(client.get_slot_node(16383), Some(("127.0.0.1", servers[1].get_port())));
+}
+
+#[test]
+fn cluster_needs_a_seed_node() {
+    match ClusterClient::with_configs(&[]) {
+        Err(RedisError::InvalidInput(_)) => {},
+        other => panic!("unexpected result {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn slots_are_loaded_with_cluster_shards() {
+    let (servers, _commands) = start_cluster(1, |_node, ports, args| {
+        match (command_name(args).as_str(), args.get(1).map(|arg| String::from_utf8_lossy(arg).to_uppercase())) {
+            ("CLUSTER", Some(ref subcommand)) if subcommand == "SLOTS" => Some(b"-ERR unknown subcommand 'SLOTS'\r\n".to_vec()),
+            ("CLUSTER", _) => Some(format!("*1\r\n*4\r\n$5\r\nslots\r\n*2\r\n:0\r\n:16383\r\n$5\r\nnodes\r\n*2\r\n\
+                *6\r\n$4\r\nport\r\n:1\r\n$2\r\nip\r\n$9\r\n127.0.0.1\r\n$4\r\nrole\r\n$7\r\nreplica\r\n\
+                *6\r\n$4\r\nport\r\n:{}\r\n$2\r\nip\r\n$9\r\n127.0.0.1\r\n$4\r\nrole\r\n$6\r\nmaster\r\n", ports[0]).into_bytes()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let client = cluster_client(&servers);
+
+    assert_eq!(client.get_slot_node(42), Some(("127.0.0.1", servers[0].get_port())));
+}
+
+#[test]
+fn commands_are_routed_to_the_slot_owner() {
+    let (servers, commands) = start_cluster(2, half_node);
+    let mut client = cluster_client(&servers);
+
+    assert_eq!(client.get::<String, _>("foo").unwrap(), "node1");
+    assert_eq!(client.get::<String, _>("bar").unwrap(), "node0");
+    assert_eq!(client.get::<String, _>("{bar}.foo").unwrap(), "node0");
+    assert!(!commands.lock().unwrap().iter().any(|(_, name)| name == "ASKING"));
+}
+
+#[test]
+fn moved_redirection_refreshes_the_slots() {
+    let migrated = Arc::new(AtomicBool::new(false));
+    let node_migrated = migrated.clone();
+    let (servers, commands) = start_cluster(2, move |node, ports, args| {
+        match command_name(args).as_str() {
+            // every slot belongs to the first node until its half is migrated to the second one
+            "CLUSTER" if node_migrated.load(Ordering::SeqCst) => Some(slots_reply(&[(0, 8191, ports[0]), (8192, 16383, ports[1])])),
+            "CLUSTER" => Some(slots_reply(&[(0, 16383, ports[0])])),
+            "GET" if node == 0 && half_owner(key_slot(&args[1])) == 1 => {
+                node_migrated.store(true, Ordering::SeqCst);
+                Some(format!("-MOVED {} 127.0.0.1:{}\r\n", key_slot(&args[1]), ports[1]).into_bytes())
+            },
+            "GET" => Some(bulk(&format!("node{}", node))),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut client = cluster_client(&servers);
+    assert_eq!(client.get_slot_node(16383), Some(("127.0.0.1", servers[0].get_port())));
+
+    assert_eq!(client.get::<String, _>("foo").unwrap(), "node1");
+    assert!(migrated.load(Ordering::SeqCst));
+    assert_eq!(client.get_slot_node(16383), Some(("127.0.0.1", servers[1].get_port())));
+
+    commands.lock().unwrap().clear();
+    assert_eq!(client.get::<String, _>("foo").unwrap(), "node1");
+    assert_eq!(*commands.lock().unwrap(), vec![(1, "GET".to_string())]);
+}
+
+#[test]
+fn ask_redirection_sends_asking_once() {
+    let (servers, commands) = start_cluster(2, |node, ports, args| {
+        match command_name(args).as_str() {
+            "CLUSTER" => Some(slots_reply(&[(0, 16383, ports[0])])),
+            "GET" if node == 0 => Some(format!("-ASK {} 127.0.0.1:{}\r\n", key_slot(&args[1]), ports[1]).into_bytes()),
+            "GET" => Some(bulk("node1")),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut client = cluster_client(&servers);
+
+    assert_eq!(client.get::<String, _>("foo").unwrap(), "node1");
+    let node1_commands: Vec<String> = commands.lock().unwrap().iter().filter(|&&(node, _)| node == 1).map(|(_, name)| name.clone()).collect();
+    assert_eq!(node1_commands, vec!["ASKING", "GET"]);
+    assert_eq!(client.get_slot_node(key_slot(b"foo")), Some(("127.0.0.1", servers[0].get_port())));
+}
+
+#[test]
+fn tryagain_is_retried() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let node_attempts = attempts.clone();
+    let (servers, _commands) = start_cluster(1, move |_node, ports, args| {
+        match command_name(args).as_str() {
+            "CLUSTER" => Some(slots_reply(&[(0, 16383, ports[0])])),
+            "GET" if node_attempts.fetch_add(1, Ordering::SeqCst) < 2 => Some(b"-TRYAGAIN Multiple keys request during rehashing of slot\r\n".to_vec()),
+            "GET" => Some(bulk("value")),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut client = cluster_client(&servers);
+
+    assert_eq!(client.get::<String, _>("foo").unwrap(), "value");
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn pipeline_is_split_by_node() {
+    let (servers, commands) = start_cluster(2, half_node);
+    let mut client = cluster_client(&servers);
+    commands.lock().unwrap().clear();
+
+    let results: Vec<String> = Pipeline::new().get("foo").get("bar").set("bar", "value").ignore().get("foo").query(&mut client).unwrap();
+
+    assert_eq!(results, vec!["node1", "node0", "node1"]);
+    let commands = commands.lock().unwrap();
+    assert_eq!(commands.iter().filter(|&&(node, _)| node == 0).count(), 2);
+    assert_eq!(commands.iter().filter(|&&(node, _)| node == 1).count(), 2);
+}
+
+#[test]
+fn pipeline_follows_the_redirections_of_its_commands() {
+    let (servers, commands) = start_cluster(2, |node, ports, args| {
+        match command_name(args).as_str() {
+            // the slots are announced the other way around, so each command is moved
+            "CLUSTER" => Some(slots_reply(&[(0, 8191, ports[1]), (8192, 16383, ports[0])])),
+            _ => half_node(node, ports, args),
+        }
+    });
+    let mut client = cluster_client(&servers);
+    commands.lock().unwrap().clear();
+
+    let results: Vec<String> = Pipeline::new().get("foo").get("bar").get("{foo}1").get("{bar}1").query(&mut client).unwrap();
+    assert_eq!(results, vec!["node1", "node0", "node1", "node0"]);
+    // the slots are refreshed once for the whole pipeline
+    assert_eq!(commands.lock().unwrap().iter().filter(|(_, name)| name == "CLUSTER").count(), 1);
+}
+
+/// A node running the transaction SET foo value, GET foo. It queues the commands and EXEC returns their responses.
+fn transaction_node(args: &[Vec<u8>]) -> Option<Vec<u8>> {
+    match command_name(args).as_str() {
+        "SET" | "GET" => Some(b"+QUEUED\r\n".to_vec()),
+        "EXEC" => Some(b"*2\r\n+OK\r\n$5\r\nvalue\r\n".to_vec()),
+        _ => Some(b"+OK\r\n".to_vec()),
+    }
+}
+
+/// Run the transaction SET foo value, GET foo
+fn run_transaction(client: &mut ClusterClient) -> Result<(String, String), RedisError> {
+    Pipeline::new().atomic().set("foo", "value").get("foo").query(client)
+}
+
+fn node_commands(commands: &ReceivedCommands, node: usize) -> Vec<String> {
+    commands.lock().unwrap().iter().filter(|&&(command_node, _)| command_node == node).map(|(_, name)| name.clone()).collect()
+}
+
+#[test]
+fn transaction_follows_moved_redirection() {
+    let migrated = Arc::new(AtomicBool::new(false));
+    let node_migrated = migrated.clone();
+    let (servers, commands) = start_cluster(2, move |node, ports, args| {
+        match command_name(args).as_str() {
+            "CLUSTER" if node_migrated.load(Ordering::SeqCst) => Some(slots_reply(&[(0, 8191, ports[0]), (8192, 16383, ports[1])])),
+            "CLUSTER" => Some(slots_reply(&[(0, 16383, ports[0])])),
+            "SET" | "GET" if node == 0 => {
+                node_migrated.store(true, Ordering::SeqCst);
+                Some(format!("-MOVED {} 127.0.0.1:{}\r\n", key_slot(&args[1]), ports[1]).into_bytes())
+            },
+            "EXEC" if node == 0 => Some(b"-EXECABORT Transaction discarded because of previous errors.\r\n".to_vec()),
+            _ => transaction_node(args),
+        }
+    });
+    let mut client = cluster_client(&servers);
+    commands.lock().unwrap().clear();
+
+    assert_eq!(run_transaction(&mut client).unwrap(), ("OK".to_string(), "value".to_string()));
+    assert_eq!(node_commands(&commands, 1), vec!["MULTI", "SET", "GET", "EXEC"]);
+    assert_eq!(client.get_slot_node(key_slot(b"foo")), Some(("127.0.0.1", servers[1].get_port())));
+}
+
+#[test]
+fn transaction_sends_asking_before_multi() {
+    let (servers, commands) = start_cluster(2, |node, ports, args| {
+        match command_name(args).as_str() {
+            "CLUSTER" => Some(slots_reply(&[(0, 16383, ports[0])])),
+            "SET" | "GET" if node == 0 => Some(format!("-ASK {} 127.0.0.1:{}\r\n", key_slot(&args[1]), ports[1]).into_bytes()),
+            "EXEC" if node == 0 => Some(b"-EXECABORT Transaction discarded because of previous errors.\r\n".to_vec()),
+            _ => transaction_node(args),
+        }
+    });
+    let mut client = cluster_client(&servers);
+
+    assert_eq!(run_transaction(&mut client).unwrap(), ("OK".to_string(), "value".to_string()));
+    assert_eq!(node_commands(&commands, 1), vec!["ASKING", "MULTI", "SET", "GET", "EXEC"]);
+    assert_eq!(client.get_slot_node(key_slot(b"foo")), Some(("127.0.0.1", servers[0].get_port())));
+}
+
+#[test]
+fn transaction_is_retried_on_tryagain() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let node_attempts = attempts.clone();
+    let (servers, _commands) = start_cluster(1, move |_node, ports, args| {
+        match command_name(args).as_str() {
+            "CLUSTER" => Some(slots_reply(&[(0, 16383, ports[0])])),
+            "MULTI" => {
+                node_attempts.fetch_add(1, Ordering::SeqCst);
+                Some(b"+OK\r\n".to_vec())
+            },
+            "SET" if node_attempts.load(Ordering::SeqCst) < 3 => Some(b"-TRYAGAIN Multiple keys request during rehashing of slot\r\n".to_vec()),
+            "EXEC" if node_attempts.load(Ordering::SeqCst) < 3 => Some(b"-EXECABORT Transaction discarded because of previous errors.\r\n".to_vec()),
+            _ => transaction_node(args),
+        }
+    });
+    let mut client = cluster_client(&servers);
+
+    assert_eq!(run_transaction(&mut client).unwrap(), ("OK".to_string(), "value".to_string()));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn transaction_is_sent_again_once_after_a_connection_error() {
+    let multi_nb = Arc::new(AtomicUsize::new(0));
+    let node_multi_nb = multi_nb.clone();
+    let (servers, _commands) = start_cluster(1, move |_node, ports, args| {
+        match command_name(args).as_str() {
+            "CLUSTER" => Some(slots_reply(&[(0, 16383, ports[0])])),
+            // the first transaction loses its connection, the next ones succeed
+            "MULTI" if node_multi_nb.fetch_add(1, Ordering::SeqCst) == 0 => None,
+            _ => transaction_node(args),
+        }
+    });
+    let mut client = cluster_client(&servers);
+
+    assert_eq!(run_transaction(&mut client).unwrap(), ("OK".to_string(), "value".to_string()));
+    assert_eq!(multi_nb.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn transaction_fails_after_a_second_connection_error() {
+    let (servers, _commands) = start_cluster(1, |_node, ports, args| {
+        match command_name(args).as_str() {
+            "CLUSTER" => Some(slots_reply(&[(0, 16383, ports[0])])),
+            "MULTI" => None,
+            _ => transaction_node(args),
+        }
+    });
+    let mut client = cluster_client(&servers);
+
+    match run_transaction(&mut client) {
+        Err(RedisError::Io(_)) => {},
+        other => panic!("the transaction should fail, got {:?}", other),
+    };
+    assert!(wait_for(|| servers[0].get_closed_nb() == 2, Duration::new(5, 0)));
+}
@@ -4,6 +4,7 @@ extern crate redis_client;
 
 use redis_client::commands::CommandBuilder;
 use redis_client::commands::RedisCommand;
+use redis_client::ScanOptions;
 
 use std::collections::HashMap;
 
@@ -16,7 +17,7 @@ use std::collections::HashMap;
 /// let cmd = &mut RedisCommand::new();
 /// cmd.append("key", "value");
 ///
-/// check_result(cmd.into(), b"APPEND key value\r\n");
+/// check_result(cmd.into(), b"*3\r\n$6\r\nAPPEND\r\n$3\r\nkey\r\n$5\r\nvalue\r\n");
 /// ```
 fn check_result(result: &[u8], expected: &[u8]) {
     assert_eq!( result, expected);
@@ -27,7 +28,7 @@ fn append_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.append("key", "value");
 
-    check_result(cmd.into(), b"APPEND key value\r\n");
+    check_result(cmd.into(), b"*3\r\n$6\r\nAPPEND\r\n$3\r\nkey\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -35,7 +36,15 @@ fn auth_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.auth("password");
 
-    check_result(cmd.into(), b"AUTH password\r\n");
+    check_result(cmd.into(), b"*2\r\n$4\r\nAUTH\r\n$8\r\npassword\r\n");
+}
+
+#[test]
+fn auth_user_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.auth_user("user", "password");
+
+    check_result(cmd.into(), b"*3\r\n$4\r\nAUTH\r\n$4\r\nuser\r\n$8\r\npassword\r\n");
 }
 
 #[test]
@@ -43,7 +52,7 @@ fn bgrewriteaof_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.bgrewriteaof();
 
-    check_result(cmd.into(), b"BGREWRITEAOF\r\n");
+    check_result(cmd.into(), b"*1\r\n$12\r\nBGREWRITEAOF\r\n");
 }
 
 #[test]
@@ -51,7 +60,7 @@ fn bgsave_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.bgsave();
 
-    check_result(cmd.into(), b"BGSAVE\r\n");
+    check_result(cmd.into(), b"*1\r\n$6\r\nBGSAVE\r\n");
 }
 
 #[test]
@@ -59,7 +68,7 @@ fn bitcount_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.bitcount("key");
 
-    check_result(cmd.into(), b"BITCOUNT key\r\n");
+    check_result(cmd.into(), b"*2\r\n$8\r\nBITCOUNT\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -67,7 +76,7 @@ fn bitcount_range_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.bitcount_range("key", -1, 1);
 
-    check_result(cmd.into(), b"BITCOUNT key -1 1\r\n");
+    check_result(cmd.into(), b"*4\r\n$8\r\nBITCOUNT\r\n$3\r\nkey\r\n$2\r\n-1\r\n$1\r\n1\r\n");
 }
 
 #[test]
@@ -75,7 +84,7 @@ fn blpop_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.blpop("key", 10);
 
-    check_result(cmd.into(), b"BLPOP key 10\r\n");
+    check_result(cmd.into(), b"*3\r\n$5\r\nBLPOP\r\n$3\r\nkey\r\n$2\r\n10\r\n");
 }
 
 #[test]
@@ -83,7 +92,7 @@ fn mblpop_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.mblpop(vec!["key1", "key2"], 10);
 
-    check_result(cmd.into(), b"BLPOP key1 key2 10\r\n");
+    check_result(cmd.into(), b"*4\r\n$5\r\nBLPOP\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n$2\r\n10\r\n");
 }
 
 #[test]
@@ -91,7 +100,7 @@ fn brpop_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.brpop("key", 10);
 
-    check_result(cmd.into(), b"BRPOP key 10\r\n");
+    check_result(cmd.into(), b"*3\r\n$5\r\nBRPOP\r\n$3\r\nkey\r\n$2\r\n10\r\n");
 }
 
 #[test]
@@ -99,7 +108,7 @@ fn mbrpop_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.mbrpop(vec!["key1", "key2"], 10);
 
-    check_result(cmd.into(), b"BRPOP key1 key2 10\r\n");
+    check_result(cmd.into(), b"*4\r\n$5\r\nBRPOP\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n$2\r\n10\r\n");
 }
 
 #[test]
@@ -107,7 +116,23 @@ fn brpoplpush_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.brpoplpush("source", "dest", 10);
 
-    check_result(cmd.into(), b"BRPOPLPUSH source dest 10\r\n");
+    check_result(cmd.into(), b"*4\r\n$10\r\nBRPOPLPUSH\r\n$6\r\nsource\r\n$4\r\ndest\r\n$2\r\n10\r\n");
+}
+
+#[test]
+fn client_getname_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.client_getname();
+
+    check_result(cmd.into(), b"*2\r\n$6\r\nCLIENT\r\n$7\r\nGETNAME\r\n");
+}
+
+#[test]
+fn client_setname_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.client_setname("name");
+
+    check_result(cmd.into(), b"*3\r\n$6\r\nCLIENT\r\n$7\r\nSETNAME\r\n$4\r\nname\r\n");
 }
 
 #[test]
@@ -115,7 +140,7 @@ fn decr_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.decr("key");
 
-    check_result(cmd.into(), b"DECR key\r\n");
+    check_result(cmd.into(), b"*2\r\n$4\r\nDECR\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -123,7 +148,7 @@ fn decrby_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.decrby("key", 42);
 
-    check_result(cmd.into(), b"DECRBY key 42\r\n");
+    check_result(cmd.into(), b"*3\r\n$6\r\nDECRBY\r\n$3\r\nkey\r\n$2\r\n42\r\n");
 }
 
 #[test]
@@ -131,7 +156,7 @@ fn del_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.del("key");
 
-    check_result(cmd.into(), b"DEL key\r\n");
+    check_result(cmd.into(), b"*2\r\n$3\r\nDEL\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -139,7 +164,7 @@ fn mdel_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.mdel(vec!["key1", "key2"]);
 
-    check_result(cmd.into(), b"DEL key1 key2\r\n");
+    check_result(cmd.into(), b"*3\r\n$3\r\nDEL\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n");
 }
 
 #[test]
@@ -147,7 +172,7 @@ fn discard_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.discard();
 
-    check_result(cmd.into(), b"DISCARD\r\n");
+    check_result(cmd.into(), b"*1\r\n$7\r\nDISCARD\r\n");
 }
 
 #[test]
@@ -155,7 +180,25 @@ fn echo_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.echo("hello");
 
-    check_result(cmd.into(), b"ECHO hello\r\n");
+    check_result(cmd.into(), b"*2\r\n$4\r\nECHO\r\n$5\r\nhello\r\n");
+}
+
+#[test]
+fn eval_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.eval("return KEYS[1]", ["key1", "key2"], 10).eval_ro("return 1", (), ());
+
+    check_result(cmd.into(), b"*6\r\n$4\r\nEVAL\r\n$14\r\nreturn KEYS[1]\r\n$1\r\n2\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n$2\r\n10\r\n\
+                                 *3\r\n$7\r\nEVAL_RO\r\n$8\r\nreturn 1\r\n$1\r\n0\r\n");
+}
+
+#[test]
+fn evalsha_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.evalsha("e0e1f9fabfc9d4800c877a703b823ac0578ff8db", "key", ()).evalsha_ro("e0e1f9fabfc9d4800c877a703b823ac0578ff8db", (), ["a", "b"]);
+
+    check_result(cmd.into(), b"*4\r\n$7\r\nEVALSHA\r\n$40\r\ne0e1f9fabfc9d4800c877a703b823ac0578ff8db\r\n$1\r\n1\r\n$3\r\nkey\r\n\
+                                 *5\r\n$10\r\nEVALSHA_RO\r\n$40\r\ne0e1f9fabfc9d4800c877a703b823ac0578ff8db\r\n$1\r\n0\r\n$1\r\na\r\n$1\r\nb\r\n");
 }
 
 #[test]
@@ -163,7 +206,7 @@ fn exec_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.exec();
 
-    check_result(cmd.into(), b"EXEC\r\n");
+    check_result(cmd.into(), b"*1\r\n$4\r\nEXEC\r\n");
 }
 
 #[test]
@@ -171,7 +214,7 @@ fn exists_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.exists("key");
 
-    check_result(cmd.into(), b"EXISTS key\r\n");
+    check_result(cmd.into(), b"*2\r\n$6\r\nEXISTS\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -179,7 +222,7 @@ fn mexists_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.mexists(vec!["key1", "key2"]);
 
-    check_result(cmd.into(), b"EXISTS key1 key2\r\n");
+    check_result(cmd.into(), b"*3\r\n$6\r\nEXISTS\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n");
 }
 
 #[test]
@@ -187,7 +230,7 @@ fn expire_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.expire("key", 42);
 
-    check_result(cmd.into(), b"EXPIRE key 42\r\n");
+    check_result(cmd.into(), b"*3\r\n$6\r\nEXPIRE\r\n$3\r\nkey\r\n$2\r\n42\r\n");
 }
 
 #[test]
@@ -195,7 +238,36 @@ fn expireat_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.expireat("key", 42);
 
-    check_result(cmd.into(), b"EXPIREAT key 42\r\n");
+    check_result(cmd.into(), b"*3\r\n$8\r\nEXPIREAT\r\n$3\r\nkey\r\n$2\r\n42\r\n");
+}
+
+#[test]
+fn fcall_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.fcall("add", ["key1", "key2"], 5).fcall_ro("read", (), ());
+
+    check_result(cmd.into(), b"*6\r\n$5\r\nFCALL\r\n$3\r\nadd\r\n$1\r\n2\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n$1\r\n5\r\n\
+                                 *3\r\n$8\r\nFCALL_RO\r\n$4\r\nread\r\n$1\r\n0\r\n");
+}
+
+#[test]
+fn function_cmds_work() {
+    let cmd = &mut RedisCommand::new();
+    cmd.function_load("code").function_load_replace("code").function_delete("lib").function_list().function_list_library("li*");
+
+    check_result(cmd.into(), b"*3\r\n$8\r\nFUNCTION\r\n$4\r\nLOAD\r\n$4\r\ncode\r\n\
+                                 *4\r\n$8\r\nFUNCTION\r\n$4\r\nLOAD\r\n$7\r\nREPLACE\r\n$4\r\ncode\r\n\
+                                 *3\r\n$8\r\nFUNCTION\r\n$6\r\nDELETE\r\n$3\r\nlib\r\n\
+                                 *2\r\n$8\r\nFUNCTION\r\n$4\r\nLIST\r\n\
+                                 *4\r\n$8\r\nFUNCTION\r\n$4\r\nLIST\r\n$11\r\nLIBRARYNAME\r\n$3\r\nli*\r\n");
+
+    let cmd = &mut RedisCommand::new();
+    cmd.function_dump().function_restore(&b"\x05"[..]).function_restore_with_policy(&b"\x05"[..], "REPLACE").function_flush();
+
+    check_result(cmd.into(), b"*2\r\n$8\r\nFUNCTION\r\n$4\r\nDUMP\r\n\
+                                 *3\r\n$8\r\nFUNCTION\r\n$7\r\nRESTORE\r\n$1\r\n\x05\r\n\
+                                 *4\r\n$8\r\nFUNCTION\r\n$7\r\nRESTORE\r\n$1\r\n\x05\r\n$7\r\nREPLACE\r\n\
+                                 *2\r\n$8\r\nFUNCTION\r\n$5\r\nFLUSH\r\n");
 }
 
 #[test]
@@ -203,7 +275,7 @@ fn get_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.get("key");
 
-    check_result(cmd.into(), b"GET key\r\n");
+    check_result(cmd.into(), b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -211,7 +283,7 @@ fn getrange_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.getrange("key", -1, 1);
 
-    check_result(cmd.into(), b"GETRANGE key -1 1\r\n");
+    check_result(cmd.into(), b"*4\r\n$8\r\nGETRANGE\r\n$3\r\nkey\r\n$2\r\n-1\r\n$1\r\n1\r\n");
 }
 
 #[test]
@@ -219,7 +291,7 @@ fn hdel_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hdel("key", "field");
 
-    check_result(cmd.into(), b"HDEL key field\r\n");
+    check_result(cmd.into(), b"*3\r\n$4\r\nHDEL\r\n$3\r\nkey\r\n$5\r\nfield\r\n");
 }
 
 #[test]
@@ -227,7 +299,23 @@ fn mhdel_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hmdel("key", vec!["field1", "field2"]);
 
-    check_result(cmd.into(), b"HDEL key field1 field2\r\n");
+    check_result(cmd.into(), b"*4\r\n$4\r\nHDEL\r\n$3\r\nkey\r\n$6\r\nfield1\r\n$6\r\nfield2\r\n");
+}
+
+#[test]
+fn hello_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.hello(3);
+
+    check_result(cmd.into(), b"*2\r\n$5\r\nHELLO\r\n$1\r\n3\r\n");
+}
+
+#[test]
+fn hello_auth_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.hello_auth(3, "user", "password");
+
+    check_result(cmd.into(), b"*5\r\n$5\r\nHELLO\r\n$1\r\n3\r\n$4\r\nAUTH\r\n$4\r\nuser\r\n$8\r\npassword\r\n");
 }
 
 #[test]
@@ -235,7 +323,7 @@ fn hexists_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hexists("key", "field");
 
-    check_result(cmd.into(), b"HEXISTS key field\r\n");
+    check_result(cmd.into(), b"*3\r\n$7\r\nHEXISTS\r\n$3\r\nkey\r\n$5\r\nfield\r\n");
 }
 
 #[test]
@@ -243,7 +331,7 @@ fn hget_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hget("key", "field");
 
-    check_result(cmd.into(), b"HGET key field\r\n");
+    check_result(cmd.into(), b"*3\r\n$4\r\nHGET\r\n$3\r\nkey\r\n$5\r\nfield\r\n");
 }
 
 #[test]
@@ -251,7 +339,7 @@ fn hgetall_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hgetall("key");
 
-    check_result(cmd.into(), b"HGETALL key\r\n");
+    check_result(cmd.into(), b"*2\r\n$7\r\nHGETALL\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -259,7 +347,7 @@ fn hincrby_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hincrby("key", "field", 1);
 
-    check_result(cmd.into(), b"HINCRBY key field 1\r\n");
+    check_result(cmd.into(), b"*4\r\n$7\r\nHINCRBY\r\n$3\r\nkey\r\n$5\r\nfield\r\n$1\r\n1\r\n");
 }
 
 #[test]
@@ -267,7 +355,7 @@ fn hincrbyfloat_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hincrbyfloat("key", "value", 4.2);
 
-    check_result(cmd.into(), b"HINCRBYBYFLOAT key value 4.2\r\n");
+    check_result(cmd.into(), b"*4\r\n$14\r\nHINCRBYBYFLOAT\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$3\r\n4.2\r\n");
 }
 
 #[test]
@@ -275,7 +363,7 @@ fn hkeys_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hkeys("key");
 
-    check_result(cmd.into(), b"HKEYS key\r\n");
+    check_result(cmd.into(), b"*2\r\n$5\r\nHKEYS\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -283,7 +371,7 @@ fn hlen_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hlen("key");
 
-    check_result(cmd.into(), b"HLEN key\r\n");
+    check_result(cmd.into(), b"*2\r\n$4\r\nHLEN\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -291,7 +379,7 @@ fn hmget_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hmget("key", vec!["field1", "field2"]);
 
-    check_result(cmd.into(), b"HMGET key field1 field2\r\n");
+    check_result(cmd.into(), b"*4\r\n$5\r\nHMGET\r\n$3\r\nkey\r\n$6\r\nfield1\r\n$6\r\nfield2\r\n");
 }
 
 #[test]
@@ -303,7 +391,17 @@ fn hmset_cmd_works() {
     //fields.insert("field2".to_string(), "value2");
     cmd.hmset("key", fields);
 
-    check_result(cmd.into(), b"HMSET key field1 value1\r\n");
+    check_result(cmd.into(), b"*4\r\n$5\r\nHMSET\r\n$3\r\nkey\r\n$6\r\nfield1\r\n$6\r\nvalue1\r\n");
+}
+
+#[test]
+fn hmset_binary_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    let mut fields = HashMap::new();
+    fields.insert(&b"f\xff"[..], vec![0u8, 13, 10]);
+    cmd.hmset(42, fields);
+
+    check_result(cmd.into(), b"*4\r\n$5\r\nHMSET\r\n$2\r\n42\r\n$2\r\nf\xff\r\n$3\r\n\x00\r\n\r\n");
 }
 
 #[test]
@@ -311,7 +409,7 @@ fn hset_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hset("key", "field", "value");
 
-    check_result(cmd.into(), b"HSET key field value\r\n");
+    check_result(cmd.into(), b"*4\r\n$4\r\nHSET\r\n$3\r\nkey\r\n$5\r\nfield\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -319,7 +417,7 @@ fn hstrlen_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hstrlen("key", "field");
 
-    check_result(cmd.into(), b"HSTRLEN key field\r\n");
+    check_result(cmd.into(), b"*3\r\n$7\r\nHSTRLEN\r\n$3\r\nkey\r\n$5\r\nfield\r\n");
 }
 
 #[test]
@@ -327,7 +425,7 @@ fn hsetnx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hsetnx("key", "field", "value");
 
-    check_result(cmd.into(), b"HSETNX key field value\r\n");
+    check_result(cmd.into(), b"*4\r\n$6\r\nHSETNX\r\n$3\r\nkey\r\n$5\r\nfield\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -335,7 +433,7 @@ fn hvals_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.hvals("key");
 
-    check_result(cmd.into(), b"HVALS key\r\n");
+    check_result(cmd.into(), b"*2\r\n$5\r\nHVALS\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -343,7 +441,7 @@ fn lindex_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.lindex("key", 2);
 
-    check_result(cmd.into(), b"LINDEX key 2\r\n");
+    check_result(cmd.into(), b"*3\r\n$6\r\nLINDEX\r\n$3\r\nkey\r\n$1\r\n2\r\n");
 }
 
 #[test]
@@ -351,7 +449,7 @@ fn linsert_after_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.linsert_after("key", "pivot", "value");
 
-    check_result(cmd.into(), b"LINSERT key AFTER pivot value\r\n");
+    check_result(cmd.into(), b"*5\r\n$7\r\nLINSERT\r\n$3\r\nkey\r\n$5\r\nAFTER\r\n$5\r\npivot\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -359,7 +457,7 @@ fn linsert_before_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.linsert_before("key", "pivot", "value");
 
-    check_result(cmd.into(), b"LINSERT key BEFORE pivot value\r\n");
+    check_result(cmd.into(), b"*5\r\n$7\r\nLINSERT\r\n$3\r\nkey\r\n$6\r\nBEFORE\r\n$5\r\npivot\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -367,7 +465,7 @@ fn llen_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.llen("key");
 
-    check_result(cmd.into(), b"LLEN key\r\n");
+    check_result(cmd.into(), b"*2\r\n$4\r\nLLEN\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -375,7 +473,7 @@ fn lpop_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.lpop("key");
 
-    check_result(cmd.into(), b"LPOP key\r\n");
+    check_result(cmd.into(), b"*2\r\n$4\r\nLPOP\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -383,7 +481,7 @@ fn lpush_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.lpush("key", "value");
 
-    check_result(cmd.into(), b"LPUSH key value\r\n");
+    check_result(cmd.into(), b"*3\r\n$5\r\nLPUSH\r\n$3\r\nkey\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -391,7 +489,7 @@ fn mlpush_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.mlpush("key", vec!["value1", "value2"]);
 
-    check_result(cmd.into(), b"LPUSH key value1 value2\r\n");
+    check_result(cmd.into(), b"*4\r\n$5\r\nLPUSH\r\n$3\r\nkey\r\n$6\r\nvalue1\r\n$6\r\nvalue2\r\n");
 }
 
 #[test]
@@ -399,7 +497,7 @@ fn lpushx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.lpushx("key", "value");
 
-    check_result(cmd.into(), b"LPUSHX key value\r\n");
+    check_result(cmd.into(), b"*3\r\n$6\r\nLPUSHX\r\n$3\r\nkey\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -407,7 +505,7 @@ fn lrange_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.lrange("key", 1, -1);
 
-    check_result(cmd.into(), b"LRANGE key 1 -1\r\n");
+    check_result(cmd.into(), b"*4\r\n$6\r\nLRANGE\r\n$3\r\nkey\r\n$1\r\n1\r\n$2\r\n-1\r\n");
 }
 
 #[test]
@@ -415,7 +513,7 @@ fn lrem_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.lrem("key", 1, "value");
 
-    check_result(cmd.into(), b"LREM key 1 value\r\n");
+    check_result(cmd.into(), b"*4\r\n$4\r\nLREM\r\n$3\r\nkey\r\n$1\r\n1\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -423,7 +521,7 @@ fn lset_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.lset("key", 1, "value");
 
-    check_result(cmd.into(), b"LSET key 1 value\r\n");
+    check_result(cmd.into(), b"*4\r\n$4\r\nLSET\r\n$3\r\nkey\r\n$1\r\n1\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -431,7 +529,7 @@ fn ltrim_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.ltrim("key", 1, -1);
 
-    check_result(cmd.into(), b"LTRIM key 1 -1\r\n");
+    check_result(cmd.into(), b"*4\r\n$5\r\nLTRIM\r\n$3\r\nkey\r\n$1\r\n1\r\n$2\r\n-1\r\n");
 }
 
 #[test]
@@ -439,7 +537,39 @@ fn multi_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.multi();
 
-    check_result(cmd.into(), b"MULTI\r\n");
+    check_result(cmd.into(), b"*1\r\n$5\r\nMULTI\r\n");
+}
+
+#[test]
+fn ping_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.ping();
+
+    check_result(cmd.into(), b"*1\r\n$4\r\nPING\r\n");
+}
+
+#[test]
+fn pubsub_channels_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.pubsub_channels(Some("news.*")).pubsub_channels(None::<&str>);
+
+    check_result(cmd.into(), b"*3\r\n$6\r\nPUBSUB\r\n$8\r\nCHANNELS\r\n$6\r\nnews.*\r\n*2\r\n$6\r\nPUBSUB\r\n$8\r\nCHANNELS\r\n");
+}
+
+#[test]
+fn pubsub_numpat_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.pubsub_numpat();
+
+    check_result(cmd.into(), b"*2\r\n$6\r\nPUBSUB\r\n$6\r\nNUMPAT\r\n");
+}
+
+#[test]
+fn pubsub_numsub_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.pubsub_numsub(["a", "b"]);
+
+    check_result(cmd.into(), b"*4\r\n$6\r\nPUBSUB\r\n$6\r\nNUMSUB\r\n$1\r\na\r\n$1\r\nb\r\n");
 }
 
 #[test]
@@ -447,7 +577,7 @@ fn rename_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.rename("key", "new_key");
 
-    check_result(cmd.into(), b"RENAME key new_key\r\n");
+    check_result(cmd.into(), b"*3\r\n$6\r\nRENAME\r\n$3\r\nkey\r\n$7\r\nnew_key\r\n");
 }
 
 #[test]
@@ -455,7 +585,7 @@ fn renamenx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.renamenx("key", "new_key");
 
-    check_result(cmd.into(), b"RENAMENX key new_key\r\n");
+    check_result(cmd.into(), b"*3\r\n$8\r\nRENAMENX\r\n$3\r\nkey\r\n$7\r\nnew_key\r\n");
 }
 
 #[test]
@@ -463,7 +593,7 @@ fn rpop_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.rpop("key");
 
-    check_result(cmd.into(), b"RPOP key\r\n");
+    check_result(cmd.into(), b"*2\r\n$4\r\nRPOP\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -471,7 +601,7 @@ fn rpoplpush_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.rpoplpush("key", "value");
 
-    check_result(cmd.into(), b"RPOPLPUSH key value\r\n");
+    check_result(cmd.into(), b"*3\r\n$9\r\nRPOPLPUSH\r\n$3\r\nkey\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -479,7 +609,7 @@ fn rpush_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.rpush("key", "value");
 
-    check_result(cmd.into(), b"RPUSH key value\r\n");
+    check_result(cmd.into(), b"*3\r\n$5\r\nRPUSH\r\n$3\r\nkey\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -487,7 +617,7 @@ fn mrpush_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.mrpush("key", vec!["value1", "value2"]);
 
-    check_result(cmd.into(), b"RPUSH key value1 value2\r\n");
+    check_result(cmd.into(), b"*4\r\n$5\r\nRPUSH\r\n$3\r\nkey\r\n$6\r\nvalue1\r\n$6\r\nvalue2\r\n");
 }
 
 #[test]
@@ -495,7 +625,7 @@ fn rpushx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.rpushx("key", "value");
 
-    check_result(cmd.into(), b"RPUSHX key value\r\n");
+    check_result(cmd.into(), b"*3\r\n$6\r\nRPUSHX\r\n$3\r\nkey\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -503,7 +633,7 @@ fn sadd_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.sadd("key", "member");
 
-    check_result(cmd.into(), b"SADD key member\r\n");
+    check_result(cmd.into(), b"*3\r\n$4\r\nSADD\r\n$3\r\nkey\r\n$6\r\nmember\r\n");
 }
 
 #[test]
@@ -511,15 +641,35 @@ fn msadd_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.msadd("key", vec!["member1", "member2"]);
 
-    check_result(cmd.into(), b"SADD key member1 member2\r\n");
+    check_result(cmd.into(), b"*4\r\n$4\r\nSADD\r\n$3\r\nkey\r\n$7\r\nmember1\r\n$7\r\nmember2\r\n");
 }
 
 #[test]
 fn sadd_binary_cmd_works() {
     let cmd = &mut RedisCommand::new();
-    cmd.sadd_binary("key", b"member");
+    cmd.sadd("key", b"member");
 
-    check_result(cmd.into(), b"SADD key member\r\n");
+    check_result(cmd.into(), b"*3\r\n$4\r\nSADD\r\n$3\r\nkey\r\n$6\r\nmember\r\n");
+}
+
+#[test]
+fn scan_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.scan(0, &ScanOptions::new()).scan(17, ScanOptions::new().set_pattern("user:*").set_count(100).set_type("hash"));
+
+    check_result(cmd.into(), b"*2\r\n$4\r\nSCAN\r\n$1\r\n0\r\n\
+                                 *8\r\n$4\r\nSCAN\r\n$2\r\n17\r\n$5\r\nMATCH\r\n$6\r\nuser:*\r\n$5\r\nCOUNT\r\n$3\r\n100\r\n$4\r\nTYPE\r\n$4\r\nhash\r\n");
+}
+
+#[test]
+fn collection_scan_cmds_work() {
+    let cmd = &mut RedisCommand::new();
+    let options = ScanOptions::new().set_pattern("f*").set_count(5).set_type("ignored").clone();
+    cmd.sscan("key", 0, &options).hscan("key", 3, &ScanOptions::new()).zscan("key", 4, ScanOptions::new().set_count(5));
+
+    check_result(cmd.into(), b"*7\r\n$5\r\nSSCAN\r\n$3\r\nkey\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$2\r\nf*\r\n$5\r\nCOUNT\r\n$1\r\n5\r\n\
+                                 *3\r\n$5\r\nHSCAN\r\n$3\r\nkey\r\n$1\r\n3\r\n\
+                                 *5\r\n$5\r\nZSCAN\r\n$3\r\nkey\r\n$1\r\n4\r\n$5\r\nCOUNT\r\n$1\r\n5\r\n");
 }
 
 #[test]
@@ -527,7 +677,17 @@ fn scard_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.scard("key");
 
-    check_result(cmd.into(), b"SCARD key\r\n");
+    check_result(cmd.into(), b"*2\r\n$5\r\nSCARD\r\n$3\r\nkey\r\n");
+}
+
+#[test]
+fn script_cmds_work() {
+    let cmd = &mut RedisCommand::new();
+    cmd.script_load("return 1").script_exists(["e0e1f9fabfc9d4800c877a703b823ac0578ff8db"]).script_flush();
+
+    check_result(cmd.into(), b"*3\r\n$6\r\nSCRIPT\r\n$4\r\nLOAD\r\n$8\r\nreturn 1\r\n\
+                                 *3\r\n$6\r\nSCRIPT\r\n$6\r\nEXISTS\r\n$40\r\ne0e1f9fabfc9d4800c877a703b823ac0578ff8db\r\n\
+                                 *2\r\n$6\r\nSCRIPT\r\n$5\r\nFLUSH\r\n");
 }
 
 #[test]
@@ -535,7 +695,7 @@ fn select_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.select(1);
 
-    check_result(cmd.into(), b"SELECT 1\r\n");
+    check_result(cmd.into(), b"*2\r\n$6\r\nSELECT\r\n$1\r\n1\r\n");
 }
 
 #[test]
@@ -543,15 +703,15 @@ fn set_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.set("key", "value");
 
-    check_result(cmd.into(), b"SET key value\r\n");
+    check_result(cmd.into(), b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
 fn set_binary_cmd_works() {
     let cmd = &mut RedisCommand::new();
-    cmd.set_binary("key", b"value");
+    cmd.set("key", b"value");
 
-    check_result(cmd.into(), b"SET key value\r\n");
+    check_result(cmd.into(), b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -559,7 +719,7 @@ fn setex_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.setex("key", "value", 42);
 
-    check_result(cmd.into(), b"SET key value EX 42\r\n");
+    check_result(cmd.into(), b"*5\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$2\r\nEX\r\n$2\r\n42\r\n");
 }
 
 #[test]
@@ -567,7 +727,7 @@ fn psetex_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.psetex("key", "value", 42);
 
-    check_result(cmd.into(), b"SET key value PX 42\r\n");
+    check_result(cmd.into(), b"*5\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$2\r\nPX\r\n$2\r\n42\r\n");
 }
 
 #[test]
@@ -575,7 +735,7 @@ fn setnx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.setnx("key", "value");
 
-    check_result(cmd.into(), b"SET key value NX\r\n");
+    check_result(cmd.into(), b"*4\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$2\r\nNX\r\n");
 }
 
 #[test]
@@ -583,7 +743,7 @@ fn setxx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.setxx("key", "value");
 
-    check_result(cmd.into(), b"SET key value XX\r\n");
+    check_result(cmd.into(), b"*4\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$2\r\nXX\r\n");
 }
 
 #[test]
@@ -591,7 +751,7 @@ fn setex_nx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.setex_nx("key", "value", 42);
 
-    check_result(cmd.into(), b"SET key value EX 42 NX\r\n");
+    check_result(cmd.into(), b"*6\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$2\r\nEX\r\n$2\r\n42\r\n$2\r\nNX\r\n");
 }
 
 #[test]
@@ -599,7 +759,7 @@ fn setex_xx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.setex_xx("key", "value", 42);
 
-    check_result(cmd.into(), b"SET key value EX 42 XX\r\n");
+    check_result(cmd.into(), b"*6\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$2\r\nEX\r\n$2\r\n42\r\n$2\r\nXX\r\n");
 }
 
 #[test]
@@ -607,7 +767,7 @@ fn psetex_nx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.psetex_nx("key", "value", 42);
 
-    check_result(cmd.into(), b"SET key value PX 42 NX\r\n");
+    check_result(cmd.into(), b"*6\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$2\r\nPX\r\n$2\r\n42\r\n$2\r\nNX\r\n");
 }
 
 #[test]
@@ -615,7 +775,7 @@ fn psetex_xx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.psetex_xx("key", "value", 42);
 
-    check_result(cmd.into(), b"SET key value PX 42 XX\r\n");
+    check_result(cmd.into(), b"*6\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n$2\r\nPX\r\n$2\r\n42\r\n$2\r\nXX\r\n");
 }
 
 #[test]
@@ -623,7 +783,7 @@ fn setbit_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.setbit("key", 42, 0);
 
-    check_result(cmd.into(), b"SETBIT key 42 0\r\n");
+    check_result(cmd.into(), b"*4\r\n$6\r\nSETBIT\r\n$3\r\nkey\r\n$2\r\n42\r\n$1\r\n0\r\n");
 }
 
 #[test]
@@ -631,7 +791,7 @@ fn setrange_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.setrange("key", 42, "value");
 
-    check_result(cmd.into(), b"SETRANGE key 42 value\r\n");
+    check_result(cmd.into(), b"*4\r\n$8\r\nSETRANGE\r\n$3\r\nkey\r\n$2\r\n42\r\n$5\r\nvalue\r\n");
 }
 
 #[test]
@@ -639,7 +799,7 @@ fn sismember_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.sismember("key", "member");
 
-    check_result(cmd.into(), b"SISMEMBER key member\r\n");
+    check_result(cmd.into(), b"*3\r\n$9\r\nSISMEMBER\r\n$3\r\nkey\r\n$6\r\nmember\r\n");
 }
 
 #[test]
@@ -647,7 +807,7 @@ fn smembers_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.smembers("key");
 
-    check_result(cmd.into(), b"SMEMBERS key\r\n");
+    check_result(cmd.into(), b"*2\r\n$8\r\nSMEMBERS\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -655,7 +815,7 @@ fn spop_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.spop("key");
 
-    check_result(cmd.into(), b"SPOP key\r\n");
+    check_result(cmd.into(), b"*2\r\n$4\r\nSPOP\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -663,7 +823,7 @@ fn spop_count_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.spop_count("key", 2);
 
-    check_result(cmd.into(), b"SPOP key 2\r\n");
+    check_result(cmd.into(), b"*3\r\n$4\r\nSPOP\r\n$3\r\nkey\r\n$1\r\n2\r\n");
 }
 
 #[test]
@@ -671,7 +831,7 @@ fn srem_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.srem("key", "member");
 
-    check_result(cmd.into(), b"SREM key member\r\n");
+    check_result(cmd.into(), b"*3\r\n$4\r\nSREM\r\n$3\r\nkey\r\n$6\r\nmember\r\n");
 }
 
 #[test]
@@ -679,7 +839,7 @@ fn msrem_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.msrem("key", vec!["member1", "member2"]);
 
-    check_result(cmd.into(), b"SREM key member1 member2\r\n");
+    check_result(cmd.into(), b"*4\r\n$4\r\nSREM\r\n$3\r\nkey\r\n$7\r\nmember1\r\n$7\r\nmember2\r\n");
 }
 
 #[test]
@@ -687,7 +847,7 @@ fn strlen_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.strlen("key");
 
-    check_result(cmd.into(), b"STRLEN key\r\n");
+    check_result(cmd.into(), b"*2\r\n$6\r\nSTRLEN\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -695,7 +855,7 @@ fn ttl_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.ttl("key");
 
-    check_result(cmd.into(), b"TTL key\r\n");
+    check_result(cmd.into(), b"*2\r\n$3\r\nTTL\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -703,7 +863,7 @@ fn unwatch_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.unwatch();
 
-    check_result(cmd.into(), b"UNWATCH\r\n");
+    check_result(cmd.into(), b"*1\r\n$7\r\nUNWATCH\r\n");
 }
 
 #[test]
@@ -711,7 +871,7 @@ fn watch_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.watch("key");
 
-    check_result(cmd.into(), b"WATCH key\r\n");
+    check_result(cmd.into(), b"*2\r\n$5\r\nWATCH\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -719,7 +879,7 @@ fn mwatch_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.mwatch(vec!["key1", "key2"]);
 
-    check_result(cmd.into(), b"WATCH key1 key2\r\n");
+    check_result(cmd.into(), b"*3\r\n$5\r\nWATCH\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n");
 }
 
 #[test]
@@ -727,15 +887,15 @@ fn zadd_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zadd("key", 4.2, "member");
 
-    check_result(cmd.into(), b"ZADD key 4.2 member\r\n");
+    check_result(cmd.into(), b"*4\r\n$4\r\nZADD\r\n$3\r\nkey\r\n$3\r\n4.2\r\n$6\r\nmember\r\n");
 }
 
 #[test]
 fn zadd_binary_cmd_works() {
     let cmd = &mut RedisCommand::new();
-    cmd.zadd_binary("key", 4.2, b"member");
+    cmd.zadd("key", 4.2, b"member");
 
-    check_result(cmd.into(), b"ZADD key 4.2 member\r\n");
+    check_result(cmd.into(), b"*4\r\n$4\r\nZADD\r\n$3\r\nkey\r\n$3\r\n4.2\r\n$6\r\nmember\r\n");
 }
 
 #[test]
@@ -743,7 +903,7 @@ fn zaddnx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zaddnx("key", 4.2, "member");
 
-    check_result(cmd.into(), b"ZADD key NX 4.2 member\r\n");
+    check_result(cmd.into(), b"*5\r\n$4\r\nZADD\r\n$3\r\nkey\r\n$2\r\nNX\r\n$3\r\n4.2\r\n$6\r\nmember\r\n");
 }
 
 #[test]
@@ -751,7 +911,7 @@ fn zaddxx_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zaddxx("key", 4.2, "member");
 
-    check_result(cmd.into(), b"ZADD key XX 4.2 member\r\n");
+    check_result(cmd.into(), b"*5\r\n$4\r\nZADD\r\n$3\r\nkey\r\n$2\r\nXX\r\n$3\r\n4.2\r\n$6\r\nmember\r\n");
 }
 
 #[test]
@@ -759,7 +919,7 @@ fn zaddnx_ch_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zaddnx_ch("key", 4.2, "member");
 
-    check_result(cmd.into(), b"ZADD key NX CH 4.2 member\r\n");
+    check_result(cmd.into(), b"*6\r\n$4\r\nZADD\r\n$3\r\nkey\r\n$2\r\nNX\r\n$2\r\nCH\r\n$3\r\n4.2\r\n$6\r\nmember\r\n");
 }
 
 #[test]
@@ -767,7 +927,7 @@ fn zaddxx_ch_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zaddxx_ch("key", 4.2, "member");
 
-    check_result(cmd.into(), b"ZADD key XX CH 4.2 member\r\n");
+    check_result(cmd.into(), b"*6\r\n$4\r\nZADD\r\n$3\r\nkey\r\n$2\r\nXX\r\n$2\r\nCH\r\n$3\r\n4.2\r\n$6\r\nmember\r\n");
 }
 
 #[test]
@@ -775,7 +935,7 @@ fn zcard_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zcard("key");
 
-    check_result(cmd.into(), b"ZCARD key\r\n");
+    check_result(cmd.into(), b"*2\r\n$5\r\nZCARD\r\n$3\r\nkey\r\n");
 }
 
 #[test]
@@ -783,7 +943,7 @@ fn zcount_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zcount("key", "-inf", 3);
 
-    check_result(cmd.into(), b"ZCOUNT key -inf 3\r\n");
+    check_result(cmd.into(), b"*4\r\n$6\r\nZCOUNT\r\n$3\r\nkey\r\n$4\r\n-inf\r\n$1\r\n3\r\n");
 }
 
 #[test]
@@ -791,7 +951,7 @@ fn zincrby_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zincrby("key", 4.2, "member");
 
-    check_result(cmd.into(), b"ZINCRBY key 4.2 member\r\n");
+    check_result(cmd.into(), b"*4\r\n$7\r\nZINCRBY\r\n$3\r\nkey\r\n$3\r\n4.2\r\n$6\r\nmember\r\n");
 }
 
 #[test]
@@ -799,7 +959,7 @@ fn zlexcount_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zlexcount("key", "-", "[b");
 
-    check_result(cmd.into(), b"ZLEXCOUNT key - [b\r\n");
+    check_result(cmd.into(), b"*4\r\n$9\r\nZLEXCOUNT\r\n$3\r\nkey\r\n$1\r\n-\r\n$2\r\n[b\r\n");
 }
 
 #[test]
@@ -807,7 +967,7 @@ fn zrem_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zrem("key", "member");
 
-    check_result(cmd.into(), b"ZREM key member\r\n");
+    check_result(cmd.into(), b"*3\r\n$4\r\nZREM\r\n$3\r\nkey\r\n$6\r\nmember\r\n");
 }
 
 #[test]
@@ -815,7 +975,7 @@ fn mzrem_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.mzrem("key", vec!["member1", "member2"]);
 
-    check_result(cmd.into(), b"ZREM key member1 member2\r\n");
+    check_result(cmd.into(), b"*4\r\n$4\r\nZREM\r\n$3\r\nkey\r\n$7\r\nmember1\r\n$7\r\nmember2\r\n");
 }
 
 #[test]
@@ -823,7 +983,7 @@ fn zrange_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zrange("key", -1, 1);
 
-    check_result(cmd.into(), b"ZRANGE key -1 1\r\n");
+    check_result(cmd.into(), b"*4\r\n$6\r\nZRANGE\r\n$3\r\nkey\r\n$2\r\n-1\r\n$1\r\n1\r\n");
 }
 
 #[test]
@@ -831,7 +991,7 @@ fn zrange_with_scores_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zrange_with_scores("key", -1, 1);
 
-    check_result(cmd.into(), b"ZRANGE key -1 1 WITHSCORES\r\n");
+    check_result(cmd.into(), b"*5\r\n$6\r\nZRANGE\r\n$3\r\nkey\r\n$2\r\n-1\r\n$1\r\n1\r\n$10\r\nWITHSCORES\r\n");
 }
 
 #[test]
@@ -839,7 +999,7 @@ fn zrevrange_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zrevrange("key", -1, 1);
 
-    check_result(cmd.into(), b"ZREVRANGE key -1 1\r\n");
+    check_result(cmd.into(), b"*4\r\n$9\r\nZREVRANGE\r\n$3\r\nkey\r\n$2\r\n-1\r\n$1\r\n1\r\n");
 }
 
 #[test]
@@ -847,5 +1007,61 @@ fn zrevrange_with_scores_cmd_works() {
     let cmd = &mut RedisCommand::new();
     cmd.zrevrange_with_scores("key", -1, 1);
 
-    check_result(cmd.into(), b"ZREVRANGE key -1 1 WITHSCORES\r\n");
-}
\ No newline at end of file
+    check_result(cmd.into(), b"*5\r\n$9\r\nZREVRANGE\r\n$3\r\nkey\r\n$2\r\n-1\r\n$1\r\n1\r\n$10\r\nWITHSCORES\r\n");
+}
+#[test]
+fn arg_with_whitespaces_and_crlf_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.set("key", "{\"a\": \"b c\"}\r\n");
+
+    check_result(cmd.into(), b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$14\r\n{\"a\": \"b c\"}\r\n\r\n");
+}
+
+#[test]
+fn binary_arg_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.set("key", vec![0u8, 32, 13, 10, 255]);
+
+    check_result(cmd.into(), b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\n\x00 \r\n\xff\r\n");
+}
+
+#[test]
+fn empty_arg_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.set("key", "");
+
+    check_result(cmd.into(), b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$0\r\n\r\n");
+}
+
+#[test]
+fn multi_words_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.add_cmd("CLIENT SETNAME").add_arg("name").end();
+
+    check_result(cmd.into(), b"*3\r\n$6\r\nCLIENT\r\n$7\r\nSETNAME\r\n$4\r\nname\r\n");
+}
+
+#[test]
+fn pipeline_cmd_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.set("key", "value").get("key");
+
+    assert_eq!(cmd.get_command_nb(), 2);
+    check_result(cmd.into(), b"*3\r\n$3\r\nSET\r\n$3\r\nkey\r\n$5\r\nvalue\r\n*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n");
+}
+
+#[test]
+fn binary_key_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.get(&[0xffu8, 0x00][..]);
+
+    check_result(cmd.into(), b"*2\r\n$3\r\nGET\r\n$2\r\n\xff\x00\r\n");
+}
+
+#[test]
+fn binary_multi_values_works() {
+    let cmd = &mut RedisCommand::new();
+    cmd.mrpush("key", vec![vec![0u8], vec![1u8, 2]]);
+
+    check_result(cmd.into(), b"*4\r\n$5\r\nRPUSH\r\n$3\r\nkey\r\n$1\r\n\x00\r\n$2\r\n\x01\x02\r\n");
+}
@@ -0,0 +1,93 @@
+//! The tests `function` mod is checking how the function libraries are loaded and their functions called.
+
+extern crate redis_client;
+
+use redis_client::commands::CommandSender;
+use redis_client::errors::ParsingError;
+use redis_client::{ClusterClient, FunctionLibrary, PoolConfig, RedisClient, RedisError, RedisPool};
+
+use std::sync::{Arc, Mutex};
+
+use support::{command_name, FakeServer};
+
+const LIBRARY: &str = "#!lua name=counters\nredis.register_function('add', function(keys, args) return 1 end)";
+
+/// Start a server recording the commands it receives, with the name of the node for FCALL and the subcommand for FUNCTION
+fn start_function_server(node: usize, commands: Arc<Mutex<Vec<(usize, String)>>>, slots_reply: Arc<Mutex<Vec<u8>>>) -> FakeServer {
+    FakeServer::start(move |args| {
+        let name = command_name(args);
+        let subcommand = args.get(1).map(|arg| String::from_utf8_lossy(arg).to_uppercase()).unwrap_or_default();
+        match name.as_str() {
+            "FUNCTION" => commands.lock().unwrap().push((node, format!("FUNCTION {}", subcommand))),
+            _ => commands.lock().unwrap().push((node, name.clone())),
+        };
+        match (name.as_str(), subcommand.as_str()) {
+            ("FUNCTION", "LOAD") => Some(b"$8\r\ncounters\r\n".to_vec()),
+            ("FCALL", _) => Some(format!(":{}\r\n", node).into_bytes()),
+            ("CLUSTER", _) => Some(slots_reply.lock().unwrap().clone()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    })
+}
+
+#[test]
+fn library_name_is_read_from_the_first_line() {
+    let library = FunctionLibrary::new(LIBRARY).unwrap();
+    assert_eq!(library.get_name(), "counters");
+    assert_eq!(library.get_code(), LIBRARY);
+
+    for code in &["redis.register_function('add', function(keys, args) return 1 end)", "#!lua\nreturn 1"] {
+        match FunctionLibrary::new(code) {
+            Err(RedisError::Parse(ParsingError::InvalidValue(_))) => {},
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn library_is_loaded_and_deleted() {
+    let commands = Arc::new(Mutex::new(Vec::new()));
+    let server = start_function_server(0, commands.clone(), Arc::new(Mutex::new(Vec::new())));
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+    let library = FunctionLibrary::new(LIBRARY).unwrap();
+
+    library.load(&mut client).unwrap();
+    assert_eq!(client.fcall::<i64, _, _, _>("add", "counter", 5).unwrap(), 0);
+    library.delete(&mut client).unwrap();
+    let names: Vec<String> = commands.lock().unwrap().iter().map(|(_, name)| name.clone()).collect();
+    assert_eq!(names, vec!["FUNCTION LOAD", "FCALL", "FUNCTION DELETE"]);
+}
+
+#[test]
+fn library_is_loaded_once_for_a_pool() {
+    let commands = Arc::new(Mutex::new(Vec::new()));
+    let server = start_function_server(0, commands.clone(), Arc::new(Mutex::new(Vec::new())));
+
+    let pool = RedisPool::new(&server.get_config(), PoolConfig::new().set_min_idle(2).set_test_on_checkout(false)).unwrap();
+    assert_eq!(pool.get_connection_nb(), 2);
+    FunctionLibrary::new(LIBRARY).unwrap().load_on_pool(&pool).unwrap();
+    assert_eq!(pool.get().unwrap().fcall::<i64, _, _, _>("add", "counter", 5).unwrap(), 0);
+
+    let names: Vec<String> = commands.lock().unwrap().iter().map(|(_, name)| name.clone()).collect();
+    assert_eq!(names, vec!["FUNCTION LOAD", "FCALL"]);
+}
+
+#[test]
+fn library_is_loaded_on_every_cluster_master() {
+    let commands = Arc::new(Mutex::new(Vec::new()));
+    let slots_reply = Arc::new(Mutex::new(Vec::new()));
+    let servers: Vec<FakeServer> = (0..2).map(|node| start_function_server(node, commands.clone(), slots_reply.clone())).collect();
+    *slots_reply.lock().unwrap() = format!("*2\r\n*3\r\n:0\r\n:8191\r\n*2\r\n$9\r\n127.0.0.1\r\n:{}\r\n*3\r\n:8192\r\n:16383\r\n*2\r\n$9\r\n127.0.0.1\r\n:{}\r\n",
+        servers[0].get_port(), servers[1].get_port()).into_bytes();
+
+    let mut cluster = ClusterClient::with_configs(&[servers[0].get_config()]).unwrap();
+    FunctionLibrary::new(LIBRARY).unwrap().load_on_cluster(&mut cluster).unwrap();
+    // "bar" is stored in the first half of the slots and "foo" in the second one
+    assert_eq!(cluster.fcall::<i64, _, _, _>("add", "bar", 1).unwrap(), 0);
+    assert_eq!(cluster.fcall::<i64, _, _, _>("add", "foo", 1).unwrap(), 1);
+
+    let commands = commands.lock().unwrap();
+    for node in 0..2 {
+        assert_eq!(commands.iter().filter(|(loaded_node, name)| *loaded_node == node && name == "FUNCTION LOAD").count(), 1);
+    }
+}
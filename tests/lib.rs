@@ -2,4 +2,22 @@
 
 extern crate redis_client;
 
-mod commands;
\ No newline at end of file
+#[cfg(feature = "aio")]
+mod aio;
+mod args;
+mod async_client;
+mod cluster;
+mod commands;
+mod config;
+mod function;
+mod pipeline;
+mod pool;
+mod pubsub;
+mod reader;
+mod reconnect;
+mod results;
+mod scan;
+mod script;
+mod sentinel;
+mod support;
+mod transaction;
@@ -0,0 +1,107 @@
+//! The tests `pipeline` mod is checking how the Pipeline sends its commands and converts their responses.
+
+extern crate redis_client;
+
+use redis_client::errors::{RedisError, ServerErrorKind};
+use redis_client::{Pipeline, RedisClient, RedisCommand};
+
+use std::sync::{Arc, Mutex};
+
+use support::{command_name, FakeServer};
+
+/// Start a server recording the commands it receives, which runs the transactions without errors unless they contain LLEN
+fn start_pipeline_server() -> (FakeServer, Arc<Mutex<Vec<String>>>) {
+    let commands = Arc::new(Mutex::new(Vec::new()));
+    let server_commands = commands.clone();
+    let server = FakeServer::start(move |args| {
+        let name = command_name(args);
+        let in_transaction = server_commands.lock().unwrap().contains(&"MULTI".to_string());
+        server_commands.lock().unwrap().push(name.clone());
+        match name.as_str() {
+            "MULTI" => Some(b"+OK\r\n".to_vec()),
+            "LLEN" if in_transaction => Some(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n".to_vec()),
+            _ if in_transaction && name != "EXEC" => Some(b"+QUEUED\r\n".to_vec()),
+            "EXEC" if server_commands.lock().unwrap().contains(&"LLEN".to_string()) => {
+                Some(b"-EXECABORT Transaction discarded because of previous errors.\r\n".to_vec())
+            },
+            "EXEC" => Some(b"*2\r\n+OK\r\n$5\r\nvalue\r\n".to_vec()),
+            "GET" => Some(b"$5\r\nvalue\r\n".to_vec()),
+            "LLEN" => Some(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n".to_vec()),
+            "PING" => Some(b"+PONG\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    (server, commands)
+}
+
+#[test]
+fn pipeline_results_are_typed_and_ignored_ones_are_left_out() {
+    let (server, commands) = start_pipeline_server();
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let (value, length): (Option<String>, Result<i64, RedisError>) = Pipeline::new()
+        .set("key", "value").ignore()
+        .get("key")
+        .llen("key")
+        .query(&mut client).unwrap();
+
+    assert_eq!(value, Some("value".to_string()));
+    match length {
+        Err(RedisError::Response(err)) => assert_eq!(err.kind, ServerErrorKind::WrongType),
+        other => panic!("the command should fail, got {:?}", other),
+    };
+    assert_eq!(*commands.lock().unwrap(), vec!["SET", "GET", "LLEN"]);
+}
+
+#[test]
+fn pipeline_error_fails_the_conversion_without_result_type() {
+    let (server, _commands) = start_pipeline_server();
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let result: Result<(String, i64), RedisError> = Pipeline::new().get("key").llen("key").query(&mut client);
+    match result {
+        Err(RedisError::Response(err)) => assert_eq!(err.kind, ServerErrorKind::WrongType),
+        other => panic!("the conversion should fail, got {:?}", other),
+    };
+}
+
+#[test]
+fn atomic_pipeline_returns_exec_results() {
+    let (server, commands) = start_pipeline_server();
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let pipeline = &mut Pipeline::new();
+    pipeline.atomic().set("key", "value").ignore().get("key");
+    assert!(pipeline.is_atomic());
+    let (value,): (String,) = pipeline.query(&mut client).unwrap();
+
+    assert_eq!(value, "value");
+    assert_eq!(*commands.lock().unwrap(), vec!["MULTI", "SET", "GET", "EXEC"]);
+}
+
+#[test]
+fn atomic_pipeline_surfaces_execabort() {
+    let (server, _commands) = start_pipeline_server();
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let result: Result<Vec<String>, RedisError> = Pipeline::new().atomic().get("key").llen("key").query(&mut client);
+    match result {
+        Err(RedisError::Response(err)) => assert_eq!(err.kind, ServerErrorKind::ExecAbort),
+        other => panic!("the transaction should be aborted, got {:?}", other),
+    };
+}
+
+#[test]
+fn pipeline_sends_custom_commands() {
+    let (server, _commands) = start_pipeline_server();
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let pipeline = &mut Pipeline::new();
+    pipeline.add_command(RedisCommand::new().add_cmd("PING").end()).get("key");
+    assert_eq!(pipeline.get_command_nb(), 2);
+    let results: Vec<String> = pipeline.query(&mut client).unwrap();
+    assert_eq!(results, vec!["PONG", "value"]);
+
+    let empty: Vec<String> = Pipeline::new().query(&mut client).unwrap();
+    assert!(empty.is_empty());
+}
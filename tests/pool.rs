@@ -0,0 +1,193 @@
+//! The tests `pool` mod is checking how the RedisPool hands out and takes back its connections.
+
+extern crate redis_client;
+
+use redis_client::commands::{CommandBuilder, CommandSender, RedisCommand};
+use redis_client::errors::RedisError;
+use redis_client::{PoolConfig, RedisPool};
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use support::{command_name, wait_for, FakeServer};
+
+/// A server replying PONG to PING and "value" to the other commands
+fn start_server() -> FakeServer {
+    FakeServer::start(|args| {
+        if command_name(args) == "PING" {
+            Some(b"+PONG\r\n".to_vec())
+        } else {
+            Some(b"$5\r\nvalue\r\n".to_vec())
+        }
+    })
+}
+
+/// This function uses the connection through the CommandSender trait only.
+fn get_value<C: CommandSender>(client: &mut C) -> String {
+    client.get("key").unwrap()
+}
+
+#[test]
+fn pool_reuses_connections() {
+    let server = start_server();
+    let pool = RedisPool::new(&server.get_config(), &PoolConfig::new()).unwrap();
+
+    for _ in 0..5 {
+        let mut connection = pool.get().unwrap();
+        assert_eq!(get_value(&mut connection), "value");
+    }
+    assert_eq!(server.get_connection_nb(), 1);
+    assert_eq!(pool.get_connection_nb(), 1);
+    assert_eq!(pool.get_idle_nb(), 1);
+}
+
+#[test]
+fn pool_opens_min_idle_connections() {
+    let server = start_server();
+    let mut pool_config = PoolConfig::new();
+    pool_config.set_min_idle(3);
+    let pool = RedisPool::new(&server.get_config(), &pool_config).unwrap();
+
+    assert_eq!(pool.get_idle_nb(), 3);
+    assert!(wait_for(|| server.get_connection_nb() == 3, Duration::new(5, 0)));
+}
+
+#[test]
+fn pool_closes_connections_above_max_idle() {
+    let server = start_server();
+    let mut pool_config = PoolConfig::new();
+    pool_config.set_max_idle(1);
+    let pool = RedisPool::new(&server.get_config(), &pool_config).unwrap();
+
+    {
+        let _first = pool.get().unwrap();
+        let _second = pool.get().unwrap();
+        assert_eq!(pool.get_connection_nb(), 2);
+    }
+    assert_eq!(pool.get_connection_nb(), 1);
+    assert_eq!(pool.get_idle_nb(), 1);
+}
+
+#[test]
+fn pool_checkout_times_out() {
+    let server = start_server();
+    let mut pool_config = PoolConfig::new();
+    pool_config.set_max_size(1).set_checkout_timeout(Some(Duration::from_millis(100)));
+    let pool = RedisPool::new(&server.get_config(), &pool_config).unwrap();
+
+    let _connection = pool.get().unwrap();
+    let start = Instant::now();
+    match pool.get() {
+        Err(RedisError::Io(ref err)) if err.kind() == io::ErrorKind::TimedOut => {},
+        other => panic!("expected a timeout, got {:?}", other),
+    };
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[test]
+fn pool_checkout_waits_for_returned_connection() {
+    let server = start_server();
+    let mut pool_config = PoolConfig::new();
+    pool_config.set_max_size(1);
+    let pool = RedisPool::new(&server.get_config(), &pool_config).unwrap();
+
+    let connection = pool.get().unwrap();
+    let waiting_pool = pool.clone();
+    let waiter = thread::spawn(move || get_value(&mut waiting_pool.get().unwrap()));
+
+    thread::sleep(Duration::from_millis(50));
+    drop(connection);
+    assert_eq!(waiter.join().unwrap(), "value");
+    assert_eq!(server.get_connection_nb(), 1);
+}
+
+#[test]
+fn pool_replaces_connection_failing_ping() {
+    let server = FakeServer::start(|args| {
+        if command_name(args) == "PING" {
+            None
+        } else {
+            Some(b"$5\r\nvalue\r\n".to_vec())
+        }
+    });
+    let pool = RedisPool::new(&server.get_config(), &PoolConfig::new()).unwrap();
+
+    drop(pool.get().unwrap());
+    let mut connection = pool.get().unwrap();
+    assert_eq!(get_value(&mut connection), "value");
+    assert_eq!(server.get_connection_nb(), 2);
+    assert_eq!(pool.get_connection_nb(), 1);
+}
+
+#[test]
+fn pool_replaces_expired_connection() {
+    let server = start_server();
+    let mut pool_config = PoolConfig::new();
+    pool_config.set_max_lifetime(Some(Duration::from_millis(50)));
+    let pool = RedisPool::new(&server.get_config(), &pool_config).unwrap();
+
+    drop(pool.get().unwrap());
+    thread::sleep(Duration::from_millis(100));
+    drop(pool.get().unwrap());
+    assert!(wait_for(|| server.get_connection_nb() == 2, Duration::new(5, 0)));
+    assert_eq!(pool.get_connection_nb(), 1);
+}
+
+#[test]
+fn pool_closes_broken_connection() {
+    let server = FakeServer::start(|_args| None);
+    let mut pool_config = PoolConfig::new();
+    pool_config.set_test_on_checkout(false);
+    let pool = RedisPool::new(&server.get_config(), &pool_config).unwrap();
+
+    {
+        let mut connection = pool.get().unwrap();
+        assert!(connection.get::<String, _>("key").is_err());
+    }
+    assert_eq!(pool.get_connection_nb(), 0);
+    assert_eq!(pool.get_idle_nb(), 0);
+}
+
+#[test]
+fn pool_reopens_min_idle_connections() {
+    // GET closes the connection
+    let server = FakeServer::start(|args| if command_name(args) == "GET" { None } else { Some(b"+PONG\r\n".to_vec()) });
+    let mut pool_config = PoolConfig::new();
+    pool_config.set_min_idle(1).set_test_on_checkout(false);
+    let pool = RedisPool::new(&server.get_config(), &pool_config).unwrap();
+
+    {
+        let mut connection = pool.get().unwrap();
+        assert!(connection.exec_redis_command(RedisCommand::new().get("key")).is_err());
+    }
+    // the broken connection is closed on drop, the next checkout opens the missing one
+    assert_eq!(pool.get_connection_nb(), 0);
+    assert_eq!(server.get_connection_nb(), 1);
+
+    let _connection = pool.get().unwrap();
+    assert_eq!(pool.get_connection_nb(), 1);
+    assert!(wait_for(|| server.get_connection_nb() == 2, Duration::new(5, 0)));
+}
+
+#[test]
+fn pool_is_shared_between_threads() {
+    let server = start_server();
+    let mut pool_config = PoolConfig::new();
+    pool_config.set_max_size(3);
+    let pool = RedisPool::new(&server.get_config(), &pool_config).unwrap();
+
+    let workers: Vec<_> = (0..8).map(|_| {
+        let pool = pool.clone();
+        thread::spawn(move || {
+            for _ in 0..20 {
+                assert_eq!(get_value(&mut pool.get().unwrap()), "value");
+            }
+        })
+    }).collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    assert!(server.get_connection_nb() <= 3);
+    assert!(pool.get_connection_nb() <= 3);
+}
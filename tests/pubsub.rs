@@ -0,0 +1,391 @@
+//! The tests `pubsub` mod is checking how the PubSubClientAsync routes the replies and the messages read on its connection.
+
+extern crate redis_client;
+
+use redis_client::commands::PubSubCommandAsync;
+use redis_client::results::RedisResult;
+use redis_client::types::PubSubType;
+use redis_client::{ProtocolVersion, PubSubClientAsync, PubSubMessage, ReconnectPolicy, RedisClient};
+
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+use support::{command_name, pump_until, wait_for, FakeServer};
+
+fn timeout() -> Duration {
+    Duration::new(5, 0)
+}
+
+#[test]
+fn pubsub_replies_and_messages_are_routed() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => Some(b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nfirst\r\n".to_vec()),
+            // the message is pushed before the reply of the command
+            "PING" => Some(b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$6\r\nsecond\r\n*2\r\n$4\r\npong\r\n$0\r\n\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (cmd_tx, cmd_rx) = channel();
+    let (msg_tx, msg_rx) = channel();
+    let subscribe_tx = cmd_tx.clone();
+    client.subscribe("news", move |result| { let _ = subscribe_tx.send(result); }, move |message| { let _ = msg_tx.send(message); }).unwrap();
+    client.ping(move |result| { let _ = cmd_tx.send(result); }).unwrap();
+
+    let confirmation = pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).unwrap();
+    assert_eq!(confirmation.convert::<Vec<String>>(), vec!["subscribe", "news", "1"]);
+    let pong = pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).unwrap();
+    assert_eq!(pong.convert::<Vec<String>>(), vec!["pong", ""]);
+
+    assert_eq!(pump_until(|| client.pump().unwrap(), &msg_rx, timeout()).get_payload(), b"first");
+    assert_eq!(pump_until(|| client.pump().unwrap(), &msg_rx, timeout()).get_payload(), b"second");
+}
+
+#[test]
+fn pubsub_pattern_message_reaches_pattern_callback() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "PSUBSCRIBE" => Some(b"*3\r\n$10\r\npsubscribe\r\n$2\r\nn*\r\n:1\r\n*4\r\n$8\r\npmessage\r\n$2\r\nn*\r\n$4\r\nnews\r\n$5\r\nhello\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (msg_tx, msg_rx) = channel();
+    client.psubscribe("n*", |_result| {}, move |message| { let _ = msg_tx.send(message); }).unwrap();
+
+    let message = pump_until(|| client.pump().unwrap(), &msg_rx, timeout());
+    assert_eq!(message, PubSubMessage::new("news", Some("n*"), b"hello".to_vec()));
+}
+
+#[test]
+fn binary_payload_is_kept() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => Some(b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$4\r\n\x92\xa2\x00\xff\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (msg_tx, msg_rx) = channel();
+    client.subscribe("news", |_result| {}, move |message| { let _ = msg_tx.send(message); }).unwrap();
+
+    let message = pump_until(|| client.pump().unwrap(), &msg_rx, timeout());
+    assert_eq!(message.get_channel(), "news");
+    assert_eq!(message.get_pattern(), None);
+    assert_eq!(message.get_payload(), &[0x92u8, 0xa2, 0x00, 0xff][..]);
+    assert!(message.get_payload_str().is_err());
+    assert_eq!(message.into_payload(), vec![0x92u8, 0xa2, 0x00, 0xff]);
+}
+
+#[test]
+fn payload_decoding_works() {
+    let message = PubSubMessage::new("news", None, b"3.5".to_vec());
+
+    assert_eq!(message.get_payload_str().unwrap(), "3.5");
+    assert_eq!(message.decode::<f64>().unwrap(), 3.5);
+    assert_eq!(message.decode::<String>().unwrap(), "3.5");
+    assert!(message.decode::<i64>().is_err());
+}
+
+#[test]
+fn pubsub_resp3_pushes_are_routed() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "HELLO" => Some(b"%1\r\n$5\r\nproto\r\n:3\r\n".to_vec()),
+            "SUBSCRIBE" => Some(b">3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n>3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n".to_vec()),
+            // a regular array reply looking like a message is still a reply with RESP3
+            _ => Some(b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$4\r\nnope\r\n".to_vec()),
+        }
+    });
+    let mut client = PubSubClientAsync::new_with_protocol(server.get_host(), server.get_port(), ProtocolVersion::Resp3).unwrap();
+
+    let (cmd_tx, cmd_rx) = channel();
+    let (msg_tx, msg_rx) = channel();
+    let subscribe_tx = cmd_tx.clone();
+    client.subscribe("news", move |result| { let _ = subscribe_tx.send(result); }, move |message| { let _ = msg_tx.send(message); }).unwrap();
+    client.publish("news", "nope", move |result| { let _ = cmd_tx.send(result); }).unwrap();
+
+    match pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).unwrap() {
+        RedisResult::Push(_) => {},
+        other => panic!("expected a push confirmation, got {:?}", other),
+    };
+    match pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).unwrap() {
+        RedisResult::Array(_) => {},
+        other => panic!("expected an array reply, got {:?}", other),
+    };
+    assert_eq!(pump_until(|| client.pump().unwrap(), &msg_rx, timeout()).get_payload(), b"hello");
+    client.pump().unwrap();
+    assert!(msg_rx.try_recv().is_err());
+}
+
+#[test]
+fn pubsub_commands_are_sent_while_the_connection_is_idle() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => Some(b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n".to_vec()),
+            _ => Some(b"*2\r\n$4\r\npong\r\n$0\r\n\r\n".to_vec()),
+        }
+    });
+    let mut config = server.get_config();
+    config.set_read_timeout(None);
+    let mut client = PubSubClientAsync::with_config(&config).unwrap();
+
+    let (cmd_tx, cmd_rx) = channel();
+    let subscribe_tx = cmd_tx.clone();
+    client.subscribe("news", move |result| { let _ = subscribe_tx.send(result); }, |_message| {}).unwrap();
+    assert!(pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).is_ok());
+
+    // nothing is read on the connection anymore, the ping is written anyway
+    client.ping(move |result| { let _ = cmd_tx.send(result); }).unwrap();
+    let pong = pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).unwrap();
+    assert_eq!(pong.convert::<Vec<String>>(), vec!["pong", ""]);
+}
+
+#[test]
+fn pubsub_worker_stops_when_client_is_dropped() {
+    let server = FakeServer::start(|_args| Some(b"+OK\r\n".to_vec()));
+    let client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    drop(client);
+    assert!(wait_for(|| server.get_closed_nb() == 1, timeout()));
+}
+
+#[test]
+fn unsubscribe_removes_callback_once_confirmed() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => Some(b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n".to_vec()),
+            // a message published right after the unsubscription must not reach the removed callback
+            "UNSUBSCRIBE" => Some(b"*3\r\n$11\r\nunsubscribe\r\n$4\r\nnews\r\n:0\r\n*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$4\r\nlate\r\n".to_vec()),
+            _ => Some(b"*2\r\n$4\r\npong\r\n$0\r\n\r\n".to_vec()),
+        }
+    });
+    let mut client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (cmd_tx, cmd_rx) = channel();
+    let (msg_tx, msg_rx) = channel();
+    let subscribe_tx = cmd_tx.clone();
+    client.subscribe("news", move |result| { let _ = subscribe_tx.send(result); }, move |message| { let _ = msg_tx.send(message); }).unwrap();
+    assert!(pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).is_ok());
+    assert_eq!(client.get_subscriptions(), vec![PubSubType::Channel("news".to_string())]);
+
+    let unsubscribe_tx = cmd_tx.clone();
+    client.unsubscribe("news", move |result| { let _ = unsubscribe_tx.send(result); }).unwrap();
+    client.ping(move |result| { let _ = cmd_tx.send(result); }).unwrap();
+    let confirmation = pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).unwrap();
+    assert_eq!(confirmation.convert::<Vec<String>>(), vec!["unsubscribe", "news", "0"]);
+    assert!(pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).is_ok());
+
+    assert!(client.get_subscriptions().is_empty());
+    assert!(msg_rx.try_recv().is_err());
+}
+
+#[test]
+fn multi_subscriptions_get_every_confirmation() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => Some(b"*3\r\n$9\r\nsubscribe\r\n$1\r\na\r\n:1\r\n*3\r\n$9\r\nsubscribe\r\n$1\r\nb\r\n:2\r\n\
+                *3\r\n$7\r\nmessage\r\n$1\r\nb\r\n$2\r\nto\r\n".to_vec()),
+            "PSUBSCRIBE" => Some(b"*3\r\n$10\r\npsubscribe\r\n$2\r\nx*\r\n:3\r\n*3\r\n$10\r\npsubscribe\r\n$2\r\ny*\r\n:4\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (cmd_tx, cmd_rx) = channel();
+    let (msg_tx, msg_rx) = channel();
+    let subscribe_tx = cmd_tx.clone();
+    client.msubscribe(&["a", "b"], move |result| { let _ = subscribe_tx.send(result); }, move |message| { let _ = msg_tx.send(message); }).unwrap();
+    client.mpsubscribe(&["x*", "y*"], move |result| { let _ = cmd_tx.send(result); }, |_message| {}).unwrap();
+
+    let confirmations = pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).unwrap();
+    assert_eq!(confirmations.try_convert::<Vec<Vec<String>>>().unwrap(), vec![vec!["subscribe", "a", "1"], vec!["subscribe", "b", "2"]]);
+    assert_eq!(pump_until(|| client.pump().unwrap(), &msg_rx, timeout()).get_payload(), b"to");
+    assert!(pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).is_ok());
+
+    assert_eq!(client.get_subscriptions(), vec![
+        PubSubType::Channel("a".to_string()),
+        PubSubType::Channel("b".to_string()),
+        PubSubType::Pattern("x*".to_string()),
+        PubSubType::Pattern("y*".to_string()),
+    ]);
+}
+
+#[test]
+fn subscription_callbacks_can_mutate_their_captures() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => Some(b"*3\r\n$9\r\nsubscribe\r\n$1\r\na\r\n:1\r\n*3\r\n$9\r\nsubscribe\r\n$1\r\nb\r\n:2\r\n\
+                *3\r\n$7\r\nmessage\r\n$1\r\na\r\n$3\r\none\r\n*3\r\n$7\r\nmessage\r\n$1\r\nb\r\n$3\r\ntwo\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (msg_tx, msg_rx) = channel();
+    let mut payloads = Vec::new();
+    client.msubscribe(&["a", "b"], |_result| {}, move |message| {
+        payloads.push(message.into_payload());
+        if payloads.len() == 2 {
+            let _ = msg_tx.send(payloads.clone());
+        }
+    }).unwrap();
+
+    assert_eq!(pump_until(|| client.pump().unwrap(), &msg_rx, timeout()), vec![b"one".to_vec(), b"two".to_vec()]);
+}
+
+#[test]
+fn unsubscribe_all_waits_for_every_confirmation() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => Some(b"*3\r\n$9\r\nsubscribe\r\n$1\r\na\r\n:1\r\n*3\r\n$9\r\nsubscribe\r\n$1\r\nb\r\n:2\r\n".to_vec()),
+            "PSUBSCRIBE" => Some(b"*3\r\n$10\r\npsubscribe\r\n$2\r\nx*\r\n:3\r\n".to_vec()),
+            // the pattern stays subscribed, so the count stops at 1
+            "UNSUBSCRIBE" => Some(b"*3\r\n$11\r\nunsubscribe\r\n$1\r\na\r\n:2\r\n*3\r\n$11\r\nunsubscribe\r\n$1\r\nb\r\n:1\r\n".to_vec()),
+            _ => Some(b"*2\r\n$4\r\npong\r\n$0\r\n\r\n".to_vec()),
+        }
+    });
+    let mut client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (cmd_tx, cmd_rx) = channel();
+    client.msubscribe(&["a", "b"], |_result| {}, |_message| {}).unwrap();
+    client.psubscribe("x*", |_result| {}, |_message| {}).unwrap();
+    let unsubscribe_tx = cmd_tx.clone();
+    client.unsubscribe_all(move |result| { let _ = unsubscribe_tx.send(result); }).unwrap();
+    client.ping(move |result| { let _ = cmd_tx.send(result); }).unwrap();
+
+    let confirmations = pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).unwrap();
+    assert_eq!(confirmations.try_convert::<Vec<Vec<String>>>().unwrap(), vec![vec!["unsubscribe", "a", "2"], vec!["unsubscribe", "b", "1"]]);
+    let pong = pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).unwrap();
+    assert_eq!(pong.convert::<Vec<String>>(), vec!["pong", ""]);
+    assert_eq!(client.get_subscriptions(), vec![PubSubType::Pattern("x*".to_string())]);
+}
+
+#[test]
+fn shard_message_reaches_shard_callback() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SSUBSCRIBE" => Some(b"*3\r\n$10\r\nssubscribe\r\n$4\r\nnews\r\n:1\r\n*3\r\n$8\r\nsmessage\r\n$4\r\nnews\r\n$5\r\nshard\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (msg_tx, msg_rx) = channel();
+    client.ssubscribe("news", |_result| {}, move |message| { let _ = msg_tx.send(message); }).unwrap();
+
+    assert_eq!(pump_until(|| client.pump().unwrap(), &msg_rx, timeout()).get_payload(), b"shard");
+    assert_eq!(client.get_subscriptions(), vec![PubSubType::ShardChannel("news".to_string())]);
+}
+
+#[test]
+fn pubsub_introspection_works() {
+    let server = FakeServer::start(|args| {
+        match String::from_utf8_lossy(&args[1]).to_uppercase().as_str() {
+            "CHANNELS" => Some(b"*1\r\n$4\r\nnews\r\n".to_vec()),
+            "NUMSUB" => Some(b"*2\r\n$4\r\nnews\r\n:3\r\n".to_vec()),
+            _ => Some(b":2\r\n".to_vec()),
+        }
+    });
+    let mut client = PubSubClientAsync::new(server.get_host(), server.get_port()).unwrap();
+
+    let (tx, rx) = channel();
+    let channels_tx = tx.clone();
+    let numsub_tx = tx.clone();
+    client.pubsub_channels(Some("n*"), move |result| { let _ = channels_tx.send(result); }).unwrap();
+    client.pubsub_numsub("news", move |result| { let _ = numsub_tx.send(result); }).unwrap();
+    client.pubsub_numpat(move |result| { let _ = tx.send(result); }).unwrap();
+
+    assert_eq!(pump_until(|| client.pump().unwrap(), &rx, timeout()).unwrap().convert::<Vec<String>>(), vec!["news"]);
+    assert_eq!(pump_until(|| client.pump().unwrap(), &rx, timeout()).unwrap().convert::<Vec<String>>(), vec!["news", "3"]);
+    assert_eq!(pump_until(|| client.pump().unwrap(), &rx, timeout()).unwrap().convert::<i64>(), 2);
+}
+
+/// This function starts a server confirming the subscriptions to "a" and "b" and the pattern "x*",
+/// pushing a message on "a" after each SUBSCRIBE
+fn start_subscription_server() -> FakeServer {
+    FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => {
+                let mut reply = Vec::new();
+                for (index, channel) in args[1..].iter().enumerate() {
+                    reply.extend_from_slice(format!("*3\r\n$9\r\nsubscribe\r\n${}\r\n", channel.len()).as_bytes());
+                    reply.extend_from_slice(channel);
+                    reply.extend_from_slice(format!("\r\n:{}\r\n", index + 1).as_bytes());
+                }
+                reply.extend_from_slice(b"*3\r\n$7\r\nmessage\r\n$1\r\na\r\n$5\r\nfirst\r\n*3\r\n$7\r\nmessage\r\n$1\r\na\r\n$6\r\nsecond\r\n");
+                Some(reply)
+            },
+            "PSUBSCRIBE" => Some(b"*3\r\n$10\r\npsubscribe\r\n$2\r\nx*\r\n:3\r\n*4\r\n$8\r\npmessage\r\n$2\r\nx*\r\n$2\r\nxy\r\n$5\r\nthird\r\n".to_vec()),
+            "UNSUBSCRIBE" => Some(b"*3\r\n$11\r\nunsubscribe\r\n$1\r\na\r\n:2\r\n*3\r\n$11\r\nunsubscribe\r\n$1\r\nb\r\n:1\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    })
+}
+
+#[test]
+fn sync_pubsub_get_message_works() {
+    let server = start_subscription_server();
+    let mut pubsub = RedisClient::new(server.get_host(), server.get_port()).unwrap().into_pubsub();
+
+    pubsub.subscribe(["a", "b"]).unwrap();
+    assert_eq!(pubsub.get_subscriptions(), vec![PubSubType::Channel("a".to_string()), PubSubType::Channel("b".to_string())]);
+
+    assert_eq!(pubsub.get_message(Some(timeout())).unwrap(), Some(PubSubMessage::new("a", None, b"first".to_vec())));
+    assert_eq!(pubsub.get_message(Some(timeout())).unwrap(), Some(PubSubMessage::new("a", None, b"second".to_vec())));
+    assert_eq!(pubsub.get_message(Some(Duration::from_millis(50))).unwrap(), None);
+}
+
+#[test]
+fn sync_pubsub_keeps_messages_received_during_subscriptions() {
+    let server = start_subscription_server();
+    let mut pubsub = RedisClient::new(server.get_host(), server.get_port()).unwrap().into_pubsub();
+
+    pubsub.subscribe("a").unwrap();
+    pubsub.psubscribe("x*").unwrap();
+    pubsub.unsubscribe_all().unwrap();
+    assert_eq!(pubsub.get_subscriptions(), vec![PubSubType::Pattern("x*".to_string())]);
+
+    let messages: Vec<PubSubMessage> = pubsub.by_ref().take(3).collect::<Result<_, _>>().unwrap();
+    assert_eq!(messages, vec![
+        PubSubMessage::new("a", None, b"first".to_vec()),
+        PubSubMessage::new("a", None, b"second".to_vec()),
+        PubSubMessage::new("xy", Some("x*"), b"third".to_vec()),
+    ]);
+}
+
+#[test]
+fn sync_pubsub_iteration_ends_on_error() {
+    let server = FakeServer::start(|args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => Some(b"*3\r\n$9\r\nsubscribe\r\n$1\r\na\r\n:1\r\n*3\r\n$7\r\nmessage\r\n$1\r\na\r\n$5\r\nfirst\r\n".to_vec()),
+            _ => None,
+        }
+    });
+    let mut config = server.get_config();
+    config.set_reconnect_policy(ReconnectPolicy::disabled());
+    let mut pubsub = RedisClient::with_config(&config).unwrap().into_pubsub();
+
+    pubsub.subscribe("a").unwrap();
+    // the connection is closed by the server
+    assert!(pubsub.psubscribe("x*").is_err());
+
+    assert_eq!(pubsub.next().unwrap().unwrap().get_payload(), b"first");
+    assert!(pubsub.next().unwrap().is_err());
+    assert!(pubsub.next().is_none());
+}
+
+#[test]
+fn sync_pubsub_receiver_works() {
+    let server = start_subscription_server();
+    let mut pubsub = RedisClient::new(server.get_host(), server.get_port()).unwrap().into_pubsub();
+    pubsub.subscribe("a").unwrap();
+
+    let receiver = pubsub.into_receiver();
+    assert_eq!(receiver.recv_timeout(timeout()).unwrap().get_payload(), b"first");
+    assert_eq!(receiver.recv_timeout(timeout()).unwrap().get_payload(), b"second");
+}
@@ -0,0 +1,400 @@
+//! The tests `reader` mod is checking if the responses are correctly parsed.
+
+extern crate redis_client;
+
+use redis_client::errors::{ParsingError, RedisError, ServerError, ServerErrorKind};
+use redis_client::reader::{Decoder, Reader};
+use redis_client::results::RedisResult;
+
+use std::collections::VecDeque;
+use std::io;
+use std::io::{BufReader, Cursor, Read};
+
+/// This function parses an array of bytes and checks the result and the number of bytes consumed.
+///
+/// # Examples
+///
+/// ``` rust
+/// check_parse(b":42\r\n", "42", 5);
+/// ```
+fn check_parse(input: &[u8], expected: &str, expected_consumed: usize) {
+    let (result, consumed) = Reader::parse(input).unwrap();
+    assert_eq!(result.convert::<String>(), expected);
+    assert_eq!(consumed, expected_consumed);
+}
+
+#[test]
+fn parse_simple_string_works() {
+    check_parse(b"+OK\r\n", "OK", 5);
+}
+
+#[test]
+fn parse_integer_works() {
+    check_parse(b":-42\r\n", "-42", 6);
+}
+
+#[test]
+fn parse_bulk_string_works() {
+    check_parse(b"$12\r\nhello\r\nworld\r\n", "hello\r\nworld", 19);
+}
+
+#[test]
+fn parse_binary_bulk_string_works() {
+    let (result, consumed) = Reader::parse(b"$3\r\n\x00\xff\n\r\n").unwrap();
+    assert_eq!(result.convert::<Vec<u8>>(), vec![0, 255, 10]);
+    assert_eq!(consumed, 9);
+}
+
+#[test]
+fn parse_bulk_string_checks_its_line_ending() {
+    for input in &[&b"$3\r\nfooXY"[..], &b"=7\r\ntxt:fooXY"[..], &b"!3\r\nERR\r:1\r\n"[..]] {
+        match Reader::parse(input) {
+            Err(RedisError::Parse(ParsingError::InvalidValue(_))) => {},
+            other => panic!("unexpected result {:?}", other.map(|(_, consumed)| consumed)),
+        };
+    }
+}
+
+#[test]
+fn parse_nil_bulk_string_works() {
+    check_parse(b"$-1\r\n", "null", 5);
+}
+
+#[test]
+fn parse_array_works() {
+    check_parse(b"*3\r\n$3\r\nfoo\r\n:1\r\n*0\r\n", "[foo,1,[],]", 21);
+}
+
+#[test]
+fn parse_nil_array_works() {
+    check_parse(b"*-1\r\n", "null", 5);
+}
+
+#[test]
+fn parse_only_consumes_one_response() {
+    let input = b"+OK\r\n$3\r\nbar\r\n";
+    let (_, consumed) = Reader::parse(input).unwrap();
+    check_parse(&input[consumed..], "bar", 9);
+}
+
+#[test]
+fn parse_error_works() {
+    match Reader::parse(b"-ERR unknown command\r\n") {
+        Err(RedisError::Response(err)) => {
+            assert_eq!(err.kind, ServerErrorKind::Err);
+            assert_eq!(err.message, "unknown command");
+        },
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn parse_bad_identifier_fails() {
+    match Reader::parse(b"?42\r\n") {
+        Err(RedisError::Parse(_)) => {},
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn parse_incomplete_response_fails() {
+    for input in [&b""[..], b"+OK", b"$5\r\nhel", b"$5\r\nhello", b"*2\r\n:1\r\n"].iter() {
+        match Reader::parse(input) {
+            Err(RedisError::Io(_)) => {},
+            other => panic!("unexpected result {:?} for {:?}", other, input),
+        }
+    }
+}
+
+#[test]
+fn read_from_buf_reader_works() {
+    let mut buffer = BufReader::with_capacity(2, Cursor::new(&b"$10\r\n0123456789\r\n"[..]));
+    let result = Reader::read(&mut buffer, &mut Decoder::new()).unwrap();
+    assert_eq!(result.convert::<String>(), "0123456789");
+}
+
+/// A source giving its chunks one at a time, like a socket receiving a response in several packets
+struct Chunks(VecDeque<io::Result<Vec<u8>>>);
+
+impl Read for Chunks {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.pop_front() {
+            Some(Ok(mut chunk)) => {
+                let size = chunk.len().min(buf.len());
+                buf[..size].copy_from_slice(&chunk[..size]);
+                if size < chunk.len() {
+                    self.0.push_front(Ok(chunk.split_off(size)));
+                }
+                Ok(size)
+            },
+            Some(Err(err)) => Err(err),
+            None => Ok(0),
+        }
+    }
+}
+
+#[test]
+fn read_continues_after_timeout() {
+    let chunks = vec![Ok(b"*2\r\n$10\r\n0123".to_vec()), Err(io::ErrorKind::TimedOut.into()), Ok(b"456789\r\n:1\r\n".to_vec())];
+    let mut buffer = BufReader::with_capacity(4, Chunks(chunks.into_iter().collect()));
+    let mut decoder = Decoder::new();
+
+    match Reader::read(&mut buffer, &mut decoder) {
+        Err(RedisError::Io(ref err)) if err.kind() == io::ErrorKind::TimedOut => {},
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert_eq!(decoder.len(), 13);
+    assert_eq!(Reader::read(&mut buffer, &mut decoder).unwrap().convert::<Vec<String>>(), vec!["0123456789", "1"]);
+}
+
+#[test]
+fn read_pipeline_works() {
+    let mut buffer = Cursor::new(&b"+OK\r\n-ERR wrong\r\n$3\r\nbar\r\n"[..]);
+    let results = Reader::read_pipeline(&mut buffer, &mut Decoder::new(), 3).unwrap();
+    let strings: Vec<String> = results.into_iter().map(RedisResult::convert::<String>).collect();
+    assert_eq!(strings, vec!["OK", "ERR wrong", "bar"]);
+}
+
+#[test]
+fn read_pipeline_keeps_errors() {
+    let mut buffer = Cursor::new(&b"+OK\r\n-ERR wrong\r\n"[..]);
+    let results = Reader::read_pipeline(&mut buffer, &mut Decoder::new(), 2).unwrap();
+    match (&results[0], &results[1]) {
+        (RedisResult::String(status), RedisResult::Error(err)) => {
+            assert_eq!(status, "OK");
+            assert_eq!(*err, ServerError::new("ERR wrong"));
+        },
+        other => panic!("unexpected results {:?}", other),
+    }
+}
+
+#[test]
+fn parse_nested_error_works() {
+    let (result, _) = Reader::parse(b"*2\r\n:1\r\n-EXECABORT Transaction discarded\r\n").unwrap();
+    match result {
+        RedisResult::Array(values) => match values[1] {
+            RedisResult::Error(ref err) => assert_eq!(err.kind, ServerErrorKind::ExecAbort),
+            ref other => panic!("unexpected value {:?}", other),
+        },
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn parse_refuses_deeply_nested_responses() {
+    let mut input = b"*1\r\n".repeat(100_000);
+    match Reader::parse(&input) {
+        Err(RedisError::Parse(ParsingError::InvalidValue(_))) => {},
+        other => panic!("unexpected result {:?}", other.map(|(_, consumed)| consumed)),
+    };
+    input.extend_from_slice(b":1\r\n");
+    assert!(Reader::parse(&input).is_err());
+
+    let mut input = b"*1\r\n".repeat(100);
+    input.extend_from_slice(b":1\r\n");
+    let (_, consumed) = Reader::parse(&input).unwrap();
+    assert_eq!(consumed, input.len());
+}
+
+#[test]
+fn server_error_kinds_work() {
+    let kinds = vec![
+        ("ERR", ServerErrorKind::Err), ("WRONGTYPE", ServerErrorKind::WrongType), ("MOVED", ServerErrorKind::Moved),
+        ("ASK", ServerErrorKind::Ask), ("TRYAGAIN", ServerErrorKind::TryAgain), ("LOADING", ServerErrorKind::Loading),
+        ("NOSCRIPT", ServerErrorKind::NoScript), ("BUSY", ServerErrorKind::Busy), ("READONLY", ServerErrorKind::ReadOnly),
+        ("NOAUTH", ServerErrorKind::NoAuth), ("EXECABORT", ServerErrorKind::ExecAbort), ("CROSSSLOT", ServerErrorKind::CrossSlot),
+        ("CLUSTERDOWN", ServerErrorKind::ClusterDown), ("OOM", ServerErrorKind::OutOfMemory),
+    ];
+    for (code, kind) in kinds {
+        let err = ServerError::new(&format!("{} some message", code));
+        assert_eq!(err.kind, kind);
+        assert_eq!(err.message, "some message");
+        assert_eq!(err.to_string(), format!("{} some message", code));
+    }
+
+    let err = ServerError::new("MOVED 3999 127.0.0.1:6381\r\n");
+    assert_eq!(err.message, "3999 127.0.0.1:6381");
+
+    let err = ServerError::new("something went wrong");
+    assert_eq!(err.kind, ServerErrorKind::Other(String::new()));
+    assert_eq!(err.to_string(), "something went wrong");
+}
+
+#[test]
+fn decoder_waits_for_complete_response() {
+    let mut decoder = Decoder::new();
+    decoder.feed(b"*2\r\n$3\r\nfoo\r\n");
+    assert!(decoder.decode().unwrap().is_none());
+    assert_eq!(decoder.len(), 13);
+
+    decoder.feed(b":1\r\n");
+    let result = decoder.decode().unwrap().unwrap();
+    assert_eq!(result.convert::<String>(), "[foo,1,]");
+    assert!(decoder.is_empty());
+}
+
+#[test]
+fn decoder_works_byte_by_byte() {
+    let input = b"+OK\r\n$5\r\nhello\r\n*-1\r\n:7\r\n";
+    let mut decoder = Decoder::new();
+    let mut results: Vec<String> = Vec::new();
+    for byte in input.iter() {
+        decoder.feed(&[*byte]);
+        while let Some(result) = decoder.decode().unwrap() {
+            results.push(result.convert::<String>());
+        }
+    }
+    assert_eq!(results, vec!["OK", "hello", "null", "7"]);
+    assert!(decoder.is_empty());
+}
+
+#[test]
+fn decoder_works_byte_by_byte_on_nested_responses() {
+    let input: &[u8] = b"*3\r\n%1\r\n+key\r\n~2\r\n:1\r\n-ERR nested\r\n|1\r\n+ttl\r\n:3\r\n$3\r\nfoo\r\n*0\r\n";
+    let mut decoder = Decoder::new();
+    let mut results = Vec::new();
+    for byte in input.iter() {
+        decoder.feed(&[*byte]);
+        while let Some(result) = decoder.decode().unwrap() {
+            results.push(result);
+        }
+    }
+    assert_eq!(results.len(), 1);
+    assert_eq!(format!("{:?}", results[0]), format!("{:?}", Reader::parse(input).unwrap().0));
+    assert!(decoder.is_empty());
+}
+
+#[test]
+fn decoder_consumes_error_response() {
+    let mut decoder = Decoder::new();
+    decoder.feed(b"-WRONGTYPE Operation against a key\r\n+OK\r\n");
+    match decoder.decode() {
+        Err(RedisError::Response(err)) => assert_eq!(err.to_string(), "WRONGTYPE Operation against a key"),
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert_eq!(decoder.decode().unwrap().unwrap().convert::<String>(), "OK");
+}
+
+#[test]
+fn decoder_discards_invalid_bytes() {
+    let mut decoder = Decoder::new();
+    decoder.feed(b"?invalid\r\n+OK\r\n");
+    match decoder.decode() {
+        Err(RedisError::Parse(_)) => {},
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(decoder.is_empty());
+    assert!(decoder.decode().unwrap().is_none());
+}
+
+#[test]
+fn parse_map_works() {
+    let (result, consumed) = Reader::parse(b"%2\r\n+first\r\n:1\r\n$6\r\nsecond\r\n_\r\n").unwrap();
+    match result {
+        RedisResult::Map(pairs) => {
+            let pairs: Vec<(String, String)> = pairs.into_iter().map(|(key, value)| (key.convert(), value.convert())).collect();
+            assert_eq!(pairs, vec![("first".to_string(), "1".to_string()), ("second".to_string(), "null".to_string())]);
+        },
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert_eq!(consumed, 31);
+}
+
+#[test]
+fn parse_set_works() {
+    match Reader::parse(b"~2\r\n+a\r\n+b\r\n").unwrap().0 {
+        RedisResult::Set(members) => assert_eq!(RedisResult::Set(members).convert::<Vec<String>>(), vec!["a", "b"]),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn parse_double_works() {
+    for &(input, expected) in [(&b",2.5\r\n"[..], 2.5), (b",-2\r\n", -2.0), (b",1.5e3\r\n", 1500.0), (b",inf\r\n", f64::INFINITY), (b",-inf\r\n", f64::NEG_INFINITY)].iter() {
+        match Reader::parse(input).unwrap().0 {
+            RedisResult::Double(value) => assert_eq!(value, expected),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+    match Reader::parse(b",nan\r\n").unwrap().0 {
+        RedisResult::Double(value) => assert!(value.is_nan()),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn parse_boolean_works() {
+    match (Reader::parse(b"#t\r\n").unwrap().0, Reader::parse(b"#f\r\n").unwrap().0) {
+        (RedisResult::Boolean(true), RedisResult::Boolean(false)) => {},
+        other => panic!("unexpected result {:?}", other),
+    }
+    match Reader::parse(b"#x\r\n") {
+        Err(RedisError::Parse(_)) => {},
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn parse_big_number_works() {
+    check_parse(b"(3492890328409238509324850943850943825024385\r\n", "3492890328409238509324850943850943825024385", 46);
+}
+
+#[test]
+fn parse_verbatim_string_works() {
+    match Reader::parse(b"=15\r\ntxt:Some string\r\n").unwrap() {
+        (RedisResult::VerbatimString(format, text), 22) => {
+            assert_eq!(format, "txt");
+            assert_eq!(text, "Some string");
+        },
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn parse_blob_error_works() {
+    match Reader::parse(b"!21\r\nSYNTAX invalid syntax\r\n") {
+        Err(RedisError::Response(err)) => {
+            assert_eq!(err.kind, ServerErrorKind::Other("SYNTAX".to_string()));
+            assert_eq!(err.message, "invalid syntax");
+        },
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn parse_null_works() {
+    check_parse(b"_\r\n", "null", 3);
+}
+
+#[test]
+fn parse_attribute_works() {
+    let input = b"|1\r\n+key-popularity\r\n%1\r\n$1\r\na\r\n,0.1923\r\n*1\r\n:2039123\r\n";
+    match Reader::parse(input).unwrap() {
+        (RedisResult::Attribute(value, attributes), consumed) => {
+            assert_eq!(value.convert::<String>(), "[2039123,]");
+            assert_eq!(attributes.len(), 1);
+            assert_eq!(consumed, input.len());
+        },
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn parse_push_works() {
+    match Reader::parse(b">3\r\n$7\r\nmessage\r\n$3\r\nfoo\r\n$3\r\nbar\r\n").unwrap().0 {
+        RedisResult::Push(value) => assert_eq!(RedisResult::Push(value).convert::<Vec<String>>(), vec!["message", "foo", "bar"]),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn decoder_waits_for_complete_resp3_response() {
+    let input = b"%1\r\n=9\r\ntxt:hello\r\n~1\r\n#t\r\n";
+    let mut decoder = Decoder::new();
+    for byte in input[..input.len() - 1].iter() {
+        decoder.feed(&[*byte]);
+        assert!(decoder.decode().unwrap().is_none());
+    }
+    decoder.feed(&input[input.len() - 1..]);
+    assert_eq!(decoder.decode().unwrap().unwrap().convert::<String>(), "{hello:[true,],}");
+}
@@ -0,0 +1,214 @@
+//! The tests `reconnect` mod is checking how the clients open a new connection once theirs dropped.
+
+extern crate redis_client;
+
+use redis_client::commands::{CommandSender, CommandSenderAsync, PubSubCommandAsync};
+use redis_client::errors::RedisError;
+use redis_client::{ConnectionConfig, ConnectionEvent, PubSubClientAsync, ReconnectPolicy, RedisClient, RedisClientAsync};
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use support::{command_name, pump_until, wait_for, FakeServer};
+
+fn timeout() -> Duration {
+    Duration::new(5, 0)
+}
+
+/// This function returns a policy retrying quickly so the tests don't wait
+fn fast_policy(max_attempts: u32) -> ReconnectPolicy {
+    let mut policy = ReconnectPolicy::new();
+    policy.set_max_attempts(max_attempts).set_initial_delay(Duration::from_millis(10)).set_max_delay(Duration::from_millis(20));
+    policy
+}
+
+/// This function starts a server closing the connection on the first GET and replying "value" to the next ones
+fn start_dropping_server(commands: Arc<Mutex<Vec<String>>>) -> FakeServer {
+    let dropped = AtomicBool::new(false);
+    FakeServer::start(move |args| {
+        let name = command_name(args);
+        commands.lock().unwrap().push(name.clone());
+        match name.as_str() {
+            "GET" if !dropped.swap(true, Ordering::SeqCst) => None,
+            "GET" => Some(b"$5\r\nvalue\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    })
+}
+
+/// This function records the events in a list of their names
+fn record_events(config: &mut ConnectionConfig) -> Arc<Mutex<Vec<String>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    config.set_event_handler(move |event| {
+        let name = match event {
+            ConnectionEvent::Disconnected(_) => "disconnected".to_string(),
+            ConnectionEvent::Reconnected(attempts) => format!("reconnected {}", attempts),
+            ConnectionEvent::ReconnectFailed(_) => "failed".to_string(),
+        };
+        recorded.lock().unwrap().push(name);
+    });
+    events
+}
+
+#[test]
+fn client_reconnects_and_replays_init_commands() {
+    let commands = Arc::new(Mutex::new(Vec::new()));
+    let server = start_dropping_server(commands.clone());
+    let mut config = server.get_config();
+    config.set_password("secret").set_db(2).set_client_name("worker").set_reconnect_policy(fast_policy(3));
+    let mut client = RedisClient::with_config(&config).unwrap();
+
+    match client.get::<Option<String>, _>("key") {
+        Err(RedisError::Io(_)) => {},
+        other => panic!("expected an io error, got {:?}", other),
+    };
+    assert!(!client.is_connected());
+
+    let value: Option<String> = client.get("key").unwrap();
+    assert_eq!(value, Some("value".to_string()));
+    assert!(client.is_connected());
+    assert_eq!(server.get_connection_nb(), 2);
+    assert_eq!(*commands.lock().unwrap(), vec!["AUTH", "SELECT", "CLIENT", "GET", "AUTH", "SELECT", "CLIENT", "GET"]);
+}
+
+#[test]
+fn reconnection_events_are_notified() {
+    let server = start_dropping_server(Arc::new(Mutex::new(Vec::new())));
+    let mut config = server.get_config();
+    config.set_reconnect_policy(fast_policy(3));
+    let events = record_events(&mut config);
+    let mut client = RedisClient::with_config(&config).unwrap();
+
+    assert!(client.get::<Option<String>, _>("key").is_err());
+    assert!(client.get::<Option<String>, _>("key").is_ok());
+    assert_eq!(*events.lock().unwrap(), vec!["disconnected", "reconnected 1"]);
+}
+
+#[test]
+fn reconnection_gives_up_after_max_attempts() {
+    // the first connection is named, the next ones are closed as soon as they send CLIENT SETNAME
+    let named = AtomicBool::new(false);
+    let server = FakeServer::start(move |args| {
+        match command_name(args).as_str() {
+            "CLIENT" if named.swap(true, Ordering::SeqCst) => None,
+            "GET" => None,
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut config = server.get_config();
+    config.set_client_name("worker").set_reconnect_policy(fast_policy(3));
+    let events = record_events(&mut config);
+    let mut client = RedisClient::with_config(&config).unwrap();
+
+    assert!(client.get::<Option<String>, _>("key").is_err());
+    assert!(client.get::<Option<String>, _>("key").is_err());
+    assert!(!client.is_connected());
+    assert_eq!(server.get_connection_nb(), 4);
+    assert_eq!(*events.lock().unwrap(), vec!["disconnected", "failed"]);
+}
+
+#[test]
+fn disabled_policy_never_reconnects() {
+    let server = start_dropping_server(Arc::new(Mutex::new(Vec::new())));
+    let mut config = server.get_config();
+    config.set_reconnect_policy(ReconnectPolicy::disabled());
+    let mut client = RedisClient::with_config(&config).unwrap();
+
+    assert!(client.get::<Option<String>, _>("key").is_err());
+    assert!(client.get::<Option<String>, _>("key").is_err());
+    assert_eq!(server.get_connection_nb(), 1);
+}
+
+#[test]
+fn async_client_reconnects() {
+    let server = start_dropping_server(Arc::new(Mutex::new(Vec::new())));
+    let mut config = server.get_config();
+    config.set_reconnect_policy(fast_policy(3));
+    let mut client = RedisClientAsync::with_config(&config).unwrap();
+
+    let (tx, rx) = channel();
+    let first_tx = tx.clone();
+    client.get("key", move |result| { let _ = first_tx.send(result); }).unwrap();
+    assert!(pump_until(|| client.pump().unwrap(), &rx, timeout()).is_err());
+
+    client.get("key", move |result| { let _ = tx.send(result); }).unwrap();
+    let result = pump_until(|| client.pump().unwrap(), &rx, timeout());
+    assert_eq!(result.unwrap().convert::<String>(), "value");
+}
+
+#[test]
+fn pubsub_client_resubscribes_after_reconnection() {
+    let subscribe_nb = Arc::new(AtomicUsize::new(0));
+    let server_subscribe_nb = subscribe_nb.clone();
+    let server = FakeServer::start(move |args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => {
+                let mut reply = b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n".to_vec();
+                // the second subscription comes from the reconnection, a message follows it
+                if server_subscribe_nb.fetch_add(1, Ordering::SeqCst) == 1 {
+                    reply.extend_from_slice(b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n");
+                }
+                Some(reply)
+            },
+            "PUBLISH" => None,
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut config = server.get_config();
+    config.set_reconnect_policy(fast_policy(3)).set_read_timeout(Some(Duration::from_millis(50)));
+    let mut client = PubSubClientAsync::with_config(&config).unwrap();
+
+    let (cmd_tx, cmd_rx) = channel();
+    let (msg_tx, msg_rx) = channel();
+    let subscribe_tx = cmd_tx.clone();
+    client.subscribe("news", move |result| { let _ = subscribe_tx.send(result); }, move |message| { let _ = msg_tx.send(message); }).unwrap();
+    assert!(pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).is_ok());
+
+    client.publish("news", "closing", move |result| { let _ = cmd_tx.send(result); }).unwrap();
+    assert!(pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).is_err());
+
+    let message = pump_until(|| client.pump().unwrap(), &msg_rx, timeout());
+    assert_eq!(message.get_payload(), b"hello");
+    assert!(wait_for(|| subscribe_nb.load(Ordering::SeqCst) == 2, timeout()));
+    assert_eq!(server.get_connection_nb(), 2);
+}
+
+#[test]
+fn pubsub_commands_fail_once_the_reconnection_is_refused() {
+    let client_nb = Arc::new(AtomicUsize::new(0));
+    let server = FakeServer::start(move |args| {
+        match command_name(args).as_str() {
+            "SUBSCRIBE" => Some(b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n".to_vec()),
+            // the connections opened after the first one are refused
+            "CLIENT" if client_nb.fetch_add(1, Ordering::SeqCst) > 0 => Some(b"-ERR refused\r\n".to_vec()),
+            "PUBLISH" => None,
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut config = server.get_config();
+    config.set_client_name("subscriber").set_reconnect_policy(fast_policy(2));
+    let mut client = PubSubClientAsync::with_config(&config).unwrap();
+
+    let (cmd_tx, cmd_rx) = channel();
+    let subscribe_tx = cmd_tx.clone();
+    client.subscribe("news", move |result| { let _ = subscribe_tx.send(result); }, |_message| {}).unwrap();
+    assert!(pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).is_ok());
+
+    let publish_tx = cmd_tx.clone();
+    client.publish("news", "closing", move |result| { let _ = publish_tx.send(result); }).unwrap();
+    // queued while the client tries to reconnect, or once it gave up
+    for _ in 0..3 {
+        let ping_tx = cmd_tx.clone();
+        client.ping(move |result| { let _ = ping_tx.send(result); }).unwrap();
+    }
+    for _ in 0..4 {
+        assert!(pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).is_err());
+    }
+
+    let ping_tx = cmd_tx.clone();
+    client.ping(move |result| { let _ = ping_tx.send(result); }).unwrap();
+    assert!(pump_until(|| client.pump().unwrap(), &cmd_rx, timeout()).is_err());
+}
@@ -0,0 +1,162 @@
+//! The tests `results` mod is checking if the responses are correctly converted into the requested types.
+
+extern crate redis_client;
+
+use redis_client::commands::CommandSender;
+use redis_client::errors::{RedisError, ServerError, ServerErrorKind};
+use redis_client::results::RedisResult;
+use redis_client::RedisClient;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use support::FakeServer;
+
+fn bytes(value: &str) -> RedisResult {
+    RedisResult::Bytes(value.as_bytes().to_vec())
+}
+
+/// This function checks that converting the value into T fails with a RedisError::Conversion.
+fn check_conversion_error<T: redis_client::FromRedisValue + std::fmt::Debug>(value: RedisResult) {
+    match value.try_convert::<T>() {
+        Err(RedisError::Conversion(_)) => {},
+        other => panic!("expected a conversion error, got {:?}", other),
+    }
+}
+
+#[test]
+fn integer_conversion_works() {
+    assert_eq!(RedisResult::Int(42).try_convert::<i64>().unwrap(), 42);
+    assert_eq!(RedisResult::Int(42).try_convert::<u16>().unwrap(), 42);
+    assert_eq!(bytes("-12").try_convert::<i32>().unwrap(), -12);
+    assert_eq!(RedisResult::Boolean(true).try_convert::<i64>().unwrap(), 1);
+}
+
+#[test]
+fn integer_conversion_fails() {
+    check_conversion_error::<i64>(bytes("abc"));
+    check_conversion_error::<i64>(RedisResult::Nil);
+    check_conversion_error::<i64>(RedisResult::Array(vec![RedisResult::Int(1)]));
+    check_conversion_error::<u32>(RedisResult::Int(-1));
+    check_conversion_error::<i8>(RedisResult::Int(300));
+}
+
+#[test]
+fn float_conversion_works() {
+    assert_eq!(bytes("2.5").try_convert::<f64>().unwrap(), 2.5);
+    assert_eq!(RedisResult::Double(2.5).try_convert::<f32>().unwrap(), 2.5);
+    assert_eq!(RedisResult::Int(3).try_convert::<f64>().unwrap(), 3.0);
+    assert_eq!(bytes("-inf").try_convert::<f64>().unwrap(), f64::NEG_INFINITY);
+    check_conversion_error::<f64>(bytes("abc"));
+}
+
+#[test]
+fn bool_conversion_works() {
+    assert!(RedisResult::Int(1).try_convert::<bool>().unwrap());
+    assert!(!RedisResult::Int(0).try_convert::<bool>().unwrap());
+    assert!(RedisResult::String("OK".to_string()).try_convert::<bool>().unwrap());
+    assert!(!RedisResult::Nil.try_convert::<bool>().unwrap());
+    check_conversion_error::<bool>(bytes("maybe"));
+}
+
+#[test]
+fn string_conversion_works() {
+    assert_eq!(bytes("value").try_convert::<String>().unwrap(), "value");
+    assert_eq!(RedisResult::Int(42).try_convert::<String>().unwrap(), "42");
+    check_conversion_error::<String>(RedisResult::Bytes(vec![0xff, 0xfe]));
+    check_conversion_error::<String>(RedisResult::Nil);
+}
+
+#[test]
+fn binary_conversion_works() {
+    assert_eq!(RedisResult::Bytes(vec![0xff, 0, 1]).try_convert::<Vec<u8>>().unwrap(), vec![0xff, 0, 1]);
+    check_conversion_error::<Vec<u8>>(RedisResult::Array(vec![]));
+}
+
+#[test]
+fn option_conversion_works() {
+    assert_eq!(RedisResult::Nil.try_convert::<Option<String>>().unwrap(), None);
+    assert_eq!(bytes("value").try_convert::<Option<String>>().unwrap(), Some("value".to_string()));
+    check_conversion_error::<Option<i64>>(bytes("abc"));
+}
+
+#[test]
+fn result_conversion_keeps_errors() {
+    assert_eq!(bytes("7").try_convert::<Result<i64, RedisError>>().unwrap().unwrap(), 7);
+    match RedisResult::Error(ServerError::new("ERR failed")).try_convert::<Result<i64, RedisError>>() {
+        Ok(Err(RedisError::Response(err))) => assert_eq!(err.to_string(), "ERR failed"),
+        other => panic!("expected the error of the response, got {:?}", other),
+    };
+}
+
+#[test]
+fn vec_conversion_works() {
+    let value = RedisResult::Array(vec![RedisResult::Int(1), bytes("2"), RedisResult::Int(3)]);
+    assert_eq!(value.try_convert::<Vec<i64>>().unwrap(), vec![1, 2, 3]);
+    assert_eq!(RedisResult::Nil.try_convert::<Vec<i64>>().unwrap(), Vec::<i64>::new());
+    check_conversion_error::<Vec<i64>>(RedisResult::Array(vec![RedisResult::Int(1), RedisResult::Nil]));
+    check_conversion_error::<Vec<i64>>(RedisResult::Int(1));
+}
+
+#[test]
+fn tuple_conversion_works() {
+    let value = RedisResult::Array(vec![bytes("key"), RedisResult::Int(3)]);
+    assert_eq!(value.try_convert::<(String, i64)>().unwrap(), ("key".to_string(), 3));
+    check_conversion_error::<(String, i64)>(RedisResult::Array(vec![bytes("key")]));
+}
+
+#[test]
+fn map_conversion_works() {
+    let array = RedisResult::Array(vec![bytes("a"), RedisResult::Int(1), bytes("b"), RedisResult::Int(2)]);
+    let map = array.clone().try_convert::<HashMap<String, i64>>().unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["a"], 1);
+    assert_eq!(map["b"], 2);
+
+    let btree_map = array.try_convert::<BTreeMap<String, i64>>().unwrap();
+    assert_eq!(btree_map.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+
+    let resp3_map = RedisResult::Map(vec![(bytes("a"), RedisResult::Double(1.5))]);
+    assert_eq!(resp3_map.try_convert::<HashMap<String, f64>>().unwrap()["a"], 1.5);
+
+    check_conversion_error::<HashMap<String, i64>>(RedisResult::Array(vec![bytes("a")]));
+    check_conversion_error::<HashMap<String, i64>>(bytes("a"));
+}
+
+#[test]
+fn set_conversion_works() {
+    let value = RedisResult::Set(vec![bytes("a"), bytes("b"), bytes("a")]);
+    let set = value.try_convert::<HashSet<String>>().unwrap();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains("a"));
+}
+
+#[test]
+fn attribute_conversion_works() {
+    let value = RedisResult::Attribute(Box::new(RedisResult::Int(7)), vec![]);
+    assert_eq!(value.try_convert::<i64>().unwrap(), 7);
+}
+
+#[test]
+fn error_conversion_fails_with_response_error() {
+    let value = RedisResult::Error(ServerError::new("WRONGTYPE Operation against a key"));
+    match value.clone().try_convert::<String>() {
+        Err(RedisError::Response(err)) => assert_eq!(err.kind, ServerErrorKind::WrongType),
+        other => panic!("expected a response error, got {:?}", other),
+    };
+    assert!(value.clone().try_convert::<()>().is_err());
+    assert!(RedisResult::Array(vec![RedisResult::Int(1), value]).try_convert::<Vec<i64>>().is_err());
+}
+
+#[test]
+fn command_sender_fails_on_type_mismatch() {
+    let server = FakeServer::start(|_args| Some(b"$3\r\nabc\r\n".to_vec()));
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let value: String = client.get("key").unwrap();
+    assert_eq!(value, "abc");
+
+    match client.get::<i64, _>("key") {
+        Err(RedisError::Conversion(err)) => assert_eq!(err.target, "i64"),
+        other => panic!("expected a conversion error, got {:?}", other),
+    };
+}
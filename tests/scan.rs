@@ -0,0 +1,96 @@
+//! The tests `scan` mod is checking how the scan iterators follow the cursor and convert the elements.
+
+extern crate redis_client;
+
+use redis_client::{RedisClient, RedisError, ScanOptions};
+
+use std::sync::{Arc, Mutex};
+
+use support::{command_name, FakeServer};
+
+/// Encode a page of a scan reply: the next cursor and the elements
+fn page(cursor: &str, elements: &[&str]) -> Vec<u8> {
+    let mut reply = format!("*2\r\n${}\r\n{}\r\n*{}\r\n", cursor.len(), cursor, elements.len()).into_bytes();
+    for element in elements {
+        reply.extend_from_slice(format!("${}\r\n{}\r\n", element.len(), element).as_bytes());
+    }
+    reply
+}
+
+/// Start a server replying to the scan commands with the pages of their collection, indexed by the cursor.
+/// It records the arguments of the commands it receives.
+fn start_scan_server() -> (FakeServer, Arc<Mutex<Vec<Vec<String>>>>) {
+    let commands = Arc::new(Mutex::new(Vec::new()));
+    let server_commands = commands.clone();
+    let server = FakeServer::start(move |args| {
+        server_commands.lock().unwrap().push(args.iter().map(|arg| String::from_utf8_lossy(arg).into_owned()).collect());
+        let name = command_name(args);
+        let cursor = String::from_utf8_lossy(&args[if name == "SCAN" { 1 } else { 2 }]).into_owned();
+        match (name.as_str(), cursor.as_str()) {
+            ("HSCAN", _) if args[1] == b"odd" => Some(page("0", &["visits", "12", "likes"])),
+            ("SCAN", "0") => Some(page("2", &["user:1", "user:2"])),
+            ("SCAN", "2") => Some(page("7", &[])),
+            ("SCAN", "7") => Some(page("0", &["user:3"])),
+            ("HSCAN", "0") => Some(page("4", &["visits", "12", "likes", "3"])),
+            ("HSCAN", "4") => Some(page("0", &["shares", "1"])),
+            ("ZSCAN", "0") => Some(page("0", &["alice", "12.5", "bob", "inf"])),
+            ("SSCAN", _) => Some(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n".to_vec()),
+            _ => Some(b"-ERR invalid cursor\r\n".to_vec()),
+        }
+    });
+    (server, commands)
+}
+
+#[test]
+fn scan_iter_follows_the_cursor() {
+    let (server, commands) = start_scan_server();
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let keys: Vec<String> = client.scan_iter(ScanOptions::new().set_pattern("user:*").set_type("string"))
+        .collect::<Result<_, _>>().unwrap();
+    assert_eq!(keys, vec!["user:1", "user:2", "user:3"]);
+
+    let commands = commands.lock().unwrap();
+    let cursors: Vec<&str> = commands.iter().map(|args| &args[1][..]).collect();
+    assert_eq!(cursors, vec!["0", "2", "7"]);
+    assert_eq!(commands[0], vec!["SCAN", "0", "MATCH", "user:*", "TYPE", "string"]);
+}
+
+#[test]
+fn hscan_and_zscan_iter_return_pairs() {
+    let (server, commands) = start_scan_server();
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let fields: Vec<(String, i64)> = client.hscan_iter("stats", ScanOptions::new().set_count(2))
+        .collect::<Result<_, _>>().unwrap();
+    assert_eq!(fields, vec![("visits".to_string(), 12), ("likes".to_string(), 3), ("shares".to_string(), 1)]);
+    assert_eq!(commands.lock().unwrap()[1], vec!["HSCAN", "stats", "4", "COUNT", "2"]);
+
+    let mut scores = client.zscan_iter::<(String, f64), _>("leaderboard", &ScanOptions::new());
+    assert_eq!(scores.next().unwrap().unwrap(), ("alice".to_string(), 12.5));
+    assert_eq!(scores.next().unwrap().unwrap(), ("bob".to_string(), f64::INFINITY));
+    assert!(scores.next().is_none());
+}
+
+#[test]
+fn scan_iter_ends_on_error() {
+    let (server, commands) = start_scan_server();
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let mut members = client.sscan_iter::<String, _>("stats", &ScanOptions::new());
+    assert!(members.next().unwrap().is_err());
+    assert!(members.next().is_none());
+    assert_eq!(commands.lock().unwrap().len(), 1);
+
+    let mut keys = client.scan_iter::<i64>(&ScanOptions::new());
+    // a key which cannot be converted doesn't end the iteration
+    assert!(keys.next().unwrap().is_err());
+    assert_eq!(keys.count(), 2);
+
+    let mut fields = client.hscan_iter::<(String, i64), _>("odd", &ScanOptions::new());
+    match fields.next() {
+        Some(Err(RedisError::Conversion(err))) => assert_eq!(err.value.try_convert::<Vec<String>>().unwrap(), vec!["visits", "12", "likes"]),
+        other => panic!("the odd page should fail, got {:?}", other),
+    };
+    assert!(fields.next().is_none());
+}
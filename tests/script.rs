@@ -0,0 +1,101 @@
+//! The tests `script` mod is checking how the scripts are invoked by their SHA1 and loaded when redis doesn't know them.
+
+extern crate redis_client;
+
+use redis_client::commands::CommandSender;
+use redis_client::{Pipeline, RedisClient, Script};
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use support::{command_name, FakeServer};
+
+const SCRIPT: &str = "return redis.call('INCRBY', KEYS[1], ARGV[1])";
+
+/// Start a server with a script cache, running any script as `INCRBY` of its first key by its first argument from 10.
+/// It records the commands it receives.
+fn start_script_server() -> (FakeServer, Arc<Mutex<Vec<String>>>) {
+    let cache = Mutex::new(HashSet::new());
+    let commands = Arc::new(Mutex::new(Vec::new()));
+    let server_commands = commands.clone();
+    let server = FakeServer::start(move |args| {
+        let name = command_name(args);
+        let run = |args: &[Vec<u8>]| Some(format!(":{}\r\n", 10 + String::from_utf8_lossy(&args[4]).parse::<i64>().unwrap()).into_bytes());
+        let mut cache = cache.lock().unwrap();
+        let subcommand = args.get(1).map(|arg| String::from_utf8_lossy(arg).to_uppercase()).unwrap_or_default();
+        match name.as_str() {
+            "SCRIPT" => server_commands.lock().unwrap().push(format!("SCRIPT {}", subcommand)),
+            _ => server_commands.lock().unwrap().push(name.clone()),
+        };
+        match (name.as_str(), subcommand.as_str()) {
+            ("EVAL", _) => {
+                cache.insert(Script::new(&String::from_utf8_lossy(&args[1])).get_hash().to_string());
+                run(args)
+            },
+            ("EVALSHA", _) if cache.contains(&*String::from_utf8_lossy(&args[1])) => run(args),
+            ("EVALSHA", _) => Some(b"-NOSCRIPT No matching script. Please use EVAL.\r\n".to_vec()),
+            ("SCRIPT", "LOAD") => {
+                let hash = Script::new(&String::from_utf8_lossy(&args[2])).get_hash().to_string();
+                cache.insert(hash.clone());
+                Some(format!("$40\r\n{}\r\n", hash).into_bytes())
+            },
+            ("SCRIPT", "EXISTS") => {
+                let mut reply = format!("*{}\r\n", args.len() - 2).into_bytes();
+                for hash in &args[2..] {
+                    reply.extend_from_slice(if cache.contains(&*String::from_utf8_lossy(hash)) { b":1\r\n" } else { b":0\r\n" });
+                }
+                Some(reply)
+            },
+            ("SCRIPT", "FLUSH") => {
+                cache.clear();
+                Some(b"+OK\r\n".to_vec())
+            },
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    (server, commands)
+}
+
+#[test]
+fn script_hash_is_computed_locally() {
+    let script = Script::new("return 1");
+    assert_eq!(script.get_hash(), "e0e1f9fabfc9d4800c877a703b823ac0578ff8db");
+    assert_eq!(script.get_code(), "return 1");
+}
+
+#[test]
+fn script_is_sent_again_on_noscript() {
+    let (server, commands) = start_script_server();
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+    let script = Script::new(SCRIPT);
+
+    assert_eq!(script.invoke::<i64, _, _, _>(&mut client, "counter", 5).unwrap(), 15);
+    assert_eq!(script.invoke::<i64, _, _, _>(&mut client, "counter", 6).unwrap(), 16);
+    assert_eq!(*commands.lock().unwrap(), vec!["EVALSHA", "EVAL", "EVALSHA"]);
+
+    client.script_flush::<String>().unwrap();
+    commands.lock().unwrap().clear();
+    script.load(&mut client).unwrap();
+    assert_eq!(script.invoke::<i64, _, _, _>(&mut client, "counter", 1).unwrap(), 11);
+    assert_eq!(*commands.lock().unwrap(), vec!["SCRIPT LOAD", "EVALSHA"]);
+}
+
+#[test]
+fn pipeline_loads_the_missing_scripts() {
+    let (server, commands) = start_script_server();
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+    let script = Script::new(SCRIPT);
+
+    let results: (String, i64, i64) = Pipeline::new()
+        .set("counter", 10)
+        .invoke_script(&script, "counter", 1)
+        .invoke_script(&script, "counter", 2)
+        .query(&mut client).unwrap();
+    assert_eq!(results, ("OK".to_string(), 11, 12));
+    assert_eq!(*commands.lock().unwrap(), vec!["SCRIPT EXISTS", "SCRIPT LOAD", "SET", "EVALSHA", "EVALSHA"]);
+
+    commands.lock().unwrap().clear();
+    let results: Vec<i64> = Pipeline::new().invoke_script(&script, "counter", 3).query(&mut client).unwrap();
+    assert_eq!(results, vec![13]);
+    assert_eq!(*commands.lock().unwrap(), vec!["SCRIPT EXISTS", "EVALSHA"]);
+}
@@ -0,0 +1,118 @@
+//! The tests `sentinel` mod is checking how the SentinelClient finds the master, follows its failovers and reaches the replicas.
+
+extern crate redis_client;
+
+use redis_client::commands::{CommandSender, CommandSenderAsync};
+use redis_client::{ConnectionConfig, PoolConfig, SentinelClient};
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::time::Duration;
+
+use support::{command_name, pump_until, wait_for, FakeServer};
+
+fn timeout() -> Duration {
+    Duration::new(5, 0)
+}
+
+fn bulk(value: &str) -> String {
+    format!("${}\r\n{}\r\n", value.len(), value)
+}
+
+/// A redis node answering ROLE with its role and GET with its name
+fn start_node(role: &'static str, name: &'static str) -> FakeServer {
+    FakeServer::start(move |args| {
+        match command_name(args).as_str() {
+            "ROLE" => Some(format!("*3\r\n{}:0\r\n*0\r\n", bulk(role)).into_bytes()),
+            "GET" => Some(bulk(name).into_bytes()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    })
+}
+
+/// A sentinel monitoring mymaster, whose master listens on the port. SUBSCRIBE sets the subscribed flag.
+fn start_sentinel(master_port: Arc<AtomicUsize>, replicas: String, subscribed: Arc<AtomicBool>) -> FakeServer {
+    FakeServer::start(move |args| {
+        match command_name(args).as_str() {
+            "SENTINEL" => match (String::from_utf8_lossy(&args[1]).to_lowercase().as_str(), &args[2][..]) {
+                ("get-master-addr-by-name", b"mymaster") => {
+                    Some(format!("*2\r\n{}{}", bulk("127.0.0.1"), bulk(&master_port.load(Ordering::SeqCst).to_string())).into_bytes())
+                },
+                ("get-master-addr-by-name", _) => Some(b"*-1\r\n".to_vec()),
+                _ => Some(replicas.clone().into_bytes()),
+            },
+            "SUBSCRIBE" => {
+                subscribed.store(true, Ordering::SeqCst);
+                Some(format!("*3\r\n{}{}:1\r\n", bulk("subscribe"), bulk("+switch-master")).into_bytes())
+            },
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    })
+}
+
+/// Build a SENTINEL REPLICAS response from the ports and the flags of the replicas
+fn replicas_reply(replicas: &[(u16, &str)]) -> String {
+    let mut reply = format!("*{}\r\n", replicas.len());
+    for &(port, flags) in replicas {
+        reply += &format!("*6\r\n{}{}{}{}{}{}", bulk("ip"), bulk("127.0.0.1"), bulk("port"), bulk(&port.to_string()), bulk("flags"), bulk(flags));
+    }
+    reply
+}
+
+#[test]
+fn master_is_resolved_and_checked_with_role() {
+    let stale_master = start_node("slave", "stale");
+    let master = start_node("master", "master");
+    let no_subscription = Arc::new(AtomicBool::new(false));
+    let stale_sentinel = start_sentinel(Arc::new(AtomicUsize::new(stale_master.get_port() as usize)), replicas_reply(&[]), no_subscription.clone());
+    let sentinel = start_sentinel(Arc::new(AtomicUsize::new(master.get_port() as usize)), replicas_reply(&[]), no_subscription);
+
+    let client = SentinelClient::with_configs(&[stale_sentinel.get_config(), sentinel.get_config()], "mymaster", &ConnectionConfig::new("127.0.0.1", 6379)).unwrap();
+    assert_eq!(client.get_master(), ("127.0.0.1".to_string(), master.get_port()));
+    assert_eq!(client.get_client().unwrap().get::<String, _>("key").unwrap(), "master");
+
+    assert!(SentinelClient::new(&[format!("redis://127.0.0.1:{}", sentinel.get_port())], "unknown").is_err());
+}
+
+#[test]
+fn clients_follow_the_switch_master_messages() {
+    let old_master = start_node("master", "old");
+    let new_master = start_node("master", "new");
+    let master_port = Arc::new(AtomicUsize::new(old_master.get_port() as usize));
+    let subscribed = Arc::new(AtomicBool::new(false));
+    let sentinel_server = start_sentinel(master_port.clone(), replicas_reply(&[]), subscribed.clone());
+
+    let sentinel = SentinelClient::new(&[format!("redis://127.0.0.1:{}", sentinel_server.get_port())], "mymaster").unwrap();
+    let mut client = sentinel.get_client().unwrap();
+    let mut async_client = sentinel.get_async_client().unwrap();
+    let pool = sentinel.get_pool(&PoolConfig::new()).unwrap();
+    assert_eq!(client.get::<String, _>("key").unwrap(), "old");
+    assert_eq!(pool.get().unwrap().get::<String, _>("key").unwrap(), "old");
+
+    assert!(wait_for(|| subscribed.load(Ordering::SeqCst), timeout()));
+    master_port.store(new_master.get_port() as usize, Ordering::SeqCst);
+    let payload = format!("mymaster 127.0.0.1 {} 127.0.0.1 {}", old_master.get_port(), new_master.get_port());
+    sentinel_server.push(format!("*3\r\n{}{}{}", bulk("message"), bulk("+switch-master"), bulk(&payload)).as_bytes());
+    assert!(wait_for(|| sentinel.get_master().1 == new_master.get_port(), timeout()));
+
+    assert_eq!(client.get::<String, _>("key").unwrap(), "new");
+    assert_eq!(pool.get().unwrap().get::<String, _>("key").unwrap(), "new");
+    let (tx, rx) = channel();
+    async_client.get("key", move |result| { let _ = tx.send(result); }).unwrap();
+    assert_eq!(pump_until(|| async_client.pump().unwrap(), &rx, timeout()).unwrap().convert::<String>(), "new");
+}
+
+#[test]
+fn replica_clients_skip_the_replicas_down() {
+    let master = start_node("master", "master");
+    let replica = start_node("slave", "replica");
+    let replicas = replicas_reply(&[(1, "slave,s_down"), (replica.get_port(), "slave")]);
+    let sentinel_server = start_sentinel(Arc::new(AtomicUsize::new(master.get_port() as usize)), replicas, Arc::new(AtomicBool::new(false)));
+
+    let sentinel = SentinelClient::new(&[format!("redis://127.0.0.1:{}", sentinel_server.get_port())], "mymaster").unwrap();
+    assert_eq!(sentinel.get_replicas().unwrap(), vec![("127.0.0.1".to_string(), replica.get_port())]);
+    for _ in 0..2 {
+        assert_eq!(sentinel.get_replica_client().unwrap().get::<String, _>("key").unwrap(), "replica");
+    }
+}
@@ -0,0 +1,165 @@
+//! The `support` mod provides a fake redis server so the clients can be tested without a running redis.
+
+#![allow(dead_code)]
+
+use redis_client::ConnectionConfig;
+use redis_client::reader::Decoder;
+use redis_client::results::RedisResult;
+
+use std::io::prelude::*;
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The handler of a fake server. It receives the arguments of each command and returns the bytes to reply with,
+/// or None to close the connection.
+pub type Handler = Arc<dyn Fn(&[Vec<u8>]) -> Option<Vec<u8>> + Send + Sync>;
+
+/// A fake redis server listening on a random local port. Each connection is served in its own thread.
+pub struct FakeServer {
+    port: u16,
+    connection_nb: Arc<AtomicUsize>,
+    closed_nb: Arc<AtomicUsize>,
+    streams: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl FakeServer {
+    /// Start a server answering every command with the handler.
+    ///
+    /// # Examples
+    ///
+    /// ``` rust
+    /// let server = FakeServer::start(|_args| Some(b"+OK\r\n".to_vec()));
+    /// ```
+    pub fn start<F>(handler: F) -> FakeServer where F: Fn(&[Vec<u8>]) -> Option<Vec<u8>> + Send + Sync + 'static {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handler: Handler = Arc::new(handler);
+        let connection_nb = Arc::new(AtomicUsize::new(0));
+        let accepted_nb = connection_nb.clone();
+        let closed_nb = Arc::new(AtomicUsize::new(0));
+        let served_closed_nb = closed_nb.clone();
+        let streams = Arc::new(Mutex::new(Vec::new()));
+        let accepted_streams = streams.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        accepted_nb.fetch_add(1, Ordering::SeqCst);
+                        let pushed_stream = stream.try_clone();
+                        if let Ok(ref pushed_stream) = pushed_stream {
+                            if let Ok(clone) = pushed_stream.try_clone() {
+                                accepted_streams.lock().unwrap().push(clone);
+                            }
+                        }
+                        let handler = handler.clone();
+                        let served_closed_nb = served_closed_nb.clone();
+                        thread::spawn(move || {
+                            serve(stream, handler);
+                            // the clone kept for the pushes would leave the socket opened
+                            if let Ok(pushed_stream) = pushed_stream {
+                                let _ = pushed_stream.shutdown(Shutdown::Both);
+                            }
+                            served_closed_nb.fetch_add(1, Ordering::SeqCst);
+                        });
+                    },
+                    Err(_) => return,
+                };
+            }
+        });
+
+        FakeServer { port, connection_nb, closed_nb, streams }
+    }
+
+    pub fn get_host(&self) -> &'static str {
+        "127.0.0.1"
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    /// Get the number of connections accepted so far
+    pub fn get_connection_nb(&self) -> usize {
+        self.connection_nb.load(Ordering::SeqCst)
+    }
+
+    /// Get the number of connections closed so far, by the client or by the handler
+    pub fn get_closed_nb(&self) -> usize {
+        self.closed_nb.load(Ordering::SeqCst)
+    }
+
+    /// Write the bytes on every connection still opened, like the messages redis pushes to the subscribers
+    pub fn push(&self, bytes: &[u8]) {
+        for mut stream in self.streams.lock().unwrap().iter() {
+            let _ = stream.write_all(bytes);
+        }
+    }
+
+    /// Get a configuration connecting to the server
+    pub fn get_config(&self) -> ConnectionConfig {
+        ConnectionConfig::new(self.get_host(), self.get_port())
+    }
+}
+
+/// Read the commands sent on the connection and write the replies of the handler
+fn serve(mut stream: TcpStream, handler: Handler) {
+    let mut decoder = Decoder::new();
+    let mut buffer = [0; 4096];
+    loop {
+        let read = match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => return,
+            Ok(read) => read,
+        };
+        decoder.feed(&buffer[..read]);
+
+        while let Ok(Some(command)) = decoder.decode() {
+            let args: Vec<Vec<u8>> = match command {
+                RedisResult::Array(args) => args.into_iter().map(|arg| arg.convert::<Vec<u8>>()).collect(),
+                _ => return,
+            };
+            match handler(&args) {
+                Some(reply) => {
+                    if stream.write_all(&reply).is_err() {
+                        return;
+                    }
+                },
+                None => return,
+            };
+        }
+    }
+}
+
+/// Get the name of a command in upper case
+pub fn command_name(args: &[Vec<u8>]) -> String {
+    args.first().map(|name| String::from_utf8_lossy(name).to_uppercase()).unwrap_or_default()
+}
+
+/// Call pump until the receiver gets a value or the timeout expires
+pub fn pump_until<T, P>(mut pump: P, receiver: &Receiver<T>, timeout: Duration) -> T where P: FnMut() {
+    let start = Instant::now();
+    loop {
+        pump();
+        if let Ok(value) = receiver.try_recv() {
+            return value;
+        }
+        assert!(start.elapsed() < timeout, "nothing was received before the timeout");
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Wait until the condition is true, returning false if it is still false once the timeout expired
+pub fn wait_for<C>(condition: C, timeout: Duration) -> bool where C: Fn() -> bool {
+    let start = Instant::now();
+    while !condition() {
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    true
+}
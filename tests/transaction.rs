@@ -0,0 +1,145 @@
+//! The tests `transaction` mod is checking the optimistic locking of the transactions.
+
+extern crate redis_client;
+
+use redis_client::commands::{CommandBuilder, CommandExecutor, CommandSender, TRANSACTION_MAX_ATTEMPTS};
+use redis_client::errors::{RedisError, ServerErrorKind};
+use redis_client::RedisClient;
+
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use support::{command_name, FakeServer};
+
+/// Start a server recording the commands it receives. EXEC returns nil the first `conflict_nb` times.
+fn start_transaction_server(conflict_nb: usize) -> (FakeServer, Arc<Mutex<Vec<String>>>) {
+    let commands = Arc::new(Mutex::new(Vec::new()));
+    let server_commands = commands.clone();
+    let exec_nb = AtomicUsize::new(0);
+    let server = FakeServer::start(move |args| {
+        let name = command_name(args);
+        server_commands.lock().unwrap().push(name.clone());
+        match name.as_str() {
+            "GET" => Some(b"$2\r\n10\r\n".to_vec()),
+            "SET" | "INCRBY" => Some(b"+QUEUED\r\n".to_vec()),
+            "BADCMD" => Some(b"-ERR unknown command 'BADCMD'\r\n".to_vec()),
+            "EXEC" if exec_nb.fetch_add(1, Ordering::SeqCst) < conflict_nb => Some(b"*-1\r\n".to_vec()),
+            "EXEC" if server_commands.lock().unwrap().contains(&"BADCMD".to_string()) => {
+                Some(b"-EXECABORT Transaction discarded because of previous errors.\r\n".to_vec())
+            },
+            "EXEC" => Some(b"*2\r\n+OK\r\n:21\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    (server, commands)
+}
+
+#[test]
+fn transaction_returns_typed_exec_results() {
+    let (server, commands) = start_transaction_server(0);
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let (status, counter): (String, i64) = client.transaction(["counter"], |client, cmd| {
+        let counter: i64 = client.get("counter")?;
+        cmd.set("copy", counter).add_cmd("INCRBY").add_arg("counter").add_arg(11).end();
+        Ok(())
+    }).unwrap();
+
+    assert_eq!((status.as_str(), counter), ("OK", 21));
+    assert_eq!(*commands.lock().unwrap(), vec!["WATCH", "GET", "MULTI", "SET", "INCRBY", "EXEC"]);
+}
+
+#[test]
+fn transaction_is_retried_on_watch_conflict() {
+    let (server, commands) = start_transaction_server(2);
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let mut run_nb = 0;
+    let results: Vec<String> = client.transaction("counter", |_client, cmd| {
+        run_nb += 1;
+        cmd.set("counter", run_nb);
+        Ok(())
+    }).unwrap();
+
+    assert_eq!(results, vec!["OK", "21"]);
+    assert_eq!(run_nb, 3);
+    assert_eq!(commands.lock().unwrap().iter().filter(|name| *name == "WATCH").count(), 3);
+}
+
+#[test]
+fn transaction_surfaces_execabort() {
+    let (server, _commands) = start_transaction_server(0);
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let result: Result<Vec<String>, RedisError> = client.transaction("counter", |_client, cmd| {
+        cmd.add_cmd("BADCMD").end();
+        Ok(())
+    });
+
+    match result {
+        Err(RedisError::Response(err)) => assert_eq!(err.kind, ServerErrorKind::ExecAbort),
+        other => panic!("the transaction should be aborted, got {:?}", other),
+    };
+}
+
+#[test]
+fn transaction_closure_error_unwatches_the_keys() {
+    let (server, commands) = start_transaction_server(0);
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let result: Result<Vec<String>, RedisError> = client.transaction("counter", |client, _cmd| {
+        let _: String = client.get("counter")?;
+        Err(RedisError::Io(io::Error::other("the counter is too low")))
+    });
+
+    assert!(result.is_err());
+    assert_eq!(*commands.lock().unwrap(), vec!["WATCH", "GET", "UNWATCH"]);
+}
+
+#[test]
+fn transaction_gives_up_when_the_keys_keep_changing() {
+    let (server, commands) = start_transaction_server(usize::MAX);
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let result: Result<Vec<String>, RedisError> = client.transaction("counter", |_client, cmd| {
+        cmd.set("counter", 1);
+        Ok(())
+    });
+
+    match result {
+        Err(RedisError::TransactionAborted(_)) => (),
+        other => panic!("the transaction should give up, got {:?}", other),
+    };
+    assert_eq!(commands.lock().unwrap().iter().filter(|name| *name == "EXEC").count(), TRANSACTION_MAX_ATTEMPTS);
+}
+
+#[test]
+fn transaction_fails_when_the_client_reconnected_after_watch() {
+    let commands = Arc::new(Mutex::new(Vec::new()));
+    let server_commands = commands.clone();
+    let get_nb = AtomicUsize::new(0);
+    let server = FakeServer::start(move |args| {
+        let name = command_name(args);
+        server_commands.lock().unwrap().push(name.clone());
+        match name.as_str() {
+            "GET" if get_nb.fetch_add(1, Ordering::SeqCst) == 0 => None,
+            "GET" => Some(b"$2\r\n10\r\n".to_vec()),
+            _ => Some(b"+OK\r\n".to_vec()),
+        }
+    });
+    let mut client = RedisClient::new(server.get_host(), server.get_port()).unwrap();
+
+    let result: Result<Vec<String>, RedisError> = client.transaction("counter", |client, cmd| {
+        let counter: i64 = client.get("counter").or_else(|_| client.get("counter"))?;
+        cmd.set("counter", counter + 1);
+        Ok(())
+    });
+
+    match result {
+        Err(RedisError::TransactionAborted(_)) => (),
+        other => panic!("the transaction should be aborted, got {:?}", other),
+    };
+    assert_eq!(server.get_connection_nb(), 2);
+    assert_eq!(*commands.lock().unwrap(), vec!["WATCH", "GET", "GET"]);
+}